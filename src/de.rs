@@ -0,0 +1,367 @@
+use std::str::FromStr;
+
+use serde::{
+    Deserializer,
+    de::{
+        self, DeserializeOwned, IntoDeserializer, Visitor,
+        value::{MapDeserializer, SeqDeserializer},
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{
+    error::{Error, SerdeError},
+    parser::models::{ContentEntry, DesktopFile, Group},
+};
+
+impl de::Error for SerdeError {
+    fn custom<T: std::fmt::Display>(message: T) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<Error> for SerdeError {
+    fn from(error: Error) -> Self {
+        let message = match error {
+            Error::InvalidValue(message) | Error::NotFound(message) => message,
+            error => format!("{error:?}"),
+        };
+        Self { message }
+    }
+}
+
+/// Deserializes a key-file into a user type, whose fields are the groups of the file.
+///
+/// A group is deserialized as a struct or a map whose fields are the keys of the group,
+/// and localized keys are written with their locale, such as `Name[fr]`.
+/// Values are deserialized as the specification defines them: escape sequences are decoded,
+/// booleans are either `true` or `false`, and lists are sequences of their values.
+/// Only the first group with a header and the first entry with a key and a locale are read, as in [crate::parser::models::EntrySet::find].
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct File {
+///     #[serde(rename = "Desktop Entry")]
+///     entry: Entry,
+/// }
+///
+/// #[derive(Deserialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Entry {
+///     name: String,
+///     #[serde(rename = "Name[fr]")]
+///     name_fr: Option<String>,
+///     terminal: bool,
+///     categories: Vec<String>,
+/// }
+///
+/// let file: File = freedesktop_rs::from_str(
+///     "[Desktop Entry]\nName=App\nName[fr]=Appli\nTerminal=false\nCategories=Utility;Development;\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(file.entry.name_fr.as_deref(), Some("Appli"));
+/// assert_eq!(file.entry.categories, ["Utility", "Development"]);
+/// ```
+///
+/// Fails with the line of the first syntax error if the input is not a valid key-file.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, SerdeError> {
+    let file = DesktopFile::parse_strict(input.as_bytes())?;
+    from_file(&file)
+}
+
+/// Deserializes a parsed key-file into a user type, see [from_str].
+pub fn from_file<T: DeserializeOwned>(file: &DesktopFile) -> Result<T, SerdeError> {
+    T::deserialize(FileDeserializer(file))
+}
+
+/// Keeps the first item of each name.
+fn first_of_each<'a, T>(items: impl Iterator<Item = (String, &'a T)>) -> Vec<(String, &'a T)> {
+    let mut first: Vec<(String, &T)> = vec![];
+    for (name, item) in items {
+        if !first.iter().any(|(n, _)| *n == name) {
+            first.push((name, item));
+        }
+    }
+    first
+}
+
+struct FileDeserializer<'a>(&'a DesktopFile);
+
+impl<'de> Deserializer<'de> for FileDeserializer<'_> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let groups = first_of_each(self.0.groups().map(|g| (g.header.clone(), g)));
+        visitor.visit_map(MapDeserializer::new(
+            groups
+                .into_iter()
+                .map(|(header, group)| (header, GroupDeserializer(group))),
+        ))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct GroupDeserializer<'a>(&'a Group);
+
+impl<'de> IntoDeserializer<'de, SerdeError> for GroupDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for GroupDeserializer<'_> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let entries = first_of_each(self.0.entries().map(|entry| {
+            let key = match &entry.locale {
+                Some(locale) => format!("{}[{locale}]", entry.key),
+                None => entry.key.clone(),
+            };
+            (key, entry)
+        }));
+        visitor.visit_map(MapDeserializer::new(
+            entries
+                .into_iter()
+                .map(|(key, entry)| (key, ValueDeserializer(entry))),
+        ))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct ValueDeserializer<'a>(&'a ContentEntry);
+
+impl ValueDeserializer<'_> {
+    /// Returns the values with their escape sequences decoded.
+    fn values(&self) -> Vec<String> {
+        self.0.decoded_values()
+    }
+
+    /// Returns the single value with its escape sequences decoded.
+    fn value(&self) -> Result<String, SerdeError> {
+        Ok(self.0.decoded_string()?.into_owned())
+    }
+
+    fn parse<T: FromStr>(&self, expected: &str) -> Result<T, SerdeError> {
+        let value = self.value()?;
+        value.parse().map_err(|_| SerdeError {
+            message: format!("{}={value} is not {expected}", self.0.key),
+        })
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for ValueDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident, $expected:literal;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                visitor.$visit(self.parse($expected)?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = SerdeError;
+
+    /// Deserializes a list if the values are followed by a separator or if there are several of them, or else a string.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0.values.len() == 1 && !self.0.format.trailing_separator {
+            true => self.deserialize_string(visitor),
+            false => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_bool(self.0.as_bool()?)
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8, "an integer";
+        deserialize_i16 => visit_i16, "an integer";
+        deserialize_i32 => visit_i32, "an integer";
+        deserialize_i64 => visit_i64, "an integer";
+        deserialize_i128 => visit_i128, "an integer";
+        deserialize_u8 => visit_u8, "a positive integer";
+        deserialize_u16 => visit_u16, "a positive integer";
+        deserialize_u32 => visit_u32, "a positive integer";
+        deserialize_u64 => visit_u64, "a positive integer";
+        deserialize_u128 => visit_u128, "a positive integer";
+        deserialize_f32 => visit_f32, "a number";
+        deserialize_f64 => visit_f64, "a number";
+        deserialize_char => visit_char, "a character";
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_string(self.value()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_string(self.value()?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_seq(SeqDeserializer::new(self.values().into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// Deserializes a unit variant from the value, such as `Application` for `Type`.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_enum(self.value()?.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Type {
+        Application,
+        Link,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Entry {
+        #[serde(rename = "Type")]
+        kind: Type,
+        name: String,
+        comment: Option<String>,
+        no_display: Option<bool>,
+        #[serde(rename = "X-Priority")]
+        priority: u8,
+        keywords: Vec<String>,
+        #[serde(flatten)]
+        others: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct File {
+        #[serde(rename = "Desktop Entry")]
+        entry: Entry,
+        #[serde(rename = "Desktop Action new")]
+        action: Option<BTreeMap<String, String>>,
+    }
+
+    #[test]
+    fn test_from_str() {
+        let file: File = from_str(
+            "# Comment
+[Desktop Entry]
+Type=Application
+Name=App\\sA
+Name=Ignored
+Name[fr]=Appli
+X-Priority=2
+Keywords=One;Two\\;Three;
+
+[Desktop Action new]
+Exec=app --new
+",
+        )
+        .unwrap();
+
+        assert_eq!(file.entry.kind, Type::Application);
+        assert_eq!(file.entry.name, "App A");
+        assert_eq!(file.entry.comment, None);
+        assert_eq!(file.entry.priority, 2);
+        assert_eq!(file.entry.keywords, ["One", "Two;Three"]);
+        assert_eq!(
+            file.entry.others,
+            BTreeMap::from([(String::from("Name[fr]"), String::from("Appli"))])
+        );
+        assert_eq!(file.action.unwrap()["Exec"], "app --new");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |input: &str| from_str::<File>(input).unwrap_err().message;
+
+        assert_eq!(
+            error("[Desktop Entry]\nName"),
+            "line 2: missing `=` between the key and the value"
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nType=Application\nName=App\nX-Priority=high\nKeywords=\n"),
+            "X-Priority=high is not a positive integer"
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nType=Application\nName=App\nNoDisplay=yes\n"),
+            "NoDisplay=yes is not a boolean"
+        );
+        assert_eq!(error("[Other]\n"), "missing field `Desktop Entry`");
+    }
+}
@@ -1,3 +1,8 @@
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Display;
+
+use crate::helpers::desktop_entry::exec::FieldCode;
+
 /// This crate's errors
 #[derive(Debug)]
 pub enum Error {
@@ -5,10 +10,200 @@ pub enum Error {
     NotAscii(char),
     /// The string was not found.
     NotFound(String),
+    /// The value of the key could not be converted to the requested type.
+    InvalidValue(String),
+    /// The input could not be read.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The input could not be parsed.
+    Parsing(nom::Err<nom::error::Error<Vec<u8>>>),
+    /// The hash of the file is not the expected one, so it was modified.
+    #[cfg(feature = "verify")]
+    Tampered {
+        /// The expected hash, in lowercase hexadecimal.
+        expected: String,
+        /// The hash of the file, in lowercase hexadecimal.
+        actual: String,
+    },
     /// The date could not be parsed.
     #[cfg(feature = "trash")]
     DateParsing(time::error::Parse),
     /// The date could not be formated.
     #[cfg(feature = "trash")]
     DateFormat(time::error::Format),
+    /// The operation was cancelled.
+    #[cfg(feature = "trash")]
+    Cancelled,
+}
+
+/// A syntax error found while parsing, with its position.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ParseError {
+    /// The kind of error.
+    pub kind: ParseErrorKind,
+    /// The number of the line, starting at 1.
+    pub line: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Why a line could not be parsed.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ParseErrorKind {
+    /// The line is neither a group header, a comment, nor a key-value entry.
+    MissingEquals,
+    /// The group header has no closing bracket.
+    UnterminatedGroupHeader,
+    /// The group header contains brackets.
+    InvalidGroupHeader,
+    /// The key contains characters other than alphanumerics and dashes, or is empty.
+    InvalidKeyCharacter,
+    /// The locale of the key is malformed.
+    InvalidLocale(LocaleError),
+    /// The value contains an unknown escape sequence.
+    InvalidEscape,
+    /// The line is not valid UTF-8.
+    InvalidUtf8,
+    /// The key-value entry is not part of a group.
+    EntryOutsideGroup,
+    /// The line could not be parsed for another reason, e.g. because a previous line could not be parsed.
+    Unexpected,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let message = match self {
+            ParseErrorKind::MissingEquals => "missing `=` between the key and the value",
+            ParseErrorKind::UnterminatedGroupHeader => "missing `]` at the end of the group header",
+            ParseErrorKind::InvalidGroupHeader => "group headers cannot contain `[` or `]`",
+            ParseErrorKind::InvalidKeyCharacter => {
+                "keys can only contain alphanumeric characters and `-`"
+            }
+            ParseErrorKind::InvalidLocale(error) => {
+                return write!(
+                    f,
+                    "malformed locale, expected `lang_COUNTRY.ENCODING@MODIFIER`: {error}"
+                );
+            }
+            ParseErrorKind::InvalidEscape => "unknown escape sequence in the value",
+            ParseErrorKind::InvalidUtf8 => "the line is not valid UTF-8",
+            ParseErrorKind::EntryOutsideGroup => "entries must follow a group header",
+            ParseErrorKind::Unexpected => "unexpected line",
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// Why the locale of a key, between brackets, is malformed.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum LocaleError {
+    /// The locale is empty, as in `Name[]`.
+    Empty,
+    /// The locale does not start with a language, as in `Name[_FR]`.
+    MissingLanguage,
+    /// The separator is not followed by the country (`_`), the encoding (`.`) or the modifier (`@`), as in `Name[fr_]`.
+    EmptyComponent(char),
+    /// The character is not allowed there, such as a component out of order in `Name[fr@euro_FR]`.
+    UnexpectedCharacter(char),
+    /// The brackets contain an opening bracket, as in `Name[fr[x]]`.
+    NestedBracket,
+    /// The closing bracket is missing before the end of the line.
+    Unterminated,
+}
+
+impl Display for LocaleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LocaleError::Empty => write!(f, "the locale is empty"),
+            LocaleError::MissingLanguage => write!(f, "the language is missing"),
+            LocaleError::EmptyComponent(separator) => {
+                write!(f, "nothing follows `{separator}`")
+            }
+            LocaleError::UnexpectedCharacter(c) => write!(f, "unexpected `{c}`"),
+            LocaleError::NestedBracket => write!(f, "brackets cannot be nested"),
+            LocaleError::Unterminated => write!(f, "missing `]`"),
+        }
+    }
+}
+
+impl core::error::Error for LocaleError {}
+
+/// Why the command line of `Exec` could not be tokenized, see [crate::helpers::desktop_entry::exec::tokenize].
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ExecError {
+    /// A quoted argument has no closing quote.
+    UnterminatedQuote,
+    /// A quoted argument contains a backslash which does not escape `"`, `` ` ``, `$` or `\`.
+    InvalidEscape,
+    /// The field code is not defined by the specification, or `%` ends the command line if `None`.
+    UnknownCode(Option<char>),
+    /// The field code is deprecated, such as `%d` or `%m`.
+    DeprecatedCode(char),
+    /// The field code must be an argument of its own, such as `%F`.
+    NotStandalone(FieldCode),
+    /// The field code is inside a quoted argument, where only `%%` is allowed.
+    QuotedCode(char),
+    /// The command line contains several of the `%f`, `%F`, `%u` and `%U` field codes.
+    SeveralFileCodes,
+}
+
+impl Display for ExecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExecError::UnterminatedQuote => write!(f, "unterminated quoted argument"),
+            ExecError::InvalidEscape => write!(f, "invalid escape sequence in a quoted argument"),
+            ExecError::UnknownCode(Some(code)) => write!(f, "unknown field code %{code}"),
+            ExecError::UnknownCode(None) => write!(f, "incomplete field code at the end"),
+            ExecError::DeprecatedCode(code) => write!(f, "deprecated field code %{code}"),
+            ExecError::NotStandalone(code) => {
+                write!(f, "the field code {code} must be an argument of its own")
+            }
+            ExecError::QuotedCode(code) => {
+                write!(f, "the field code %{code} is inside a quoted argument")
+            }
+            ExecError::SeveralFileCodes => write!(f, "several field codes of files or URLs"),
+        }
+    }
+}
+
+impl core::error::Error for ExecError {}
+
+impl From<ExecError> for Error {
+    fn from(error: ExecError) -> Self {
+        Error::InvalidValue(format!("{error} in Exec"))
+    }
+}
+
+/// An error of the serde data format, see [crate::from_str] and [crate::to_string].
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SerdeError {
+    /// The description of the error.
+    pub message: String,
+}
+
+#[cfg(feature = "serde")]
+impl Display for SerdeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl core::error::Error for SerdeError {}
+
+#[cfg(feature = "serde")]
+impl From<ParseError> for SerdeError {
+    fn from(error: ParseError) -> Self {
+        Self {
+            message: error.to_string(),
+        }
+    }
 }
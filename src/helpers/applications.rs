@@ -0,0 +1,205 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use crate::parser::models::{DesktopFile, EntrySet, Locale};
+
+const GROUP_NAME: &str = "Desktop Entry";
+
+const NAME_KEY: &str = "Name";
+
+/// The desktop entries of installed applications, indexed by their desktop file ID.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct ApplicationDatabase {
+    applications: BTreeMap<String, DesktopFile>,
+}
+
+impl ApplicationDatabase {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an application, replacing and returning the previous one with the same ID.
+    pub fn insert(&mut self, id: &str, file: DesktopFile) -> Option<DesktopFile> {
+        self.applications.insert(id.to_owned(), file)
+    }
+
+    /// Removes and returns the application with this ID.
+    pub fn remove(&mut self, id: &str) -> Option<DesktopFile> {
+        self.applications.remove(id)
+    }
+
+    /// Returns the application with this ID.
+    pub fn get(&self, id: &str) -> Option<&DesktopFile> {
+        self.applications.get(id)
+    }
+
+    /// Returns the number of applications.
+    pub fn len(&self) -> usize {
+        self.applications.len()
+    }
+
+    /// Returns whether the database is empty.
+    pub fn is_empty(&self) -> bool {
+        self.applications.is_empty()
+    }
+
+    /// Iterates over the applications, ordered by ID.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DesktopFile)> {
+        self.applications
+            .iter()
+            .map(|(id, file)| (id.as_str(), file))
+    }
+
+    /// Returns the `Name` of the application, translated in the locale if possible.
+    pub fn name(&self, id: &str, locale: &Locale) -> Option<&str> {
+        self.get(id).and_then(|file| localized_name(file, locale))
+    }
+
+    /// Returns the applications ordered by their `Name` in the locale, as they should appear in a menu.
+    ///
+    /// With the `unicode-collation` feature, names are compared with the Unicode Collation Algorithm.
+    /// Otherwise, they are compared case-insensitively, character by character.
+    /// Applications without a name are put last, ordered by ID.
+    pub fn sorted_by_name(&self, locale: &Locale) -> Vec<(&str, &DesktopFile)> {
+        let mut compare = NameComparator::new(locale);
+        let mut applications: Vec<_> = self
+            .iter()
+            .map(|(id, file)| (localized_name(file, locale), id, file))
+            .collect();
+
+        applications.sort_by(|(a, a_id, _), (b, b_id, _)| match (a, b) {
+            (Some(a), Some(b)) => compare.compare(a, b).then_with(|| a_id.cmp(b_id)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a_id.cmp(b_id),
+        });
+        applications
+            .into_iter()
+            .map(|(_, id, file)| (id, file))
+            .collect()
+    }
+}
+
+impl FromIterator<(String, DesktopFile)> for ApplicationDatabase {
+    fn from_iter<T: IntoIterator<Item = (String, DesktopFile)>>(iter: T) -> Self {
+        Self {
+            applications: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Finds the `Name` translated in the locale, falling back on the untranslated one.
+fn localized_name<'a>(file: &'a DesktopFile, locale: &Locale) -> Option<&'a str> {
+    file.find(GROUP_NAME)?
+        .find_localized(NAME_KEY, locale)?
+        .values
+        .first()
+        .map(String::as_str)
+}
+
+/// Compares names in a locale.
+#[cfg(feature = "unicode-collation")]
+struct NameComparator {
+    collator: feruca::Collator,
+}
+
+#[cfg(feature = "unicode-collation")]
+impl NameComparator {
+    fn new(locale: &Locale) -> Self {
+        let tailoring = match locale.lang.as_str() {
+            "ar" | "fa" | "ur" => feruca::Tailoring::Cldr(feruca::Locale::ArabicScript),
+            _ => feruca::Tailoring::default(),
+        };
+        Self {
+            collator: feruca::Collator::new(tailoring, true, true),
+        }
+    }
+
+    fn compare(&mut self, a: &str, b: &str) -> Ordering {
+        self.collator.collate(a, b)
+    }
+}
+
+/// Compares names in a locale.
+#[cfg(not(feature = "unicode-collation"))]
+struct NameComparator;
+
+#[cfg(not(feature = "unicode-collation"))]
+impl NameComparator {
+    fn new(_locale: &Locale) -> Self {
+        Self
+    }
+
+    fn compare(&mut self, a: &str, b: &str) -> Ordering {
+        a.chars()
+            .flat_map(char::to_lowercase)
+            .cmp(b.chars().flat_map(char::to_lowercase))
+            .then_with(|| a.cmp(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application(names: &str) -> DesktopFile {
+        DesktopFile::try_from(format!("[Desktop Entry]\n{names}\n").as_str()).unwrap()
+    }
+
+    fn locale(lang: &str, country: Option<&str>) -> Locale {
+        Locale {
+            lang: lang.to_owned(),
+            encoding: None,
+            country: country.map(str::to_owned),
+            modifiers: None,
+        }
+    }
+
+    #[test]
+    fn test_sorted_by_name() {
+        let database: ApplicationDatabase = [
+            ("files.desktop", "Name=Files\nName[fr]=Fichiers"),
+            ("editor.desktop", "Name=editor\nName[fr]=Éditeur"),
+            ("browser.desktop", "Name=Browser\nName[fr]=Navigateur"),
+            ("nameless.desktop", "Exec=nameless"),
+            ("zoom.desktop", "Name=Zoom"),
+        ]
+        .into_iter()
+        .map(|(id, names)| (id.to_owned(), application(names)))
+        .collect();
+
+        let ids = |locale: &Locale| {
+            database
+                .sorted_by_name(locale)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(&locale("en", None)),
+            vec![
+                "browser.desktop",
+                "editor.desktop",
+                "files.desktop",
+                "zoom.desktop",
+                "nameless.desktop"
+            ]
+        );
+        assert_eq!(
+            database.name("editor.desktop", &locale("fr", Some("FR"))),
+            Some("Éditeur")
+        );
+        #[cfg(feature = "unicode-collation")]
+        assert_eq!(
+            ids(&locale("fr", None)),
+            vec![
+                "editor.desktop",
+                "files.desktop",
+                "browser.desktop",
+                "zoom.desktop",
+                "nameless.desktop"
+            ]
+        );
+    }
+}
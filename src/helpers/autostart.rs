@@ -0,0 +1,385 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::Error,
+    helpers::{
+        desktop_entry::{DesktopEntry, keys::Key},
+        permissions::secure_write,
+    },
+    parser::models::DesktopFile,
+};
+
+const DIR_NAME: &str = "autostart";
+
+/// The autostart directories of the Desktop Application Autostart specification.
+///
+/// An entry of the user directory overrides the entry with the same file name in the system directories,
+/// and an entry with `Hidden=true` is not started, nor an entry which is not shown in the current desktop
+/// or whose `TryExec` program is not installed.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Autostart {
+    /// The user configuration directory, `$XDG_CONFIG_HOME`.
+    pub config_home: PathBuf,
+    /// The system configuration directories, `$XDG_CONFIG_DIRS`, by order of precedence.
+    pub config_dirs: Vec<PathBuf>,
+}
+
+/// The entries of autostart directories, see [Autostart::system_entries].
+#[derive(Debug, Default)]
+pub struct AutostartEntries {
+    /// The entries, by file name.
+    pub entries: BTreeMap<String, DesktopEntry>,
+    /// The files which are not valid desktop entries, with the reason, which are skipped.
+    pub invalid: Vec<(PathBuf, Error)>,
+}
+
+/// A change of the user autostart directory, see [Autostart::sync].
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum AutostartChange {
+    /// Writes the entry to the user directory, overriding the system entry if any.
+    Create {
+        /// The file name of the entry, such as `app.desktop`.
+        id: String,
+        /// The entry to write.
+        entry: DesktopEntry,
+    },
+    /// Writes a copy of the system entry with `Hidden=true` to the user directory, so that it is not started.
+    Hide {
+        /// The file name of the entry.
+        id: String,
+        /// The entry to write.
+        entry: DesktopEntry,
+    },
+    /// Removes the entry from the user directory, which restores the system entry if any.
+    Remove {
+        /// The file name of the entry.
+        id: String,
+    },
+}
+
+impl AutostartChange {
+    /// Returns the file name of the changed entry.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Create { id, .. } | Self::Hide { id, .. } | Self::Remove { id } => id,
+        }
+    }
+}
+
+impl Autostart {
+    /// Reads the directories from the XDG environment variables, with the defaults of the Base Directory specification.
+    pub fn from_env() -> Result<Self, Error> {
+        let config_home = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config"))
+                .ok_or(Error::NotFound(String::from("HOME")))?,
+        };
+        let config_dirs = env::var("XDG_CONFIG_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .as_deref()
+            .unwrap_or("/etc/xdg")
+            .split(':')
+            .map(PathBuf::from)
+            .collect();
+
+        Ok(Self {
+            config_home,
+            config_dirs,
+        })
+    }
+
+    /// Returns the user autostart directory, where the changes are written.
+    pub fn user_dir(&self) -> PathBuf {
+        self.config_home.join(DIR_NAME)
+    }
+
+    /// Reads the entries of the system directories, by file name.
+    ///
+    /// An entry of a directory overrides the entries with the same file name in the following directories.
+    /// The files which cannot be parsed are skipped and reported in [AutostartEntries::invalid],
+    /// but still override the entries with the same file name.
+    pub fn system_entries(&self) -> Result<AutostartEntries, Error> {
+        let mut entries = AutostartEntries::default();
+        for dir in self.config_dirs.iter().rev() {
+            read_entries(&dir.join(DIR_NAME), &mut entries)?;
+        }
+        Ok(entries)
+    }
+
+    /// Reads the entries of the user directory, by file name.
+    ///
+    /// The files which cannot be parsed are skipped and reported in [AutostartEntries::invalid].
+    pub fn user_entries(&self) -> Result<AutostartEntries, Error> {
+        let mut entries = AutostartEntries::default();
+        read_entries(&self.user_dir(), &mut entries)?;
+        Ok(entries)
+    }
+
+    /// Reads the entries which are started in one of the desktops, by file name, the user entries overriding the system ones.
+    ///
+    /// The desktops are usually those of `XDG_CURRENT_DESKTOP`, see [DesktopEntry::should_show_in].
+    /// Entries which are hidden or whose `TryExec` program is not installed are not started.
+    pub fn enabled(&self, desktops: &[&str]) -> Result<AutostartEntries, Error> {
+        let mut entries = self.system_entries()?;
+        read_entries(&self.user_dir(), &mut entries)?;
+        entries.entries.retain(|_, entry| {
+            !is_hidden(entry) && entry.should_show_in(desktops) && entry.try_exec_exists()
+        });
+        Ok(entries)
+    }
+
+    /// Computes the changes of the user directory after which exactly the desired entries are started.
+    ///
+    /// Entries are identified by their file name, and compared regardless of their comments and formatting.
+    /// The system entries are never deleted: the undesired ones are hidden by a user entry,
+    /// and the user entries which are no longer needed are removed to restore them.
+    /// The changes are written by [Self::apply].
+    pub fn sync(&self, desired: &[(&str, &DesktopEntry)]) -> Result<Vec<AutostartChange>, Error> {
+        let system = self.system_entries()?.entries;
+        let user = self.user_entries()?.entries;
+        let mut changes = vec![];
+
+        for (id, entry) in desired {
+            let id = id.to_string();
+            let started_by_system = system
+                .get(&id)
+                .is_some_and(|system| same_entry(system, entry));
+            match user.get(&id) {
+                Some(_) if started_by_system => changes.push(AutostartChange::Remove { id }),
+                Some(current) if same_entry(current, entry) => {}
+                None if started_by_system => {}
+                _ => changes.push(AutostartChange::Create {
+                    id,
+                    entry: (*entry).clone(),
+                }),
+            }
+        }
+
+        let undesired = |id: &String| !desired.iter().any(|(desired, _)| desired == id);
+        for (id, entry) in system.iter().filter(|(id, _)| undesired(id)) {
+            if user.get(id).is_none_or(|current| !is_hidden(current)) {
+                let mut hidden = entry.clone();
+                hidden.set(Key::Hidden, true);
+                changes.push(AutostartChange::Hide {
+                    id: id.clone(),
+                    entry: hidden,
+                });
+            }
+        }
+        for id in user.keys().filter(|id| undesired(id)) {
+            if !system.contains_key(id) {
+                changes.push(AutostartChange::Remove { id: id.clone() });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Writes the changes to the user directory, which is created if needed.
+    pub fn apply(&self, changes: &[AutostartChange]) -> Result<(), Error> {
+        let dir = self.user_dir();
+        for change in changes {
+            let path = dir.join(change.id());
+            match change {
+                AutostartChange::Create { entry, .. } | AutostartChange::Hide { entry, .. } => {
+                    secure_write(&path, format!("{}\n", entry.file()))?
+                }
+                AutostartChange::Remove { .. } => match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(error) if error.kind() == ErrorKind::NotFound => {}
+                    Err(error) => return Err(Error::Io(error)),
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the entry has `Hidden=true`, in which case it is not started.
+fn is_hidden(entry: &DesktopEntry) -> bool {
+    entry.hidden().is_ok_and(|hidden| hidden)
+}
+
+/// Whether the entries are the same, regardless of their comments and formatting.
+fn same_entry(a: &DesktopEntry, b: &DesktopEntry) -> bool {
+    a.file().content_hash() == b.file().content_hash()
+}
+
+/// Reads the `.desktop` files of the directory, which may not exist, overriding the entries with the same file name.
+fn read_entries(dir: &Path, entries: &mut AutostartEntries) -> Result<(), Error> {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(Error::Io(error)),
+    };
+
+    for entry in read_dir {
+        let path = entry.map_err(Error::Io)?.path();
+        let Some(id) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !id.ends_with(".desktop") {
+            continue;
+        }
+        let parsed = fs::read(&path).map_err(Error::Io).and_then(|content| {
+            DesktopFile::try_from(content.as_slice())
+                .map_err(Error::Parsing)
+                .and_then(|file| {
+                    DesktopEntry::try_from(file).map(|entry| entry.with_location(&path))
+                })
+        });
+        match parsed {
+            Ok(entry) => {
+                entries.entries.insert(id.to_owned(), entry);
+            }
+            Err(error) => {
+                entries.entries.remove(id);
+                entries.invalid.push((path, error));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> DesktopEntry {
+        let file = DesktopFile::try_from(
+            format!("[Desktop Entry]\nType=Application\nName={name}\n").as_str(),
+        )
+        .unwrap();
+        DesktopEntry::try_from(file).unwrap()
+    }
+
+    #[test]
+    fn test_sync() {
+        let root = env::temp_dir().join(format!("freedesktop-rs-autostart-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let autostart = Autostart {
+            config_home: root.join("config"),
+            config_dirs: vec![root.join("etc")],
+        };
+        let system = root.join("etc/autostart");
+        fs::create_dir_all(&system).unwrap();
+        for name in ["kept", "unwanted", "overridden"] {
+            fs::write(
+                system.join(format!("{name}.desktop")),
+                format!("# System\n{}\n", entry(name).file()),
+            )
+            .unwrap();
+        }
+        fs::create_dir_all(autostart.user_dir()).unwrap();
+        fs::write(
+            autostart.user_dir().join("overridden.desktop"),
+            format!("{}\n", entry("changed").file()),
+        )
+        .unwrap();
+        fs::write(
+            autostart.user_dir().join("old.desktop"),
+            format!("{}\n", entry("old").file()),
+        )
+        .unwrap();
+
+        let (kept, overridden, new) = (entry("kept"), entry("overridden"), entry("new"));
+        let desired = [
+            ("kept.desktop", &kept),
+            ("overridden.desktop", &overridden),
+            ("new.desktop", &new),
+        ];
+        let changes = autostart.sync(&desired).unwrap();
+        let ids: Vec<_> = changes
+            .iter()
+            .map(|change| match change {
+                AutostartChange::Create { id, .. } => format!("create {id}"),
+                AutostartChange::Hide { id, .. } => format!("hide {id}"),
+                AutostartChange::Remove { id } => format!("remove {id}"),
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "remove overridden.desktop",
+                "create new.desktop",
+                "hide unwanted.desktop",
+                "remove old.desktop"
+            ]
+        );
+
+        autostart.apply(&changes).unwrap();
+        let enabled: Vec<_> = autostart
+            .enabled(&[])
+            .unwrap()
+            .entries
+            .into_keys()
+            .collect();
+        assert_eq!(
+            enabled,
+            ["kept.desktop", "new.desktop", "overridden.desktop"]
+        );
+        assert!(system.join("unwanted.desktop").exists());
+        assert!(autostart.sync(&desired).unwrap().is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_enabled() {
+        let root = env::temp_dir().join(format!(
+            "freedesktop-rs-autostart-enabled-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        let autostart = Autostart {
+            config_home: root.join("config"),
+            config_dirs: vec![root.join("etc")],
+        };
+        let system = root.join("etc/autostart");
+        fs::create_dir_all(&system).unwrap();
+        fs::create_dir_all(autostart.user_dir()).unwrap();
+        for (name, extra) in [
+            ("plain", ""),
+            ("gnome", "OnlyShowIn=GNOME;\n"),
+            ("missing", "TryExec=/nonexistent/freedesktop-rs-program\n"),
+            ("broken", ""),
+        ] {
+            fs::write(
+                system.join(format!("{name}.desktop")),
+                format!("{}\n{extra}", entry(name).file()),
+            )
+            .unwrap();
+        }
+        fs::write(autostart.user_dir().join("broken.desktop"), "Name=App\n").unwrap();
+        fs::write(system.join("invalid.desktop"), "[Desktop Entry\n").unwrap();
+
+        let enabled = autostart.enabled(&["KDE"]).unwrap();
+        assert_eq!(
+            enabled.entries.into_keys().collect::<Vec<_>>(),
+            ["plain.desktop"]
+        );
+        let mut invalid: Vec<_> = enabled
+            .invalid
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap())
+            .collect();
+        invalid.sort();
+        assert_eq!(invalid, ["broken.desktop", "invalid.desktop"]);
+        assert!(
+            autostart
+                .enabled(&["GNOME"])
+                .unwrap()
+                .entries
+                .contains_key("gnome.desktop")
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,463 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::{convert::Infallible, fmt::Display, str::FromStr};
+
+#[cfg(feature = "std")]
+use crate::registry::Registry;
+
+/// Registry of the registered categories, with the categories they should be used with,
+/// initialized with the categories of the Desktop Menu Specification.
+///
+/// Categories registered since then can be added, with the categories they are related to.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub static CATEGORY_REGISTRY: Registry<Vec<Category>> = Registry::new(|| {
+    Category::REGISTERED
+        .iter()
+        .map(|category| {
+            let related = category.specified_related().to_vec();
+            (category.as_str().to_owned(), related)
+        })
+        .collect()
+});
+
+/// Defines the registered categories, with the names they are written with.
+macro_rules! categories {
+    ($($variant:ident = $name:literal,)*) => {
+        /// A category of the `Categories` key, in which the entry is shown in menus.
+        ///
+        /// The categories registered by the Desktop Menu Specification are typed,
+        /// and the other ones, which should start with `X-`, are kept as [Category::Other].
+        #[derive(Debug, Eq, PartialEq, Clone, Hash)]
+        pub enum Category {
+            $(
+                #[doc = concat!("`", $name, "`")]
+                $variant,
+            )*
+            /// A category which is not registered.
+            Other(String),
+        }
+
+        impl Category {
+            /// All the registered categories, in the order of the specification.
+            pub const REGISTERED: &[Category] = &[$(Category::$variant,)*];
+
+            /// Returns the category as written in a file.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Category::$variant => $name,)*
+                    Category::Other(category) => category,
+                }
+            }
+        }
+
+        impl From<&str> for Category {
+            /// Parses the category, which is [Category::Other] if it is not registered.
+            fn from(value: &str) -> Self {
+                match value {
+                    $($name => Category::$variant,)*
+                    other => Category::Other(other.to_owned()),
+                }
+            }
+        }
+    };
+}
+
+categories! {
+    AudioVideo = "AudioVideo",
+    Audio = "Audio",
+    Video = "Video",
+    Development = "Development",
+    Education = "Education",
+    Game = "Game",
+    Graphics = "Graphics",
+    Network = "Network",
+    Office = "Office",
+    Science = "Science",
+    Settings = "Settings",
+    System = "System",
+    Utility = "Utility",
+    Building = "Building",
+    Debugger = "Debugger",
+    Ide = "IDE",
+    GuiDesigner = "GUIDesigner",
+    Profiling = "Profiling",
+    RevisionControl = "RevisionControl",
+    Translation = "Translation",
+    Calendar = "Calendar",
+    ContactManagement = "ContactManagement",
+    Database = "Database",
+    Dictionary = "Dictionary",
+    Chart = "Chart",
+    Email = "Email",
+    Finance = "Finance",
+    FlowChart = "FlowChart",
+    Pda = "PDA",
+    ProjectManagement = "ProjectManagement",
+    Presentation = "Presentation",
+    Spreadsheet = "Spreadsheet",
+    WordProcessor = "WordProcessor",
+    Graphics2D = "2DGraphics",
+    VectorGraphics = "VectorGraphics",
+    RasterGraphics = "RasterGraphics",
+    Graphics3D = "3DGraphics",
+    Scanning = "Scanning",
+    Ocr = "OCR",
+    Photography = "Photography",
+    Publishing = "Publishing",
+    Viewer = "Viewer",
+    TextTools = "TextTools",
+    DesktopSettings = "DesktopSettings",
+    HardwareSettings = "HardwareSettings",
+    Printing = "Printing",
+    PackageManager = "PackageManager",
+    Dialup = "Dialup",
+    InstantMessaging = "InstantMessaging",
+    Chat = "Chat",
+    IrcClient = "IRCClient",
+    Feed = "Feed",
+    FileTransfer = "FileTransfer",
+    HamRadio = "HamRadio",
+    News = "News",
+    P2p = "P2P",
+    RemoteAccess = "RemoteAccess",
+    Telephony = "Telephony",
+    TelephonyTools = "TelephonyTools",
+    VideoConference = "VideoConference",
+    WebBrowser = "WebBrowser",
+    WebDevelopment = "WebDevelopment",
+    Midi = "Midi",
+    Mixer = "Mixer",
+    Sequencer = "Sequencer",
+    Tuner = "Tuner",
+    Tv = "TV",
+    AudioVideoEditing = "AudioVideoEditing",
+    Player = "Player",
+    Recorder = "Recorder",
+    DiscBurning = "DiscBurning",
+    ActionGame = "ActionGame",
+    AdventureGame = "AdventureGame",
+    ArcadeGame = "ArcadeGame",
+    BoardGame = "BoardGame",
+    BlocksGame = "BlocksGame",
+    CardGame = "CardGame",
+    KidsGame = "KidsGame",
+    LogicGame = "LogicGame",
+    RolePlaying = "RolePlaying",
+    Shooter = "Shooter",
+    Simulation = "Simulation",
+    SportsGame = "SportsGame",
+    StrategyGame = "StrategyGame",
+    Art = "Art",
+    Construction = "Construction",
+    Music = "Music",
+    Languages = "Languages",
+    ArtificialIntelligence = "ArtificialIntelligence",
+    Astronomy = "Astronomy",
+    Biology = "Biology",
+    Chemistry = "Chemistry",
+    ComputerScience = "ComputerScience",
+    DataVisualization = "DataVisualization",
+    Economy = "Economy",
+    Electricity = "Electricity",
+    Geography = "Geography",
+    Geology = "Geology",
+    Geoscience = "Geoscience",
+    History = "History",
+    Humanities = "Humanities",
+    ImageProcessing = "ImageProcessing",
+    Literature = "Literature",
+    Maps = "Maps",
+    Math = "Math",
+    NumericalAnalysis = "NumericalAnalysis",
+    MedicalSoftware = "MedicalSoftware",
+    Physics = "Physics",
+    Robotics = "Robotics",
+    Spirituality = "Spirituality",
+    Sports = "Sports",
+    ParallelComputing = "ParallelComputing",
+    Amusement = "Amusement",
+    Archiving = "Archiving",
+    Compression = "Compression",
+    Electronics = "Electronics",
+    Emulator = "Emulator",
+    Engineering = "Engineering",
+    FileTools = "FileTools",
+    FileManager = "FileManager",
+    TerminalEmulator = "TerminalEmulator",
+    Filesystem = "Filesystem",
+    Monitor = "Monitor",
+    Security = "Security",
+    Accessibility = "Accessibility",
+    Calculator = "Calculator",
+    Clock = "Clock",
+    TextEditor = "TextEditor",
+    Documentation = "Documentation",
+    Adult = "Adult",
+    Core = "Core",
+    Kde = "KDE",
+    Gnome = "GNOME",
+    Xfce = "XFCE",
+    Dde = "DDE",
+    Gtk = "GTK",
+    Qt = "Qt",
+    Motif = "Motif",
+    Java = "Java",
+    ConsoleOnly = "ConsoleOnly",
+    Screensaver = "Screensaver",
+    TrayIcon = "TrayIcon",
+    Applet = "Applet",
+    Shell = "Shell",
+}
+
+/// Categories of the education and science applications.
+const EDUCATION_SCIENCE: &[Category] = &[Category::Education, Category::Science];
+
+/// Categories of the audio and video applications.
+const AUDIO_VIDEO: &[Category] = &[Category::AudioVideo, Category::Audio, Category::Video];
+
+impl Category {
+    /// Returns whether the category is a main category, in which menus are organized.
+    pub fn is_main(&self) -> bool {
+        matches!(
+            self,
+            Category::AudioVideo
+                | Category::Audio
+                | Category::Video
+                | Category::Development
+                | Category::Education
+                | Category::Game
+                | Category::Graphics
+                | Category::Network
+                | Category::Office
+                | Category::Science
+                | Category::Settings
+                | Category::System
+                | Category::Utility
+        )
+    }
+
+    /// Returns whether the category is reserved for desktop environments, and must be used with `OnlyShowIn`.
+    pub fn is_reserved(&self) -> bool {
+        matches!(
+            self,
+            Category::Screensaver | Category::TrayIcon | Category::Applet | Category::Shell
+        )
+    }
+
+    /// Returns whether the category is neither registered, nor an extension starting with `X-`.
+    ///
+    /// With the `std` feature, the registered categories are those of [CATEGORY_REGISTRY].
+    pub fn is_unknown(&self) -> bool {
+        if self.as_str().starts_with("X-") {
+            return false;
+        }
+        #[cfg(feature = "std")]
+        {
+            !CATEGORY_REGISTRY.contains(self.as_str())
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            matches!(self, Category::Other(_))
+        }
+    }
+
+    /// Returns the categories with which the category should be used, at least one of them, or none if it can be alone.
+    ///
+    /// `Audio` and `Video` require `AudioVideo`, and the additional categories are related to main categories.
+    /// With the `std` feature, these are the categories registered with it in [CATEGORY_REGISTRY].
+    pub fn related(&self) -> Vec<Category> {
+        #[cfg(feature = "std")]
+        {
+            CATEGORY_REGISTRY.get(self.as_str()).unwrap_or_default()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            self.specified_related().to_vec()
+        }
+    }
+
+    /// Returns the related categories defined by the Desktop Menu Specification, see [Self::related].
+    fn specified_related(&self) -> &'static [Category] {
+        match self {
+            Category::Audio | Category::Video => &[Category::AudioVideo],
+            Category::Building
+            | Category::Debugger
+            | Category::Ide
+            | Category::GuiDesigner
+            | Category::Profiling
+            | Category::RevisionControl
+            | Category::Translation => &[Category::Development],
+            Category::Calendar
+            | Category::ContactManagement
+            | Category::Chart
+            | Category::Finance
+            | Category::FlowChart
+            | Category::Pda
+            | Category::Presentation
+            | Category::Spreadsheet
+            | Category::WordProcessor => &[Category::Office],
+            Category::Database => &[
+                Category::Office,
+                Category::Development,
+                Category::AudioVideo,
+            ],
+            Category::Dictionary => &[Category::Office, Category::TextTools],
+            Category::Email => &[Category::Office, Category::Network],
+            Category::ProjectManagement => &[Category::Office, Category::Development],
+            Category::Graphics2D | Category::Graphics3D | Category::Scanning => {
+                &[Category::Graphics]
+            }
+            Category::VectorGraphics | Category::RasterGraphics => {
+                &[Category::Graphics, Category::Graphics2D]
+            }
+            Category::Ocr => &[Category::Graphics, Category::Scanning],
+            Category::Photography | Category::Publishing | Category::Viewer => {
+                &[Category::Graphics, Category::Office]
+            }
+            Category::TextTools
+            | Category::TelephonyTools
+            | Category::Archiving
+            | Category::Compression
+            | Category::Calculator
+            | Category::Clock
+            | Category::TextEditor => &[Category::Utility],
+            Category::DesktopSettings | Category::HardwareSettings | Category::PackageManager => {
+                &[Category::Settings]
+            }
+            Category::Printing => &[Category::HardwareSettings, Category::Settings],
+            Category::Dialup
+            | Category::InstantMessaging
+            | Category::Chat
+            | Category::IrcClient
+            | Category::Feed
+            | Category::FileTransfer
+            | Category::News
+            | Category::P2p
+            | Category::RemoteAccess
+            | Category::Telephony
+            | Category::VideoConference
+            | Category::WebBrowser => &[Category::Network],
+            Category::HamRadio => &[Category::Network, Category::Audio],
+            Category::WebDevelopment => &[Category::Network, Category::Development],
+            Category::Midi | Category::Mixer | Category::Sequencer | Category::Tuner => {
+                &[Category::AudioVideo, Category::Audio]
+            }
+            Category::Tv => &[Category::AudioVideo, Category::Video],
+            Category::AudioVideoEditing | Category::Player | Category::Recorder => AUDIO_VIDEO,
+            Category::DiscBurning => &[Category::AudioVideo],
+            Category::ActionGame
+            | Category::AdventureGame
+            | Category::ArcadeGame
+            | Category::BoardGame
+            | Category::BlocksGame
+            | Category::CardGame
+            | Category::KidsGame
+            | Category::LogicGame
+            | Category::RolePlaying
+            | Category::Shooter
+            | Category::Simulation
+            | Category::SportsGame
+            | Category::StrategyGame => &[Category::Game],
+            Category::Art
+            | Category::Construction
+            | Category::Languages
+            | Category::ArtificialIntelligence
+            | Category::Astronomy
+            | Category::Biology
+            | Category::Chemistry
+            | Category::ComputerScience
+            | Category::DataVisualization
+            | Category::Economy
+            | Category::Electricity
+            | Category::Geography
+            | Category::Geology
+            | Category::Geoscience
+            | Category::History
+            | Category::Humanities
+            | Category::ImageProcessing
+            | Category::Literature
+            | Category::Math
+            | Category::MedicalSoftware
+            | Category::Physics
+            | Category::Robotics
+            | Category::Sports => EDUCATION_SCIENCE,
+            Category::Music => &[Category::AudioVideo, Category::Education],
+            Category::Maps | Category::Spirituality => {
+                &[Category::Education, Category::Science, Category::Utility]
+            }
+            Category::NumericalAnalysis => {
+                &[Category::Education, Category::Science, Category::Math]
+            }
+            Category::ParallelComputing => &[
+                Category::Education,
+                Category::Science,
+                Category::ComputerScience,
+            ],
+            Category::Emulator => &[Category::System, Category::Game],
+            Category::FileTools => &[Category::Utility, Category::System],
+            Category::FileManager => &[Category::System, Category::FileTools],
+            Category::TerminalEmulator | Category::Filesystem => &[Category::System],
+            Category::Monitor => &[Category::System, Category::Network],
+            Category::Security => &[Category::Settings, Category::System],
+            Category::Accessibility => &[Category::Settings, Category::Utility],
+            _ => &[],
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Category {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_category() {
+        for category in Category::REGISTERED {
+            assert_eq!(&Category::from(category.as_str()), category);
+            assert!(category.related().iter().all(|c| c != category));
+        }
+        assert_eq!(Category::REGISTERED.len(), 144);
+        assert_eq!(Category::from("2DGraphics"), Category::Graphics2D);
+        assert_eq!(Category::Ide.to_string(), "IDE");
+        assert_eq!(
+            Category::from("X-Custom"),
+            Category::Other(String::from("X-Custom"))
+        );
+
+        assert!(Category::Game.is_main());
+        assert!(!Category::ArcadeGame.is_main());
+        assert!(Category::TrayIcon.is_reserved());
+        assert!(Category::from("Custom").is_unknown());
+        assert!(!Category::from("X-Custom").is_unknown());
+        assert!(!Category::Game.is_unknown());
+        assert_eq!(Category::Audio.related(), [Category::AudioVideo]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_category_registry() {
+        let category = Category::from("Registered");
+        assert!(category.is_unknown());
+
+        CATEGORY_REGISTRY.register("Registered", vec![Category::Game]);
+        assert!(!category.is_unknown());
+        assert_eq!(category.related(), [Category::Game]);
+        CATEGORY_REGISTRY.unregister("Registered");
+        assert!(category.is_unknown());
+    }
+}
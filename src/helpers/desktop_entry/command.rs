@@ -0,0 +1,346 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::Error,
+    helpers::desktop_entry::{
+        DesktopEntry, Key,
+        exec::{ExecToken, FieldCode, decoded, tokenize},
+    },
+    helpers::percent::{decode_path, file_uri},
+    parser::models::Locale,
+};
+
+/// A file given to an application, either a local path or a URI.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum PathOrUri {
+    /// The path of a local file.
+    Path(PathBuf),
+    /// A URI, such as `https://example.org` or `file:///tmp/a.txt`.
+    Uri(String),
+}
+
+impl PathOrUri {
+    /// Returns the local path, which is the decoded path of `file://` URIs, or `None` if the URI is not local.
+    pub fn to_path(&self) -> Option<PathBuf> {
+        match self {
+            PathOrUri::Path(path) => Some(path.to_owned()),
+            PathOrUri::Uri(uri) => {
+                let path = uri.strip_prefix("file://")?;
+                let path = path.strip_prefix("localhost").unwrap_or(path);
+                path.starts_with('/').then(|| decode_path(path))
+            }
+        }
+    }
+
+    /// Returns the URI, which is the `file://` URI of the absolute path of local files.
+    pub fn to_uri(&self) -> String {
+        match self {
+            PathOrUri::Path(path) => {
+                file_uri(&std::path::absolute(path).unwrap_or_else(|_| path.to_owned()))
+            }
+            PathOrUri::Uri(uri) => uri.to_owned(),
+        }
+    }
+
+    /// Returns the argument of `%f` and `%F`, which is the URI itself if it is not local.
+    fn to_file_argument(&self) -> OsString {
+        match self.to_path() {
+            Some(path) => path.into_os_string(),
+            None => OsString::from(self.to_uri()),
+        }
+    }
+}
+
+impl From<PathBuf> for PathOrUri {
+    fn from(path: PathBuf) -> Self {
+        PathOrUri::Path(path)
+    }
+}
+
+impl From<&Path> for PathOrUri {
+    fn from(path: &Path) -> Self {
+        PathOrUri::Path(path.to_owned())
+    }
+}
+
+impl DesktopEntry {
+    /// Sets the path of the file, which expands `%k` in `Exec`.
+    pub fn with_location<P: AsRef<Path>>(mut self, location: P) -> Self {
+        self.location = Some(location.as_ref().to_owned());
+        self
+    }
+
+    /// Returns the path of the file, if it was set with [Self::with_location].
+    pub fn location(&self) -> Option<&Path> {
+        self.location.as_deref()
+    }
+
+    /// Returns whether the program of `TryExec` is installed, in which case the entry may be shown and launched.
+    ///
+    /// The program is either an absolute path or searched in the directories of `PATH`,
+    /// and must be an executable file. The entry is considered installed if it has no `TryExec`.
+    pub fn try_exec_exists(&self) -> bool {
+        let Some(program) = self
+            .group()
+            .find_preferred(Key::TryExec.as_str(), &[])
+            .map(decoded)
+        else {
+            return true;
+        };
+        program.is_ok_and(|program| {
+            find_program(&program, &env::var_os("PATH").unwrap_or_default()).is_some()
+        })
+    }
+
+    /// Returns the arguments of the command starting the application with the files, beginning with the program.
+    ///
+    /// The field codes of `Exec` are expanded with the unlocalized `Name`, see [Self::localized_command_for].
+    /// ```
+    /// use freedesktop_rs::helpers::desktop_entry::{DesktopEntry, PathOrUri};
+    ///
+    /// let entry = DesktopEntry::application("My App", "\"/opt/my app/run\" --title=%c %F");
+    /// let files = [
+    ///     PathOrUri::Path("/tmp/a b.txt".into()),
+    ///     PathOrUri::Uri(String::from("file:///tmp/c%25.txt")),
+    /// ];
+    ///
+    /// assert_eq!(
+    ///     entry.command_for(&files).unwrap(),
+    ///     ["/opt/my app/run", "--title=My App", "/tmp/a b.txt", "/tmp/c%.txt"]
+    /// );
+    /// ```
+    pub fn command_for(&self, files: &[PathOrUri]) -> Result<Vec<OsString>, Error> {
+        self.localized_command_for(files, &[])
+    }
+
+    /// Returns the arguments of the command starting the application with the files, beginning with the program.
+    ///
+    /// Every argument is kept whole, even if its expansion contains spaces or quotes, as no shell is involved.
+    /// The field codes are expanded as follows:
+    /// - `%f` is the path of the first file, and `%u` its URI, as the application only handles one file at a time,
+    /// - `%F` and `%U` are the paths and URIs of every file, each as an argument of its own,
+    /// - `%f` and `%F` are given URIs which are not local, as the files are not downloaded,
+    /// - `%i` is `--icon` followed by the `Icon`, or nothing if there is no icon,
+    /// - `%c` is the `Name` translated in the first of the locales which has one,
+    /// - `%k` is the [location](Self::location) of the file.
+    ///
+    /// Arguments only made of field codes which expand to nothing are removed.
+    /// Fails with [Error::NotFound] if there is no `Exec` key,
+    /// or with [Error::InvalidValue] if it cannot be [tokenized](super::tokenize).
+    pub fn localized_command_for(
+        &self,
+        files: &[PathOrUri],
+        locales: &[Locale],
+    ) -> Result<Vec<OsString>, Error> {
+        let group = self.group();
+        let value = |key: Key, locales: &[Locale]| {
+            group
+                .find_preferred(key.as_str(), locales)
+                .map(decoded)
+                .transpose()
+        };
+        let exec = value(Key::Exec, &[])?.ok_or(Error::NotFound(Key::Exec.to_string()))?;
+
+        let mut arguments = vec![];
+        for tokens in tokenize(&exec)? {
+            match tokens.as_slice() {
+                [ExecToken::Code(FieldCode::Files)] => {
+                    arguments.extend(files.iter().map(PathOrUri::to_file_argument));
+                    continue;
+                }
+                [ExecToken::Code(FieldCode::Urls)] => {
+                    arguments.extend(files.iter().map(|file| file.to_uri().into()));
+                    continue;
+                }
+                [ExecToken::Code(FieldCode::Icon)] => {
+                    if let Some(icon) = value(Key::Icon, &[])?.filter(|icon| !icon.is_empty()) {
+                        arguments.extend([OsString::from("--icon"), OsString::from(&*icon)]);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut argument = OsString::new();
+            for token in &tokens {
+                let expanded = match token {
+                    ExecToken::Text(text) => Some(OsString::from(text)),
+                    ExecToken::Code(FieldCode::File) => {
+                        files.first().map(PathOrUri::to_file_argument)
+                    }
+                    ExecToken::Code(FieldCode::Url) => {
+                        files.first().map(|file| file.to_uri().into())
+                    }
+                    ExecToken::Code(FieldCode::Name) => {
+                        value(Key::Name, locales)?.map(|name| OsString::from(&*name))
+                    }
+                    ExecToken::Code(FieldCode::Location) => {
+                        self.location.as_ref().map(|path| path.into())
+                    }
+                    // Tokenizing ensures the other codes are arguments of their own
+                    ExecToken::Code(_) => None,
+                };
+                argument.extend(expanded);
+            }
+            let only_codes = tokens
+                .iter()
+                .all(|token| matches!(token, ExecToken::Code(_)));
+            if !only_codes || !argument.is_empty() {
+                arguments.push(argument);
+            }
+        }
+
+        Ok(arguments)
+    }
+}
+
+/// Returns the path of the executable program, either absolute or searched in the directories of the `PATH` variable.
+fn find_program(program: &str, path: &OsStr) -> Option<PathBuf> {
+    if program.is_empty() {
+        return None;
+    }
+    if program.contains('/') {
+        let program = Path::new(program);
+        return (program.is_absolute() && is_executable(program)).then(|| program.to_owned());
+    }
+    env::split_paths(path)
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+/// Whether the path is a file which can be executed, by anyone on Unix.
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Whether the path is a file which can be executed, which is any file on this platform.
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::models::DesktopFile;
+
+    fn command(exec: &str, files: &[PathOrUri]) -> Vec<OsString> {
+        let file = DesktopFile::try_from(
+            format!("[Desktop Entry]\nType=Application\nName=App\nName[fr]=Appli\nIcon=app\nExec={exec}\n")
+                .as_str(),
+        )
+        .unwrap();
+        DesktopEntry::try_from(file)
+            .unwrap()
+            .with_location("/usr/share/applications/app.desktop")
+            .localized_command_for(files, &Locale::parse_list("fr_FR"))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_command_for() {
+        let files = [
+            PathOrUri::Path(PathBuf::from("/tmp/a b.txt")),
+            PathOrUri::Uri(String::from("https://example.org/c")),
+        ];
+
+        assert_eq!(
+            command("app %f %i -k %k %%c", &files),
+            [
+                "app",
+                "/tmp/a b.txt",
+                "--icon",
+                "app",
+                "-k",
+                "/usr/share/applications/app.desktop",
+                "%c",
+            ]
+        );
+        assert_eq!(
+            command("app --name=%c %U", &files),
+            [
+                "app",
+                "--name=Appli",
+                "file:///tmp/a%20b.txt",
+                "https://example.org/c"
+            ]
+        );
+        assert_eq!(
+            command("app %F", &files),
+            ["app", "/tmp/a b.txt", "https://example.org/c"]
+        );
+        assert_eq!(
+            command("app --url=%u", &files),
+            ["app", "--url=file:///tmp/a%20b.txt"]
+        );
+        assert_eq!(command("app %f", &[]), ["app"]);
+        assert_eq!(command("app --file=%f", &[]), ["app", "--file="]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_program() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = env::temp_dir().join(format!("freedesktop-rs-command-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (first, second) = (root.join("first"), root.join("second"));
+        for dir in [&first, &second] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(first.join("app"), "").unwrap();
+        std::fs::write(second.join("app"), "").unwrap();
+        std::fs::set_permissions(second.join("app"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+        let path = env::join_paths([&first, &second]).unwrap();
+
+        assert_eq!(find_program("app", &path), Some(second.join("app")));
+        assert_eq!(find_program("other", &path), None);
+        assert_eq!(
+            find_program(second.join("app").to_str().unwrap(), OsStr::new("")),
+            Some(second.join("app"))
+        );
+        assert_eq!(
+            find_program(first.join("app").to_str().unwrap(), &path),
+            None
+        );
+        assert_eq!(
+            find_program("second/app", &root.clone().into_os_string()),
+            None
+        );
+
+        let mut entry = DesktopEntry::application("App", "app");
+        assert!(entry.try_exec_exists());
+        entry.set(Key::TryExec, second.join("app").display());
+        assert!(entry.try_exec_exists());
+        entry.set(Key::TryExec, root.join("missing").display());
+        assert!(!entry.try_exec_exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_path_or_uri() {
+        let uri = |uri: &str| PathOrUri::Uri(String::from(uri));
+
+        assert_eq!(
+            uri("file://localhost/tmp/a%20b").to_path(),
+            Some(PathBuf::from("/tmp/a b"))
+        );
+        assert_eq!(uri("file://host/tmp/a").to_path(), None);
+        assert_eq!(uri("https://example.org").to_path(), None);
+        assert_eq!(
+            PathOrUri::from(Path::new("/tmp/é")).to_uri(),
+            "file:///tmp/%C3%A9"
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+
+use crate::{
+    error::Error,
+    helpers::desktop_entry::{
+        Category, EntryType, Key, MimeType,
+        exec::{decoded, decoded_list},
+    },
+    parser::models::{
+        ContentEntry, DesktopFile, EntrySet, Group, Locale, MAIN_GROUP, TopLevelEntry,
+    },
+};
+
+/// Representation of a desktop entry file, with typed accessors to the standard keys of its `[Desktop Entry]` group.
+///
+/// The accessors read the unlocalized values with their escape sequences decoded,
+/// and the `localized_*` ones read the translations of the preferred locales,
+/// see [Group::find_preferred]. The other groups and keys are kept, and can be reached through [Self::file].
+/// ```
+/// use freedesktop_rs::{helpers::desktop_entry::DesktopEntry, parser::models::DesktopFile};
+///
+/// let file = DesktopFile::try_from("[Desktop Entry]\nType=Application\nName=App\nTerminal=true\nCategories=Game;Arcade;\n").unwrap();
+/// let entry = DesktopEntry::try_from(file).unwrap();
+///
+/// assert_eq!(entry.name().as_deref(), Some("App"));
+/// assert!(entry.terminal().unwrap());
+/// assert_eq!(entry.categories(), ["Game", "Arcade"]);
+/// ```
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DesktopEntry {
+    file: DesktopFile,
+    /// The path of the file, expanding `%k` in `Exec`.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) location: Option<std::path::PathBuf>,
+}
+
+impl TryFrom<DesktopFile> for DesktopEntry {
+    type Error = Error;
+
+    /// Fails with [Error::NotFound] if the file has no `[Desktop Entry]` group.
+    fn try_from(file: DesktopFile) -> Result<Self, Self::Error> {
+        file.get(MAIN_GROUP)?;
+        Ok(Self {
+            file,
+            #[cfg(feature = "std")]
+            location: None,
+        })
+    }
+}
+
+impl From<DesktopEntry> for DesktopFile {
+    fn from(entry: DesktopEntry) -> Self {
+        entry.file
+    }
+}
+
+impl DesktopEntry {
+    /// Creates an entry of the type with its name, without the other keys required by the type.
+    fn new(entry_type: EntryType, name: &str) -> Self {
+        let mut group = Group::new(MAIN_GROUP);
+        group.set(Key::Type.as_str(), entry_type);
+        group.set(Key::Name.as_str(), name);
+        Self {
+            file: DesktopFile {
+                content: vec![TopLevelEntry::Group(group)],
+                ..DesktopFile::default()
+            },
+            #[cfg(feature = "std")]
+            location: None,
+        }
+    }
+
+    /// Creates an application started with the command line, which may contain field codes.
+    /// ```
+    /// use freedesktop_rs::helpers::desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::application("App", "app %U");
+    /// assert_eq!(entry.file().to_string(), "[Desktop Entry]\nType=Application\nName=App\nExec=app %U");
+    /// ```
+    pub fn application(name: &str, exec: &str) -> Self {
+        let mut entry = Self::new(EntryType::Application, name);
+        entry.set(Key::Exec, exec);
+        entry
+    }
+
+    /// Creates a link to the URL.
+    pub fn link(name: &str, url: &str) -> Self {
+        let mut entry = Self::new(EntryType::Link, name);
+        entry.set(Key::Url, url);
+        entry
+    }
+
+    /// Creates a directory of a menu.
+    pub fn directory(name: &str) -> Self {
+        Self::new(EntryType::Directory, name)
+    }
+
+    /// Returns the whole file.
+    pub fn file(&self) -> &DesktopFile {
+        &self.file
+    }
+
+    /// Returns the `[Desktop Entry]` group.
+    pub fn group(&self) -> &Group {
+        self.file
+            .find(MAIN_GROUP)
+            .expect("The group is checked when the entry is created")
+    }
+
+    /// Similar to [Self::group], but the group can be modified.
+    pub fn group_mut(&mut self) -> &mut Group {
+        self.file
+            .find_mut(MAIN_GROUP)
+            .expect("The group is checked when the entry is created")
+    }
+
+    /// Returns the unlocalized entry of the key.
+    fn entry(&self, key: Key) -> Option<&ContentEntry> {
+        self.group().find_preferred(key.as_str(), &[])
+    }
+
+    /// Returns the single unlocalized value of the key, with its escape sequences decoded.
+    fn string(&self, key: Key) -> Option<Cow<'_, str>> {
+        self.entry(key).and_then(|entry| decoded(entry).ok())
+    }
+
+    /// Returns the single value of the key in the first of the locales which has one, or its unlocalized value.
+    fn localized(&self, key: Key, locales: &[Locale]) -> Option<Cow<'_, str>> {
+        self.group()
+            .find_preferred(key.as_str(), locales)
+            .and_then(|entry| decoded(entry).ok())
+    }
+
+    /// Returns the boolean of the key, `false` if it is missing as defined by the specification.
+    fn boolean(&self, key: Key) -> Result<bool, Error> {
+        self.entry(key).map_or(Ok(false), ContentEntry::as_bool)
+    }
+
+    /// Returns the unlocalized values of the key with their escape sequences decoded, which are empty if it is missing.
+    fn list(&self, key: Key) -> Vec<String> {
+        self.entry(key).map_or(vec![], decoded_list)
+    }
+
+    /// Returns the `Type`, which defines the keys allowed in the entry.
+    pub fn type_(&self) -> Option<EntryType> {
+        self.string(Key::Type).map(|value| EntryType::from(&*value))
+    }
+
+    /// Returns the `Version` of the specification followed by the file.
+    pub fn version(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Version)
+    }
+
+    /// Returns the `Name`.
+    pub fn name(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Name)
+    }
+
+    /// Returns the `Name` in the preferred locales.
+    pub fn localized_name(&self, locales: &[Locale]) -> Option<Cow<'_, str>> {
+        self.localized(Key::Name, locales)
+    }
+
+    /// Returns the `GenericName`, such as `Web Browser`.
+    pub fn generic_name(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::GenericName)
+    }
+
+    /// Returns the `GenericName` in the preferred locales.
+    pub fn localized_generic_name(&self, locales: &[Locale]) -> Option<Cow<'_, str>> {
+        self.localized(Key::GenericName, locales)
+    }
+
+    /// Returns the `Comment`, the tooltip of the entry.
+    pub fn comment(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Comment)
+    }
+
+    /// Returns the `Comment` in the preferred locales.
+    pub fn localized_comment(&self, locales: &[Locale]) -> Option<Cow<'_, str>> {
+        self.localized(Key::Comment, locales)
+    }
+
+    /// Returns the `Icon`, either an absolute path or the name of an icon of the theme.
+    pub fn icon(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Icon)
+    }
+
+    /// Returns the `Exec` command line, with its field codes, see [ExecExpander](super::ExecExpander).
+    pub fn exec(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Exec)
+    }
+
+    /// Returns the `TryExec` program, used to know whether the application is installed.
+    pub fn try_exec(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::TryExec)
+    }
+
+    /// Returns the `Path` of the working directory of the program.
+    pub fn path(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Path)
+    }
+
+    /// Returns the `URL` of a `Link` entry.
+    pub fn url(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::Url)
+    }
+
+    /// Returns the `StartupWMClass`, the class of the windows of the application.
+    pub fn startup_wm_class(&self) -> Option<Cow<'_, str>> {
+        self.string(Key::StartupWMClass)
+    }
+
+    /// Returns whether the program runs in a terminal, from `Terminal`.
+    pub fn terminal(&self) -> Result<bool, Error> {
+        self.boolean(Key::Terminal)
+    }
+
+    /// Returns whether the entry is not shown in menus, from `NoDisplay`.
+    pub fn no_display(&self) -> Result<bool, Error> {
+        self.boolean(Key::NoDisplay)
+    }
+
+    /// Returns whether the entry is considered deleted, from `Hidden`.
+    pub fn hidden(&self) -> Result<bool, Error> {
+        self.boolean(Key::Hidden)
+    }
+
+    /// Returns whether the application is activated with D-Bus, from `DBusActivatable`.
+    pub fn dbus_activatable(&self) -> Result<bool, Error> {
+        self.boolean(Key::DBusActivatable)
+    }
+
+    /// Returns whether the application sends a startup notification, from `StartupNotify`,
+    /// or `None` if it is missing, in which case the behavior is unknown.
+    pub fn startup_notify(&self) -> Option<Result<bool, Error>> {
+        self.entry(Key::StartupNotify).map(ContentEntry::as_bool)
+    }
+
+    /// Returns whether the application prefers a discrete GPU, from `PrefersNonDefaultGPU`.
+    pub fn prefers_non_default_gpu(&self) -> Result<bool, Error> {
+        self.boolean(Key::PrefersNonDefaultGpu)
+    }
+
+    /// Returns whether the application only has a single main window, from `SingleMainWindow`.
+    pub fn single_main_window(&self) -> Result<bool, Error> {
+        self.boolean(Key::SingleMainWindow)
+    }
+
+    /// Returns the desktop environments in which the entry is shown, from `OnlyShowIn`.
+    pub fn only_show_in(&self) -> Vec<String> {
+        self.list(Key::OnlyShowIn)
+    }
+
+    /// Returns the desktop environments in which the entry is not shown, from `NotShowIn`.
+    pub fn not_show_in(&self) -> Vec<String> {
+        self.list(Key::NotShowIn)
+    }
+
+    /// Returns whether the entry is shown in the desktop environments, by order of preference,
+    /// such as the ones of `XDG_CURRENT_DESKTOP`, according to `OnlyShowIn` and `NotShowIn`.
+    ///
+    /// The first desktop listed in either key decides, and the entry is hidden if none is listed in `OnlyShowIn`
+    /// when it is set. The names are compared case-sensitively, as the specification registers them.
+    /// `NoDisplay` and `Hidden` are not taken into account.
+    /// ```
+    /// use freedesktop_rs::{helpers::desktop_entry::DesktopEntry, parser::models::DesktopFile};
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nType=Application\nName=App\nExec=app\nOnlyShowIn=GNOME;XFCE;\n").unwrap();
+    /// let entry = DesktopEntry::try_from(file).unwrap();
+    ///
+    /// assert!(entry.should_show_in(&["ubuntu", "GNOME"]));
+    /// assert!(!entry.should_show_in(&["KDE"]));
+    /// ```
+    pub fn should_show_in(&self, desktops: &[&str]) -> bool {
+        let (only_show_in, not_show_in) = (self.only_show_in(), self.not_show_in());
+        for desktop in desktops {
+            if not_show_in.iter().any(|d| d == desktop) {
+                return false;
+            }
+            if only_show_in.iter().any(|d| d == desktop) {
+                return true;
+            }
+        }
+        only_show_in.is_empty()
+    }
+
+    /// Similar to [Self::should_show_in], with the desktops of the colon-separated `XDG_CURRENT_DESKTOP` variable.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    pub fn should_show_in_current_desktop(&self) -> bool {
+        let desktops = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        let desktops: Vec<&str> = desktops.split(':').filter(|d| !d.is_empty()).collect();
+        self.should_show_in(&desktops)
+    }
+
+    /// Returns the identifiers of the `[Desktop Action <action>]` groups, from `Actions`.
+    pub fn actions(&self) -> Vec<String> {
+        self.list(Key::Actions)
+    }
+
+    /// Returns the MIME types supported by the application, from `MimeType`.
+    ///
+    /// The invalid MIME types are left out, see [DesktopFile::validate] to report them.
+    pub fn mime_types(&self) -> Vec<MimeType> {
+        self.list(Key::MimeType)
+            .iter()
+            .filter_map(|mime_type| mime_type.parse().ok())
+            .collect()
+    }
+
+    /// Returns whether the application supports the MIME type, such as `image/png`,
+    /// either listed in `MimeType` or matched by one of its `type/*` wildcards.
+    /// ```
+    /// use freedesktop_rs::helpers::desktop_entry::{DesktopEntry, Key};
+    ///
+    /// let mut entry = DesktopEntry::application("Viewer", "viewer %f");
+    /// entry.group_mut().set_list(Key::MimeType.as_str(), ["image/*", "application/pdf"]);
+    ///
+    /// assert!(entry.supports_mime("image/png"));
+    /// assert!(entry.supports_mime("Application/PDF"));
+    /// assert!(!entry.supports_mime("text/plain"));
+    /// ```
+    pub fn supports_mime(&self, mime_type: &str) -> bool {
+        let Ok(mime_type) = mime_type.parse() else {
+            return false;
+        };
+        self.mime_types()
+            .iter()
+            .any(|supported| supported.matches(&mime_type))
+    }
+
+    /// Returns the `Categories` in which the entry is shown in menus.
+    pub fn categories(&self) -> Vec<String> {
+        self.list(Key::Categories)
+    }
+
+    /// Returns the parsed `Categories`, see [Self::categories] for their names.
+    pub fn typed_categories(&self) -> Vec<Category> {
+        self.categories()
+            .iter()
+            .map(|category| Category::from(category.as_str()))
+            .collect()
+    }
+
+    /// Returns the D-Bus interfaces implemented by the application, from `Implements`.
+    pub fn implements(&self) -> Vec<String> {
+        self.list(Key::Implements)
+    }
+
+    /// Returns the unlocalized `Keywords`, see [Keywords](super::Keywords) for their translations.
+    pub fn keywords(&self) -> Vec<String> {
+        self.list(Key::Keywords)
+    }
+
+    /// Sets the unlocalized value of the key, escaping it, see [Group::set].
+    pub fn set<T: core::fmt::Display>(&mut self, key: Key, value: T) {
+        self.group_mut().set(key.as_str(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        let file = DesktopFile::try_from(
+            "[Desktop Entry]
+Type=Application
+Name=App
+Name[fr]=Appli
+Comment=First line\\nsecond line
+Exec=app %U
+Terminal=false
+Keywords=a\\;b;c;
+NoDisplay=maybe
+MimeType=text/plain;image/png;
+[Desktop Action new]
+Name=New
+",
+        )
+        .unwrap();
+        let mut entry = DesktopEntry::try_from(file.clone()).unwrap();
+
+        assert_eq!(entry.type_(), Some(EntryType::Application));
+        assert_eq!(entry.name().as_deref(), Some("App"));
+        assert_eq!(
+            entry
+                .localized_name(&Locale::parse_list("fr_FR"))
+                .as_deref(),
+            Some("Appli")
+        );
+        assert_eq!(
+            entry.localized_name(&Locale::parse_list("de")).as_deref(),
+            Some("App")
+        );
+        assert_eq!(entry.exec().as_deref(), Some("app %U"));
+        assert_eq!(entry.comment().as_deref(), Some("First line\nsecond line"));
+        assert_eq!(entry.keywords(), ["a;b", "c"]);
+        assert_eq!(entry.icon(), None);
+        assert!(!entry.terminal().unwrap());
+        assert!(!entry.hidden().unwrap());
+        assert!(matches!(entry.no_display(), Err(Error::InvalidValue(_))));
+        assert!(entry.startup_notify().is_none());
+        assert_eq!(
+            entry.mime_types(),
+            ["text/plain", "image/png"].map(|m| m.parse::<MimeType>().unwrap())
+        );
+        assert!(entry.supports_mime("image/png"));
+        assert!(!entry.supports_mime("image/jpeg"));
+        assert!(entry.categories().is_empty());
+        entry
+            .group_mut()
+            .set_list(Key::Categories.as_str(), ["Game", "X-Retro"]);
+        assert_eq!(
+            entry.typed_categories(),
+            [Category::Game, Category::Other(String::from("X-Retro"))]
+        );
+
+        entry.set(Key::Icon, "app");
+        assert_eq!(entry.icon().as_deref(), Some("app"));
+        assert_eq!(
+            DesktopFile::from(entry)["Desktop Action new"].header,
+            "Desktop Action new"
+        );
+
+        assert!(matches!(
+            DesktopEntry::try_from(DesktopFile::try_from("[Other]\nName=App\n").unwrap()),
+            Err(Error::NotFound(_))
+        ));
+
+        let link = DesktopEntry::link("Site", "https://example.org");
+        assert_eq!(link.type_(), Some(EntryType::Link));
+        assert_eq!(link.url().as_deref(), Some("https://example.org"));
+        assert_eq!(DesktopEntry::directory("Games").group().content.len(), 2);
+    }
+
+    #[test]
+    fn test_should_show_in() {
+        let mut entry = DesktopEntry::application("App", "app");
+        assert!(entry.should_show_in(&[]));
+        assert!(entry.should_show_in(&["KDE"]));
+
+        entry
+            .group_mut()
+            .set_list(Key::NotShowIn.as_str(), ["KDE", "LXQt"]);
+        assert!(!entry.should_show_in(&["KDE"]));
+        assert!(!entry.should_show_in(&["Budgie", "LXQt"]));
+        assert!(entry.should_show_in(&["GNOME"]));
+        assert!(entry.should_show_in(&["kde"]));
+
+        entry
+            .group_mut()
+            .set_list(Key::OnlyShowIn.as_str(), ["GNOME", "LXQt"]);
+        assert!(entry.should_show_in(&["GNOME", "KDE"]));
+        assert!(!entry.should_show_in(&["KDE", "GNOME"]));
+        assert!(!entry.should_show_in(&["XFCE"]));
+        assert!(!entry.should_show_in(&[]));
+    }
+}
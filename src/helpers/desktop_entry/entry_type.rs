@@ -0,0 +1,132 @@
+use alloc::{borrow::ToOwned, string::String};
+use core::{convert::Infallible, fmt::Display, str::FromStr};
+
+use crate::helpers::desktop_entry::Key;
+
+/// The `Type` of a desktop entry, which defines the keys it may contain.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum EntryType {
+    /// An application, started with its `Exec` command line.
+    Application,
+    /// A link to the `URL`.
+    Link,
+    /// A directory of a menu, described by a `.directory` file.
+    Directory,
+    /// A type which is not defined by the specification, which should be ignored.
+    Unknown(String),
+}
+
+/// Keys which are required in every desktop entry.
+const REQUIRED_KEYS: &[Key] = &[Key::Type, Key::Name];
+
+/// Keys which are required in `Link` entries.
+const REQUIRED_LINK_KEYS: &[Key] = &[Key::Type, Key::Name, Key::Url];
+
+impl EntryType {
+    /// Returns the type as written in a file.
+    pub fn as_str(&self) -> &str {
+        match self {
+            EntryType::Application => "Application",
+            EntryType::Link => "Link",
+            EntryType::Directory => "Directory",
+            EntryType::Unknown(entry_type) => entry_type,
+        }
+    }
+
+    /// Returns the keys which must be in entries of this type.
+    ///
+    /// `Exec` is not listed, as applications activated with D-Bus may leave it out, see [Self::requires_exec].
+    pub fn required_keys(&self) -> &'static [Key] {
+        match self {
+            EntryType::Link => REQUIRED_LINK_KEYS,
+            _ => REQUIRED_KEYS,
+        }
+    }
+
+    /// Returns whether `Exec` is required, which is the case of applications unless `DBusActivatable` is set.
+    pub fn requires_exec(&self, dbus_activatable: bool) -> bool {
+        *self == EntryType::Application && !dbus_activatable
+    }
+
+    /// Returns whether the key may be in entries of this type.
+    ///
+    /// `URL` is only allowed in links, and the keys describing how to start a program only in applications.
+    /// Every key is allowed in entries of an unknown type.
+    pub fn allows(&self, key: Key) -> bool {
+        match key {
+            Key::Url => matches!(self, EntryType::Link | EntryType::Unknown(_)),
+            Key::DBusActivatable
+            | Key::TryExec
+            | Key::Exec
+            | Key::Path
+            | Key::Terminal
+            | Key::Actions
+            | Key::MimeType
+            | Key::Categories
+            | Key::Implements
+            | Key::Keywords
+            | Key::StartupNotify
+            | Key::StartupWMClass
+            | Key::PrefersNonDefaultGpu
+            | Key::SingleMainWindow => {
+                matches!(self, EntryType::Application | EntryType::Unknown(_))
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Display for EntryType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for EntryType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl From<&str> for EntryType {
+    /// Parses the type, which is [EntryType::Unknown] if it is not defined by the specification.
+    fn from(value: &str) -> Self {
+        match value {
+            "Application" => EntryType::Application,
+            "Link" => EntryType::Link,
+            "Directory" => EntryType::Directory,
+            other => EntryType::Unknown(other.to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_entry_type() {
+        assert_eq!(EntryType::from("Link"), EntryType::Link);
+        assert_eq!(
+            EntryType::from("Service"),
+            EntryType::Unknown(String::from("Service"))
+        );
+        assert_eq!(EntryType::Directory.to_string(), "Directory");
+
+        assert!(EntryType::Link.required_keys().contains(&Key::Url));
+        assert!(!EntryType::Application.required_keys().contains(&Key::Url));
+        assert!(EntryType::Application.requires_exec(false));
+        assert!(!EntryType::Application.requires_exec(true));
+        assert!(!EntryType::Link.requires_exec(false));
+
+        assert!(EntryType::Link.allows(Key::Url));
+        assert!(!EntryType::Application.allows(Key::Url));
+        assert!(!EntryType::Directory.allows(Key::Exec));
+        assert!(EntryType::Directory.allows(Key::Icon));
+        assert!(EntryType::Unknown(String::from("Service")).allows(Key::Exec));
+    }
+}
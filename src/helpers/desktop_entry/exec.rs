@@ -0,0 +1,458 @@
+use core::fmt::Display;
+
+use crate::{
+    error::{Error, ExecError},
+    parser::models::{ContentEntry, EntrySet, Group, Locale},
+};
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+
+/// A field code of `Exec`, replaced when the application is started.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum FieldCode {
+    /// `%f`, a single file.
+    File,
+    /// `%F`, a list of files.
+    Files,
+    /// `%u`, a single URL.
+    Url,
+    /// `%U`, a list of URLs.
+    Urls,
+    /// `%i`, the `Icon` key preceded by `--icon`.
+    Icon,
+    /// `%c`, the translated `Name`.
+    Name,
+    /// `%k`, the location of the desktop entry file.
+    Location,
+}
+
+impl FieldCode {
+    /// Returns the field code of the character following `%`, if it is defined and not deprecated.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'f' => Some(FieldCode::File),
+            'F' => Some(FieldCode::Files),
+            'u' => Some(FieldCode::Url),
+            'U' => Some(FieldCode::Urls),
+            'i' => Some(FieldCode::Icon),
+            'c' => Some(FieldCode::Name),
+            'k' => Some(FieldCode::Location),
+            _ => None,
+        }
+    }
+
+    /// Returns the character following `%`.
+    pub fn as_char(&self) -> char {
+        match self {
+            FieldCode::File => 'f',
+            FieldCode::Files => 'F',
+            FieldCode::Url => 'u',
+            FieldCode::Urls => 'U',
+            FieldCode::Icon => 'i',
+            FieldCode::Name => 'c',
+            FieldCode::Location => 'k',
+        }
+    }
+
+    /// Returns whether the code is replaced by files or URLs.
+    pub fn is_file(&self) -> bool {
+        matches!(
+            self,
+            FieldCode::File | FieldCode::Files | FieldCode::Url | FieldCode::Urls
+        )
+    }
+
+    /// Returns whether the code must be an argument of its own, as it may be replaced by several arguments.
+    pub fn is_standalone(&self) -> bool {
+        matches!(self, FieldCode::Files | FieldCode::Urls | FieldCode::Icon)
+    }
+}
+
+impl Display for FieldCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "%{}", self.as_char())
+    }
+}
+
+/// A part of an argument of `Exec`.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum ExecToken {
+    /// Text, unquoted and unescaped, in which `%%` is written as `%`.
+    Text(String),
+    /// A field code.
+    Code(FieldCode),
+}
+
+/// Splits the command line of `Exec`, with its escape sequences decoded, into arguments made of tokens.
+///
+/// Arguments are separated by spaces, unless they are between double quotes, in which `"`, `` ` ``, `$` and `\`
+/// are escaped with a backslash. Field codes are not allowed in quoted arguments, where only `%%` may be written.
+/// Deprecated field codes are rejected, as well as codes which must be an argument of their own,
+/// such as `%F`, and command lines with several codes of files or URLs.
+/// ```
+/// use freedesktop_rs::helpers::desktop_entry::exec::{ExecToken, FieldCode, tokenize};
+///
+/// let arguments = tokenize("\"/opt/my app/run\" --name=%c %U").unwrap();
+///
+/// assert_eq!(arguments[0], [ExecToken::Text(String::from("/opt/my app/run"))]);
+/// assert_eq!(
+///     arguments[1],
+///     [ExecToken::Text(String::from("--name=")), ExecToken::Code(FieldCode::Name)]
+/// );
+/// assert_eq!(arguments[2], [ExecToken::Code(FieldCode::Urls)]);
+/// ```
+pub fn tokenize(exec: &str) -> Result<Vec<Vec<ExecToken>>, ExecError> {
+    let mut arguments = vec![];
+    let mut current: Option<Vec<ExecToken>> = None;
+    let mut text = String::new();
+    let mut file_codes = 0;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {
+                if let Some(tokens) = current.take() {
+                    arguments.push(finish_argument(tokens, &mut text)?);
+                }
+            }
+            '"' => {
+                current.get_or_insert_with(Vec::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '`' | '$' | '\\')) => text.push(c),
+                            _ => return Err(ExecError::InvalidEscape),
+                        },
+                        // Field codes would be expanded within the argument, which a shell may then run
+                        Some('%') => match chars.next() {
+                            Some('%') => text.push('%'),
+                            Some(c) => return Err(ExecError::QuotedCode(c)),
+                            None => return Err(ExecError::UnterminatedQuote),
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err(ExecError::UnterminatedQuote),
+                    }
+                }
+            }
+            '%' => {
+                let tokens = current.get_or_insert_with(Vec::new);
+                let code = match chars.next() {
+                    Some('%') => {
+                        text.push('%');
+                        continue;
+                    }
+                    Some(c @ ('d' | 'D' | 'n' | 'N' | 'v' | 'm')) => {
+                        return Err(ExecError::DeprecatedCode(c));
+                    }
+                    Some(c) => FieldCode::from_char(c).ok_or(ExecError::UnknownCode(Some(c)))?,
+                    None => return Err(ExecError::UnknownCode(None)),
+                };
+                if code.is_file() {
+                    file_codes += 1;
+                }
+                if !text.is_empty() {
+                    tokens.push(ExecToken::Text(core::mem::take(&mut text)));
+                }
+                tokens.push(ExecToken::Code(code));
+            }
+            c => {
+                current.get_or_insert_with(Vec::new);
+                text.push(c);
+            }
+        }
+    }
+    if let Some(tokens) = current {
+        arguments.push(finish_argument(tokens, &mut text)?);
+    }
+
+    if file_codes > 1 {
+        return Err(ExecError::SeveralFileCodes);
+    }
+    Ok(arguments)
+}
+
+/// Appends the remaining text to the tokens of the argument, and checks that its codes may be part of it.
+fn finish_argument(
+    mut tokens: Vec<ExecToken>,
+    text: &mut String,
+) -> Result<Vec<ExecToken>, ExecError> {
+    if !text.is_empty() || tokens.is_empty() {
+        tokens.push(ExecToken::Text(core::mem::take(text)));
+    }
+    match tokens.iter().find_map(|token| match token {
+        ExecToken::Code(code) if code.is_standalone() && tokens.len() > 1 => Some(*code),
+        _ => None,
+    }) {
+        Some(code) => Err(ExecError::NotStandalone(code)),
+        None => Ok(tokens),
+    }
+}
+
+/// Characters which the specification reserves, and which must be quoted in an argument of `Exec`.
+const RESERVED: &[char] = &[
+    ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')',
+    '`',
+];
+
+/// Quotes the argument so that it is read back as is by [tokenize], and `%` is doubled so that it is not a field code.
+///
+/// The argument is only quoted if it is empty or contains reserved characters,
+/// in which case `"`, `` ` ``, `$` and `\` are escaped with a backslash.
+/// The value still has to be escaped to be written in a file, see [crate::parser::escape_value].
+/// ```
+/// use freedesktop_rs::helpers::desktop_entry::exec::quote;
+///
+/// assert_eq!(quote("/usr/bin/app"), "/usr/bin/app");
+/// assert_eq!(quote("my \"app\""), "\"my \\\"app\\\"\"");
+/// assert_eq!(quote("100%"), "100%%");
+/// ```
+pub fn quote(argument: &str) -> Cow<'_, str> {
+    let doubled = match argument.contains('%') {
+        true => Cow::Owned(argument.replace('%', "%%")),
+        false => Cow::Borrowed(argument),
+    };
+    if !doubled.is_empty() && !doubled.contains(RESERVED) {
+        return doubled;
+    }
+
+    let mut quoted = String::with_capacity(doubled.len() + 2);
+    quoted.push('"');
+    for c in doubled.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+/// Expands the `Exec` key of a desktop entry group into the arguments of the command.
+///
+/// The field codes which depend on the other keys of the entry are substituted:
+/// `%i` becomes `--icon` followed by the `Icon` value, or nothing if there is no icon,
+/// and `%c` becomes the `Name` translated to the locale, if any.
+/// `%%` becomes `%`, and the codes of files, URLs and of the location are removed.
+/// The command line is read by [tokenize], so deprecated codes and codes in quoted arguments are rejected.
+/// ```
+/// use freedesktop_rs::{helpers::desktop_entry::ExecExpander, parser::models::Group};
+///
+/// let group: Group = "[Desktop Entry]\nName=Viewer\nName[fr]=Visionneuse\nIcon=viewer\nExec=viewer %i --title=%c %U"
+///     .parse()
+///     .unwrap();
+/// let french = "fr_FR".parse().unwrap();
+///
+/// assert_eq!(
+///     ExecExpander::new(&group).with_locale(&french).expand().unwrap(),
+///     ["viewer", "--icon", "viewer", "--title=Visionneuse"]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ExecExpander<'a> {
+    group: &'a Group,
+    locale: Option<&'a Locale>,
+}
+
+impl<'a> ExecExpander<'a> {
+    /// Creates the expander of the group, using the unlocalized `Name`.
+    pub fn new(group: &'a Group) -> Self {
+        Self {
+            group,
+            locale: None,
+        }
+    }
+
+    /// Uses the `Name` translated to this locale for `%c`.
+    pub fn with_locale(mut self, locale: &'a Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Returns the arguments of the command, starting with the program.
+    ///
+    /// Fails with [Error::NotFound] if the group has no `Exec` key,
+    /// or with [Error::InvalidValue] if it cannot be [tokenized](tokenize).
+    pub fn expand(&self) -> Result<Vec<String>, Error> {
+        let exec = decoded(self.group.get("Exec")?)?;
+        let mut arguments = vec![];
+
+        for tokens in tokenize(&exec)? {
+            if tokens == [ExecToken::Code(FieldCode::Icon)] {
+                if let Some(icon) = self.icon()? {
+                    arguments.push(String::from("--icon"));
+                    arguments.push(icon);
+                }
+                continue;
+            }
+
+            let mut expanded = String::new();
+            for token in &tokens {
+                match token {
+                    ExecToken::Text(text) => expanded.push_str(text),
+                    ExecToken::Code(FieldCode::Name) => expanded.push_str(&self.name()?),
+                    // Files, URLs and the location are not known by the expander
+                    ExecToken::Code(_) => {}
+                }
+            }
+            let only_codes = tokens
+                .iter()
+                .all(|token| matches!(token, ExecToken::Code(_)));
+            if !only_codes || !expanded.is_empty() {
+                arguments.push(expanded);
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    fn icon(&self) -> Result<Option<String>, Error> {
+        match self.group.find("Icon") {
+            Some(entry) if !entry.values.is_empty() => Ok(Some(decoded(entry)?.into_owned())),
+            _ => Ok(None),
+        }
+    }
+
+    fn name(&self) -> Result<String, Error> {
+        let entry = match self.locale {
+            Some(locale) => self.group.get_localized("Name", locale)?,
+            None => self.group.get("Name")?,
+        };
+        decoded(entry).map(Cow::into_owned)
+    }
+}
+
+/// Returns the single value of the entry, with its escape sequences decoded.
+pub(crate) fn decoded(entry: &ContentEntry) -> Result<Cow<'_, str>, Error> {
+    entry.decoded_string()
+}
+
+/// Returns the values of the entry, with their escape sequences decoded.
+pub(crate) fn decoded_list(entry: &ContentEntry) -> Vec<String> {
+    entry.decoded_values()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    fn expand(group: &str, locale: Option<&str>) -> Result<Vec<String>, Error> {
+        let group: Group = format!("[Desktop Entry]\n{group}").parse().unwrap();
+        let locale = locale.map(|l| l.parse::<Locale>().unwrap());
+        let mut expander = ExecExpander::new(&group);
+        if let Some(locale) = &locale {
+            expander = expander.with_locale(locale);
+        }
+        expander.expand()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let text = |text: &str| ExecToken::Text(String::from(text));
+
+        assert_eq!(
+            tokenize("app \"\" 100%% -k%k %i").unwrap(),
+            [
+                vec![text("app")],
+                vec![text("")],
+                vec![text("100%")],
+                vec![text("-k"), ExecToken::Code(FieldCode::Location)],
+                vec![ExecToken::Code(FieldCode::Icon)],
+            ]
+        );
+        assert_eq!(tokenize("app %d"), Err(ExecError::DeprecatedCode('d')));
+        assert_eq!(tokenize("app %z"), Err(ExecError::UnknownCode(Some('z'))));
+        assert_eq!(tokenize("app 100%"), Err(ExecError::UnknownCode(None)));
+        assert_eq!(
+            tokenize("app --files=%F"),
+            Err(ExecError::NotStandalone(FieldCode::Files))
+        );
+        assert_eq!(tokenize("app %f %u"), Err(ExecError::SeveralFileCodes));
+        assert_eq!(tokenize("\"app"), Err(ExecError::UnterminatedQuote));
+        assert_eq!(tokenize("\"app\\n\""), Err(ExecError::InvalidEscape));
+        assert_eq!(
+            tokenize("sh -c \"cat %f\""),
+            Err(ExecError::QuotedCode('f'))
+        );
+        assert_eq!(
+            tokenize("echo \"100%% %%f\"").unwrap()[1],
+            [text("100% %f")]
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        for argument in ["app", "", "a b", "$HOME", "\\", "%U", "`ls`", "it's"] {
+            let quoted = quote(argument);
+            assert_eq!(
+                tokenize(&quoted).unwrap(),
+                [vec![ExecToken::Text(String::from(argument))]],
+                "{quoted}"
+            );
+        }
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("a b"), "\"a b\"");
+    }
+
+    #[test]
+    fn test_icon() {
+        assert_eq!(
+            expand("Icon=app\nExec=app %i %f", None).unwrap(),
+            ["app", "--icon", "app"]
+        );
+        assert_eq!(expand("Exec=app %i", None).unwrap(), ["app"]);
+        assert_eq!(expand("Icon=\nExec=app %i", None).unwrap(), ["app"]);
+        assert_eq!(
+            expand("Icon=/opt/My App/icon.png\nExec=app %i", None).unwrap(),
+            ["app", "--icon", "/opt/My App/icon.png"]
+        );
+    }
+
+    #[test]
+    fn test_name() {
+        let group = "Name=Editor\nName[de]=Bearbeiter\nName[de_CH]=Editor CH\nName[de@formal]=Formal\nExec=\"editor\" --name=%c 100%%";
+
+        assert_eq!(
+            expand(group, None).unwrap(),
+            ["editor", "--name=Editor", "100%"]
+        );
+        assert_eq!(
+            expand(group, Some("de_CH@formal")).unwrap()[1],
+            "--name=Editor CH"
+        );
+        assert_eq!(
+            expand(group, Some("de_AT")).unwrap()[1],
+            "--name=Bearbeiter"
+        );
+        assert_eq!(
+            expand(group, Some("de_AT@formal")).unwrap()[1],
+            "--name=Formal"
+        );
+        assert_eq!(expand(group, Some("fr")).unwrap()[1], "--name=Editor");
+        assert!(matches!(
+            expand("Exec=app %c", None),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(
+            expand(
+                "Exec=\"/opt/my app/run\" \"a \\\\\\\\ \\\\\"b\\\\\"\"  x",
+                None
+            )
+            .unwrap(),
+            ["/opt/my app/run", "a \\ \"b\"", "x"]
+        );
+        assert!(matches!(
+            expand("Exec=\"app", None),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            expand("Exec=app %z", None),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+}
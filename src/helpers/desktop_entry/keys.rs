@@ -0,0 +1,291 @@
+use alloc::borrow::ToOwned;
+use core::{fmt::Display, str::FromStr};
+
+use crate::error::Error;
+#[cfg(feature = "std")]
+use crate::registry::Registry;
+
+/// Registry of the types of the known keys, initialized with the standard keys.
+///
+/// Extensions such as `X-KDE-*` keys can be registered to be typed as well.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub static KEY_REGISTRY: Registry<ValueType> = Registry::new(|| {
+    Key::ALL
+        .iter()
+        .map(|key| (key.as_str().to_owned(), key.value_type()))
+        .collect()
+});
+
+/// Type of the value of a key, as per the specification.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum ValueType {
+    /// An ASCII string.
+    String,
+    /// A user displayable string which may be localized.
+    LocaleString,
+    /// The name of an icon or an absolute path, which may be localized.
+    IconString,
+    /// Either `true` or `false`.
+    Boolean,
+    /// A locale-independent floating point number.
+    Numeric,
+    /// A list of strings separated by semicolons.
+    Strings,
+    /// A list of user displayable strings separated by semicolons, which may be localized.
+    LocaleStrings,
+}
+
+impl ValueType {
+    /// Returns whether the value may be localized.
+    pub fn is_localized(&self) -> bool {
+        matches!(
+            self,
+            ValueType::LocaleString | ValueType::IconString | ValueType::LocaleStrings
+        )
+    }
+
+    /// Returns whether the value is a list of values.
+    pub fn is_list(&self) -> bool {
+        matches!(self, ValueType::Strings | ValueType::LocaleStrings)
+    }
+
+    /// Returns the type of the value of the key, or `None` if the key is unknown.
+    ///
+    /// With the `std` feature, this is the type registered in [KEY_REGISTRY], so that registered extensions are typed too.
+    /// Otherwise, only the standard keys are known.
+    pub fn of_key(key: &str) -> Option<ValueType> {
+        #[cfg(feature = "std")]
+        {
+            KEY_REGISTRY.get(key)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            key.parse::<Key>().ok().map(|key| key.value_type())
+        }
+    }
+}
+
+/// Standard key of the `[Desktop Entry]` group, as of version 1.5 of the specification.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum Key {
+    /// `Type`
+    Type,
+    /// `Version`
+    Version,
+    /// `Name`
+    Name,
+    /// `GenericName`
+    GenericName,
+    /// `NoDisplay`
+    NoDisplay,
+    /// `Comment`
+    Comment,
+    /// `Icon`
+    Icon,
+    /// `Hidden`
+    Hidden,
+    /// `OnlyShowIn`
+    OnlyShowIn,
+    /// `NotShowIn`
+    NotShowIn,
+    /// `DBusActivatable`
+    DBusActivatable,
+    /// `TryExec`
+    TryExec,
+    /// `Exec`
+    Exec,
+    /// `Path`
+    Path,
+    /// `Terminal`
+    Terminal,
+    /// `Actions`
+    Actions,
+    /// `MimeType`
+    MimeType,
+    /// `Categories`
+    Categories,
+    /// `Implements`
+    Implements,
+    /// `Keywords`
+    Keywords,
+    /// `StartupNotify`
+    StartupNotify,
+    /// `StartupWMClass`
+    StartupWMClass,
+    /// `URL`
+    Url,
+    /// `PrefersNonDefaultGPU`
+    PrefersNonDefaultGpu,
+    /// `SingleMainWindow`
+    SingleMainWindow,
+}
+
+impl Key {
+    /// All the standard keys, in the order of the specification.
+    pub const ALL: &[Key] = &[
+        Key::Type,
+        Key::Version,
+        Key::Name,
+        Key::GenericName,
+        Key::NoDisplay,
+        Key::Comment,
+        Key::Icon,
+        Key::Hidden,
+        Key::OnlyShowIn,
+        Key::NotShowIn,
+        Key::DBusActivatable,
+        Key::TryExec,
+        Key::Exec,
+        Key::Path,
+        Key::Terminal,
+        Key::Actions,
+        Key::MimeType,
+        Key::Categories,
+        Key::Implements,
+        Key::Keywords,
+        Key::StartupNotify,
+        Key::StartupWMClass,
+        Key::Url,
+        Key::PrefersNonDefaultGpu,
+        Key::SingleMainWindow,
+    ];
+
+    /// Returns the key as written in a file.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Key::Type => "Type",
+            Key::Version => "Version",
+            Key::Name => "Name",
+            Key::GenericName => "GenericName",
+            Key::NoDisplay => "NoDisplay",
+            Key::Comment => "Comment",
+            Key::Icon => "Icon",
+            Key::Hidden => "Hidden",
+            Key::OnlyShowIn => "OnlyShowIn",
+            Key::NotShowIn => "NotShowIn",
+            Key::DBusActivatable => "DBusActivatable",
+            Key::TryExec => "TryExec",
+            Key::Exec => "Exec",
+            Key::Path => "Path",
+            Key::Terminal => "Terminal",
+            Key::Actions => "Actions",
+            Key::MimeType => "MimeType",
+            Key::Categories => "Categories",
+            Key::Implements => "Implements",
+            Key::Keywords => "Keywords",
+            Key::StartupNotify => "StartupNotify",
+            Key::StartupWMClass => "StartupWMClass",
+            Key::Url => "URL",
+            Key::PrefersNonDefaultGpu => "PrefersNonDefaultGPU",
+            Key::SingleMainWindow => "SingleMainWindow",
+        }
+    }
+
+    /// Returns the type of the value of the key.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Key::Type
+            | Key::Version
+            | Key::TryExec
+            | Key::Exec
+            | Key::Path
+            | Key::StartupWMClass
+            | Key::Url => ValueType::String,
+            Key::Name | Key::GenericName | Key::Comment => ValueType::LocaleString,
+            Key::Icon => ValueType::IconString,
+            Key::NoDisplay
+            | Key::Hidden
+            | Key::DBusActivatable
+            | Key::Terminal
+            | Key::StartupNotify
+            | Key::PrefersNonDefaultGpu
+            | Key::SingleMainWindow => ValueType::Boolean,
+            Key::OnlyShowIn
+            | Key::NotShowIn
+            | Key::Actions
+            | Key::MimeType
+            | Key::Categories
+            | Key::Implements => ValueType::Strings,
+            Key::Keywords => ValueType::LocaleStrings,
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Key::ALL
+            .iter()
+            .find(|k| k.as_str() == s)
+            .copied()
+            .ok_or(Error::NotFound(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Table of the recognized keys of the specification 1.5.
+    const SPEC_KEYS: &[(&str, ValueType)] = &[
+        ("Type", ValueType::String),
+        ("Version", ValueType::String),
+        ("Name", ValueType::LocaleString),
+        ("GenericName", ValueType::LocaleString),
+        ("NoDisplay", ValueType::Boolean),
+        ("Comment", ValueType::LocaleString),
+        ("Icon", ValueType::IconString),
+        ("Hidden", ValueType::Boolean),
+        ("OnlyShowIn", ValueType::Strings),
+        ("NotShowIn", ValueType::Strings),
+        ("DBusActivatable", ValueType::Boolean),
+        ("TryExec", ValueType::String),
+        ("Exec", ValueType::String),
+        ("Path", ValueType::String),
+        ("Terminal", ValueType::Boolean),
+        ("Actions", ValueType::Strings),
+        ("MimeType", ValueType::Strings),
+        ("Categories", ValueType::Strings),
+        ("Implements", ValueType::Strings),
+        ("Keywords", ValueType::LocaleStrings),
+        ("StartupNotify", ValueType::Boolean),
+        ("StartupWMClass", ValueType::String),
+        ("URL", ValueType::String),
+        ("PrefersNonDefaultGPU", ValueType::Boolean),
+        ("SingleMainWindow", ValueType::Boolean),
+    ];
+
+    #[test]
+    fn spec_keys_coverage() {
+        assert_eq!(Key::ALL.len(), SPEC_KEYS.len());
+
+        for (name, value_type) in SPEC_KEYS {
+            let key: Key = name.parse().unwrap();
+            assert_eq!(key.as_str(), *name);
+            assert_eq!(key.value_type(), *value_type, "{name}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn key_registry() {
+        assert_eq!(KEY_REGISTRY.get("Keywords"), Some(ValueType::LocaleStrings));
+
+        KEY_REGISTRY.register("X-Test-Flags", ValueType::Strings);
+        assert_eq!(KEY_REGISTRY.get("X-Test-Flags"), Some(ValueType::Strings));
+        KEY_REGISTRY.unregister("X-Test-Flags");
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert!(matches!("X-Custom".parse::<Key>(), Err(Error::NotFound(_))));
+    }
+}
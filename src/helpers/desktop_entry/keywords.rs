@@ -0,0 +1,165 @@
+use crate::parser::models::{ContentEntry, Entry, EntryFormat, Group, Locale, escape_kept_value};
+use alloc::{string::String, vec::Vec};
+
+/// The key of the keywords.
+const KEY: &str = "Keywords";
+
+/// The `Keywords` of a group, for each of their locales.
+///
+/// Keywords are decoded, so that a keyword containing an escaped separator, such as `a\;b`, is returned as `a;b`.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Keywords {
+    locales: Vec<(Option<Locale>, Vec<String>)>,
+}
+
+impl Keywords {
+    /// Creates keywords without any locale.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the keywords of the group, in the order of their entries.
+    pub fn from_group(group: &Group) -> Self {
+        let locales = group
+            .content
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Content(content) if content.key == KEY => Some(content),
+                _ => None,
+            })
+            .map(|entry| {
+                let keywords = entry
+                    .decoded_values()
+                    .into_iter()
+                    .filter(|value| !value.is_empty())
+                    .collect();
+                (entry.locale.clone(), keywords)
+            })
+            .collect();
+        Self { locales }
+    }
+
+    /// Returns the keywords of the locale, or the unlocalized ones if `locale` is `None`.
+    pub fn get(&self, locale: Option<&Locale>) -> Option<&[String]> {
+        self.locales
+            .iter()
+            .find(|(l, _)| l.as_ref() == locale)
+            .map(|(_, keywords)| keywords.as_slice())
+    }
+
+    /// Returns the locales of the keywords, `None` being the unlocalized ones.
+    pub fn locales(&self) -> impl Iterator<Item = Option<&Locale>> {
+        self.locales.iter().map(|(locale, _)| locale.as_ref())
+    }
+
+    /// Sets the keywords of the locale, or the unlocalized ones if `locale` is `None`.
+    pub fn set<S: Into<String>>(
+        &mut self,
+        locale: Option<Locale>,
+        keywords: impl IntoIterator<Item = S>,
+    ) {
+        let keywords = keywords.into_iter().map(Into::into).collect();
+        match self.locales.iter_mut().find(|(l, _)| *l == locale) {
+            Some((_, existing)) => *existing = keywords,
+            None => self.locales.push((locale, keywords)),
+        }
+    }
+
+    /// Removes the keywords of the locale, and returns them.
+    pub fn remove(&mut self, locale: Option<&Locale>) -> Option<Vec<String>> {
+        let position = self
+            .locales
+            .iter()
+            .position(|(l, _)| l.as_ref() == locale)?;
+        Some(self.locales.remove(position).1)
+    }
+
+    /// Writes the keywords to the group, escaping the separators they contain.
+    ///
+    /// Existing entries are updated in place, and the keywords of the removed locales are removed from the group.
+    pub fn write_to(&self, group: &mut Group) {
+        group.content.retain(|entry| match entry {
+            Entry::Content(content) if content.key == KEY => self
+                .locales
+                .iter()
+                .any(|(locale, _)| *locale == content.locale),
+            _ => true,
+        });
+
+        for (locale, keywords) in &self.locales {
+            let values: Vec<String> = keywords
+                .iter()
+                .map(|keyword| escape_kept_value(keyword, Some(';')))
+                .collect();
+            let existing = group.content.iter_mut().find_map(|entry| match entry {
+                Entry::Content(content) if content.key == KEY && content.locale == *locale => {
+                    Some(content)
+                }
+                _ => None,
+            });
+            match existing {
+                Some(entry) => {
+                    entry.values = values;
+                    entry.format.raw_values = None;
+                    entry.format.escapes_decoded = false;
+                }
+                None => group.content.push(Entry::Content(ContentEntry {
+                    key: String::from(KEY),
+                    values,
+                    locale: locale.clone(),
+                    format: EntryFormat {
+                        trailing_separator: true,
+                        ..EntryFormat::default()
+                    },
+                })),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::parser::models::{DesktopFile, EntrySet};
+
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let file = "[Desktop Entry]
+Keywords=a\\;b;c;
+Keywords[fr]=back\\\\;slash;
+Keywords[de]=d;";
+        let mut parsed = DesktopFile::try_from(file).unwrap();
+        let group = parsed.get_mut("Desktop Entry").unwrap();
+        let french = "fr".parse::<Locale>().unwrap();
+        let german = "de".parse::<Locale>().unwrap();
+
+        let mut keywords = Keywords::from_group(group);
+        assert_eq!(
+            keywords.get(None).unwrap(),
+            [String::from("a;b"), String::from("c")]
+        );
+        assert_eq!(
+            keywords.get(Some(&french)).unwrap(),
+            [String::from("back\\"), String::from("slash")]
+        );
+
+        keywords.write_to(group);
+        assert_eq!(parsed.to_string(), file);
+
+        keywords.set(None, ["x;y"]);
+        keywords.remove(Some(&german));
+        keywords.set(Some("it".parse().unwrap()), ["z"]);
+        let group = parsed.get_mut("Desktop Entry").unwrap();
+        keywords.write_to(group);
+        assert_eq!(
+            parsed.to_string(),
+            "[Desktop Entry]
+Keywords=x\\;y;
+Keywords[fr]=back\\\\;slash;
+Keywords[it]=z;"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    ffi::OsString,
+    process::{Child, Command},
+};
+
+use crate::{
+    error::Error,
+    helpers::desktop_entry::{
+        DesktopEntry, Key, PathOrUri,
+        exec::{ExecToken, FieldCode, decoded, tokenize},
+    },
+    parser::models::{EntrySet, Locale},
+};
+
+/// Options changing how applications are started, see [DesktopEntry::launch].
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    /// The terminal emulator in which the applications with `Terminal=true` run,
+    /// followed by the arguments preceding the command. Defaults to `xterm -e`.
+    pub terminal: Vec<OsString>,
+
+    /// The preferred locales, in which `%c` is translated. Defaults to none, which uses the unlocalized `Name`.
+    pub locales: Vec<Locale>,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            terminal: vec![OsString::from("xterm"), OsString::from("-e")],
+            locales: vec![],
+        }
+    }
+}
+
+impl LaunchOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs the applications with `Terminal=true` in this terminal emulator, such as `["gnome-terminal", "--"]`.
+    pub fn with_terminal<I: IntoIterator<Item = S>, S: Into<OsString>>(
+        mut self,
+        terminal: I,
+    ) -> Self {
+        self.terminal = terminal.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Translates `%c` in the first of the locales which has a translation.
+    pub fn with_locales(mut self, locales: &[Locale]) -> Self {
+        self.locales = locales.to_vec();
+        self
+    }
+}
+
+impl DesktopEntry {
+    /// Starts the application with the files, and returns its processes without waiting for them.
+    ///
+    /// The command is the expanded `Exec`, see [Self::localized_command_for], run in the `Path` directory if any,
+    /// and in the [terminal](LaunchOptions::terminal) if `Terminal` is `true`.
+    /// If `Exec` only takes a single file, with `%f` or `%u`, an instance is started for each file.
+    ///
+    /// Fails with [Error::InvalidValue] if the command is empty,
+    /// or with [Error::Io] if a process could not be started, in which case the previous ones keep running.
+    pub fn launch(
+        &self,
+        files: &[PathOrUri],
+        options: &LaunchOptions,
+    ) -> Result<Vec<Child>, Error> {
+        self.commands(files, options)?
+            .iter_mut()
+            .map(|command| command.spawn().map_err(Error::Io))
+            .collect()
+    }
+
+    /// Returns the commands started by [Self::launch].
+    fn commands(
+        &self,
+        files: &[PathOrUri],
+        options: &LaunchOptions,
+    ) -> Result<Vec<Command>, Error> {
+        let group = self.group();
+        let exec = decoded(group.get(Key::Exec.as_str())?)?;
+        let single_file = tokenize(&exec)?.iter().flatten().any(|token| {
+            matches!(
+                token,
+                ExecToken::Code(FieldCode::File) | ExecToken::Code(FieldCode::Url)
+            )
+        });
+        let batches: Vec<&[PathOrUri]> = match single_file && files.len() > 1 {
+            true => files.chunks(1).collect(),
+            false => vec![files],
+        };
+        let directory = group
+            .find_preferred(Key::Path.as_str(), &[])
+            .map(decoded)
+            .transpose()?
+            .filter(|path| !path.is_empty());
+        let terminal = self.terminal()?;
+
+        batches
+            .into_iter()
+            .map(|batch| {
+                let mut arguments = self.localized_command_for(batch, &options.locales)?;
+                if terminal {
+                    arguments.splice(0..0, options.terminal.iter().cloned());
+                }
+                let mut arguments = arguments.into_iter();
+                let program = arguments.next().ok_or(Error::InvalidValue(String::from(
+                    "the command of Exec is empty",
+                )))?;
+
+                let mut command = Command::new(program);
+                command.args(arguments);
+                if let Some(directory) = &directory {
+                    command.current_dir(&**directory);
+                }
+                Ok(command)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn arguments(command: &Command) -> Vec<&std::ffi::OsStr> {
+        let mut arguments = vec![command.get_program()];
+        arguments.extend(command.get_args());
+        arguments
+    }
+
+    #[test]
+    fn test_commands() {
+        let files = [
+            PathOrUri::Path(PathBuf::from("/tmp/a")),
+            PathOrUri::Path(PathBuf::from("/tmp/b")),
+        ];
+        let options = LaunchOptions::new().with_terminal(["foot", "--"]);
+
+        let mut entry = DesktopEntry::application("App", "app %f");
+        let commands = entry.commands(&files, &options).unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(arguments(&commands[0]), ["app", "/tmp/a"]);
+        assert_eq!(arguments(&commands[1]), ["app", "/tmp/b"]);
+        assert_eq!(commands[0].get_current_dir(), None);
+
+        entry.set(Key::Exec, "app %F");
+        entry.set(Key::Terminal, true);
+        entry.set(Key::Path, "/srv/app");
+        let commands = entry.commands(&files, &options).unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(
+            arguments(&commands[0]),
+            ["foot", "--", "app", "/tmp/a", "/tmp/b"]
+        );
+        assert_eq!(commands[0].get_current_dir(), Some(Path::new("/srv/app")));
+
+        entry.set(Key::Exec, "%f");
+        entry.set(Key::Terminal, false);
+        assert!(matches!(
+            entry.commands(&[], &options),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_launch() {
+        let entry = DesktopEntry::application("True", "true %u");
+        let mut children = entry.launch(&[], &LaunchOptions::new()).unwrap();
+
+        assert_eq!(children.len(), 1);
+        assert!(children[0].wait().unwrap().success());
+        assert!(matches!(
+            DesktopEntry::application("App", "/does/not/exist").launch(&[], &LaunchOptions::new()),
+            Err(Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+use alloc::{format, string::String};
+use core::{fmt::Display, str::FromStr};
+
+use crate::error::Error;
+#[cfg(feature = "std")]
+use crate::registry::Registry;
+
+/// Registry of the known media types, the part of MIME types before the `/`, such as `image`.
+///
+/// It is initialized with the top-level types registered by the IANA, and those used by freedesktop
+/// specifications for directories, volumes and URI schemes.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub static MIME_REGISTRY: Registry<()> = Registry::new(|| {
+    [
+        "application",
+        "audio",
+        "chemical",
+        "font",
+        "haptics",
+        "image",
+        "message",
+        "model",
+        "multipart",
+        "text",
+        "video",
+        "inode",
+        "x-content",
+        "x-scheme-handler",
+    ]
+    .into_iter()
+    .map(|media_type| (String::from(media_type), ()))
+    .collect()
+});
+
+/// A MIME type supported by an application, such as `image/png`, from the `MimeType` key.
+///
+/// The type and the subtype are lowercased, as MIME types are case-insensitive.
+/// A subtype `*` matches every subtype, as in `image/*`.
+/// ```
+/// use freedesktop_rs::helpers::desktop_entry::MimeType;
+///
+/// let images: MimeType = "image/*".parse().unwrap();
+/// assert!(images.matches(&"Image/PNG".parse().unwrap()));
+/// assert!("image".parse::<MimeType>().is_err());
+/// ```
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct MimeType {
+    /// The type, such as `image`.
+    pub media_type: String,
+    /// The subtype, such as `png`.
+    pub subtype: String,
+}
+
+impl MimeType {
+    /// Returns whether the MIME type is this one, or one of its subtypes if the subtype is `*`.
+    pub fn matches(&self, other: &MimeType) -> bool {
+        self.media_type == other.media_type
+            && (self.subtype == "*" || self.subtype == other.subtype)
+    }
+
+    /// Returns whether the media type is in [MIME_REGISTRY].
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    pub fn is_registered(&self) -> bool {
+        MIME_REGISTRY.contains(&self.media_type)
+    }
+}
+
+impl Display for MimeType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}/{}", self.media_type, self.subtype)
+    }
+}
+
+impl FromStr for MimeType {
+    type Err = Error;
+
+    /// Parses a MIME type, whose type and subtype start with a letter or a digit,
+    /// followed by letters, digits or one of `!#$&-^_.+`, as per RFC 6838.
+    ///
+    /// Fails with [Error::InvalidValue] if it is not valid.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_name = |name: &str| {
+            name.len() <= 127
+                && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+        };
+        match s.split_once('/') {
+            Some((media_type, subtype))
+                if is_name(media_type) && (subtype == "*" || is_name(subtype)) =>
+            {
+                Ok(Self {
+                    media_type: media_type.to_ascii_lowercase(),
+                    subtype: subtype.to_ascii_lowercase(),
+                })
+            }
+            _ => Err(Error::InvalidValue(format!("{s} is not a valid MIME type"))),
+        }
+    }
+}
+
+impl TryFrom<&str> for MimeType {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_mime_type() {
+        let mime = |s: &str| s.parse::<MimeType>();
+
+        assert_eq!(
+            mime("application/vnd.oasis.opendocument.text").unwrap(),
+            MimeType {
+                media_type: String::from("application"),
+                subtype: String::from("vnd.oasis.opendocument.text"),
+            }
+        );
+        assert_eq!(mime("Text/Plain").unwrap().to_string(), "text/plain");
+        for invalid in [
+            "",
+            "text",
+            "text/",
+            "/plain",
+            "text/plain/x",
+            "text/pl ain",
+            "*/*",
+            "x-/.a",
+        ] {
+            assert!(
+                matches!(mime(invalid), Err(Error::InvalidValue(_))),
+                "{invalid}"
+            );
+        }
+
+        assert!(mime("text/*").unwrap().matches(&mime("text/html").unwrap()));
+        assert!(
+            mime("text/html")
+                .unwrap()
+                .matches(&mime("TEXT/HTML").unwrap())
+        );
+        assert!(
+            !mime("text/html")
+                .unwrap()
+                .matches(&mime("text/plain").unwrap())
+        );
+        assert!(!mime("text/*").unwrap().matches(&mime("image/png").unwrap()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_mime_registry() {
+        assert!(
+            "x-scheme-handler/https"
+                .parse::<MimeType>()
+                .unwrap()
+                .is_registered()
+        );
+        assert!(!"vendor/type".parse::<MimeType>().unwrap().is_registered());
+    }
+}
@@ -0,0 +1,47 @@
+/// Categories in which entries are shown in menus.
+pub mod category;
+#[cfg(feature = "std")]
+pub use category::CATEGORY_REGISTRY;
+pub use category::Category;
+
+/// Typed representation of desktop entry files.
+pub mod entry;
+pub use entry::DesktopEntry;
+
+/// Types of desktop entries and the keys they may contain.
+pub mod entry_type;
+pub use entry_type::EntryType;
+
+/// Standard keys of the `[Desktop Entry]` group.
+pub mod keys;
+#[cfg(feature = "std")]
+pub use keys::KEY_REGISTRY;
+pub use keys::{Key, ValueType};
+
+/// MIME types supported by applications.
+pub mod mime_type;
+#[cfg(feature = "std")]
+pub use mime_type::MIME_REGISTRY;
+pub use mime_type::MimeType;
+
+/// Typed `Keywords` of each locale.
+pub mod keywords;
+pub use keywords::Keywords;
+
+/// Expansion of the field codes of `Exec`.
+pub mod exec;
+pub use exec::{ExecExpander, ExecToken, FieldCode, quote, tokenize};
+
+/// Command lines starting applications with files.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod command;
+#[cfg(feature = "std")]
+pub use command::PathOrUri;
+
+/// Start of applications in new processes.
+#[cfg_attr(docsrs, doc(cfg(feature = "launch")))]
+#[cfg(feature = "launch")]
+pub mod launch;
+#[cfg(feature = "launch")]
+pub use launch::LaunchOptions;
@@ -0,0 +1,248 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    error::Error,
+    parser::models::{
+        ContentEntry, DesktopFile, Entry, EntryFormat, EntrySet, Group, TopLevelEntry,
+    },
+};
+
+const GROUP_NAME: &str = "Desktop Entry";
+
+const IMPLEMENTS_KEY: &str = "Implements";
+
+/// The extension group of an interface implemented by a desktop entry.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Interface {
+    group: Group,
+}
+
+impl Interface {
+    /// Creates an interface without any key.
+    pub fn new(name: &str) -> Self {
+        Self {
+            group: Group {
+                header: name.to_owned(),
+                content: vec![],
+            },
+        }
+    }
+
+    /// Returns the name of the interface, which is also the header of its group.
+    pub fn name(&self) -> &str {
+        &self.group.header
+    }
+
+    /// Returns the underlying group.
+    pub fn group(&self) -> &Group {
+        &self.group
+    }
+
+    /// Returns the first value of the key converted to `T`.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, Error> {
+        let entry = self.group.get(key)?;
+        let raw = entry.values.first().map_or("", String::as_str);
+
+        raw.parse().map_err(|_| Error::InvalidValue(key.to_owned()))
+    }
+
+    /// Returns all the values of the key converted to `T`.
+    pub fn get_list<T: FromStr>(&self, key: &str) -> Result<Vec<T>, Error> {
+        self.group
+            .get(key)?
+            .values
+            .iter()
+            .map(|v| v.parse().map_err(|_| Error::InvalidValue(key.to_owned())))
+            .collect()
+    }
+
+    /// Sets the value of the key, replacing the first existing entry or adding a new one.
+    pub fn set<T: Display>(&mut self, key: &str, value: T) {
+        self.set_list(key, &[value]);
+    }
+
+    /// Sets the values of the key, replacing the first existing entry or adding a new one.
+    pub fn set_list<T: Display>(&mut self, key: &str, values: &[T]) {
+        let values = values.iter().map(ToString::to_string).collect();
+
+        if let Some(entry) = self.group.find_mut(key) {
+            entry.values = values;
+            entry.format.raw_values = None;
+        } else {
+            self.group.content.push(Entry::Content(ContentEntry {
+                key: key.to_owned(),
+                values,
+                locale: None,
+                format: EntryFormat::default(),
+            }));
+        }
+    }
+}
+
+/// Representation of a desktop file and the extension groups of the interfaces it implements.
+///
+/// Interfaces are declared by the `Implements` key of the `[Desktop Entry]` group. Groups that
+/// are not declared are left untouched.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct InterfaceFile {
+    desktop_file: DesktopFile,
+    /// Implemented interfaces, in the order of declaration.
+    pub interfaces: Vec<Interface>,
+}
+
+impl InterfaceFile {
+    /// Find the interface with this name, or `None` if it is not implemented.
+    pub fn find(&self, name: &str) -> Option<&Interface> {
+        self.interfaces.iter().find(|i| i.name() == name)
+    }
+
+    /// Find the interface with this name and returns it as a mutable reference, or `None` if it is not implemented.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Interface> {
+        self.interfaces.iter_mut().find(|i| i.name() == name)
+    }
+
+    /// Similar to [Self::find], but throws if the interface is not implemented.
+    pub fn get(&self, name: &str) -> Result<&Interface, Error> {
+        self.find(name).ok_or(Error::NotFound(name.to_owned()))
+    }
+
+    /// Similar to [Self::find_mut], but throws if the interface is not implemented.
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Interface, Error> {
+        self.find_mut(name).ok_or(Error::NotFound(name.to_owned()))
+    }
+}
+
+impl From<InterfaceFile> for DesktopFile {
+    fn from(interface_file: InterfaceFile) -> Self {
+        let mut desktop_file = interface_file.desktop_file;
+        let names: Vec<String> = interface_file
+            .interfaces
+            .iter()
+            .map(|i| i.name().to_owned())
+            .collect();
+
+        if let Some(group) = desktop_file.find_mut(GROUP_NAME) {
+            if let Some(implements) = group.find_mut(IMPLEMENTS_KEY) {
+                implements.values = names;
+                implements.format.raw_values = None;
+            } else if !names.is_empty() {
+                group.content.push(Entry::Content(ContentEntry {
+                    key: String::from(IMPLEMENTS_KEY),
+                    values: names,
+                    locale: None,
+                    format: EntryFormat::default(),
+                }));
+            }
+        }
+
+        for interface in interface_file.interfaces {
+            if let Some(group) = desktop_file.find_mut(interface.name()) {
+                *group = interface.group;
+            } else if !interface.group.content.is_empty() {
+                desktop_file
+                    .content
+                    .push(TopLevelEntry::Group(interface.group));
+            }
+        }
+
+        desktop_file
+    }
+}
+
+impl TryFrom<DesktopFile> for InterfaceFile {
+    type Error = Error;
+
+    fn try_from(desktop: DesktopFile) -> Result<Self, Self::Error> {
+        let group = desktop.get(GROUP_NAME)?;
+        let names = group
+            .find(IMPLEMENTS_KEY)
+            .map(|e| e.values.clone())
+            .unwrap_or_default();
+
+        let interfaces = names
+            .iter()
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                desktop
+                    .find(name)
+                    .map(|group| Interface {
+                        group: group.clone(),
+                    })
+                    .unwrap_or_else(|| Interface::new(name))
+            })
+            .collect();
+
+        Ok(Self {
+            desktop_file: desktop,
+            interfaces,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILE: &str = "[Desktop Entry]
+Type=Application
+Implements=org.example.Foo;org.example.Bar;
+
+[org.example.Foo]
+Timeout=30
+Modes=fast;slow;
+
+[org.example.Unknown]
+Key=Value
+";
+
+    #[test]
+    fn parse_interfaces() {
+        let interface_file = InterfaceFile::try_from(DesktopFile::try_from(FILE).unwrap()).unwrap();
+
+        let foo = interface_file.get("org.example.Foo").unwrap();
+        assert_eq!(foo.get::<u32>("Timeout").unwrap(), 30);
+        assert_eq!(
+            foo.get_list::<String>("Modes").unwrap(),
+            vec![String::from("fast"), String::from("slow")]
+        );
+        assert!(matches!(
+            foo.get::<bool>("Timeout"),
+            Err(Error::InvalidValue(_))
+        ));
+
+        let bar = interface_file.get("org.example.Bar").unwrap();
+        assert!(bar.group().content.is_empty());
+        assert!(interface_file.find("org.example.Unknown").is_none());
+    }
+
+    #[test]
+    fn edit_and_convert_interfaces() {
+        let mut interface_file =
+            InterfaceFile::try_from(DesktopFile::try_from(FILE).unwrap()).unwrap();
+
+        interface_file
+            .get_mut("org.example.Foo")
+            .unwrap()
+            .set("Timeout", 60);
+        interface_file
+            .get_mut("org.example.Bar")
+            .unwrap()
+            .set("Enabled", true);
+
+        assert_eq!(
+            DesktopFile::from(interface_file).to_string(),
+            "[Desktop Entry]
+Type=Application
+Implements=org.example.Foo;org.example.Bar;
+
+[org.example.Foo]
+Timeout=60
+Modes=fast;slow;
+
+[org.example.Unknown]
+Key=Value
+[org.example.Bar]
+Enabled=true"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use std::{fmt::Write, fs, io::ErrorKind, path::Path};
+
+use crate::{
+    error::Error,
+    helpers::permissions::secure_write,
+    parser::models::{
+        ContentEntry, DesktopFile, Entry, EntryFormat, FileFormat, Group, TopLevelEntry,
+    },
+};
+
+const DOCTYPE: &str = r#"<!DOCTYPE Menu PUBLIC "-//freedesktop//DTD Menu 1.0//EN"
+ "http://www.freedesktop.org/standards/menu-spec/1.0/menu.dtd">"#;
+
+/// A menu of a user-level `.menu` override, and the changes made to it.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Menu {
+    /// The name of the menu, which identifies it within its parent.
+    pub name: String,
+    /// The desktop file ID of the `.directory` file describing the menu, if any.
+    pub directory: Option<String>,
+    /// The desktop file IDs of the entries added to the menu.
+    pub included: Vec<String>,
+    /// The desktop file IDs of the entries removed from the menu.
+    pub excluded: Vec<String>,
+    /// Whether the menu is deleted.
+    pub deleted: bool,
+    /// The submenus that are changed.
+    pub submenus: Vec<Menu>,
+}
+
+impl Menu {
+    /// Creates a menu without any change.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the submenu at this path, made of the names of the nested menus.
+    pub fn find(&self, path: &[&str]) -> Option<&Menu> {
+        match path.split_first() {
+            None => Some(self),
+            Some((name, rest)) => self
+                .submenus
+                .iter()
+                .find(|m| m.name == *name)
+                .and_then(|m| m.find(rest)),
+        }
+    }
+
+    /// Returns the submenu at this path, creating the missing menus.
+    fn find_or_create(&mut self, path: &[&str]) -> &mut Menu {
+        let Some((name, rest)) = path.split_first() else {
+            return self;
+        };
+        let position = match self.submenus.iter().position(|m| m.name == *name) {
+            Some(position) => position,
+            None => {
+                self.submenus.push(Menu::new(name));
+                self.submenus.len() - 1
+            }
+        };
+        self.submenus[position].find_or_create(rest)
+    }
+
+    fn write_xml(&self, xml: &mut String, indent: &str) {
+        let _ = writeln!(xml, "{indent}<Menu>");
+        let _ = writeln!(xml, "{indent}  <Name>{}</Name>", escape(&self.name));
+        self.write_changes(xml, &format!("{indent}  "));
+        let _ = writeln!(xml, "{indent}</Menu>");
+    }
+
+    fn write_changes(&self, xml: &mut String, indent: &str) {
+        if let Some(directory) = &self.directory {
+            let _ = writeln!(xml, "{indent}<Directory>{}</Directory>", escape(directory));
+        }
+        for (tag, ids) in [("Include", &self.included), ("Exclude", &self.excluded)] {
+            if ids.is_empty() {
+                continue;
+            }
+            let _ = writeln!(xml, "{indent}<{tag}>");
+            for id in ids {
+                let _ = writeln!(xml, "{indent}  <Filename>{}</Filename>", escape(id));
+            }
+            let _ = writeln!(xml, "{indent}</{tag}>");
+        }
+        if self.deleted {
+            let _ = writeln!(xml, "{indent}<Deleted/>");
+        }
+        for submenu in &self.submenus {
+            submenu.write_xml(xml, indent);
+        }
+    }
+}
+
+/// Edits the menus of the user, by generating a `.menu` file which overrides the system one,
+/// as defined by the [Desktop Menu specification](https://specifications.freedesktop.org/menu-spec/latest/).
+///
+/// Paths of menus are made of the names of the nested menus, without the root menu.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MenuEditor {
+    file_name: String,
+    root: Menu,
+    moves: Vec<(String, String)>,
+    directories: Vec<(String, DesktopFile)>,
+}
+
+impl MenuEditor {
+    /// Creates an editor of the menu file, such as `applications.menu`, whose root menu has this name.
+    pub fn new(file_name: &str, root_name: &str) -> Self {
+        Self {
+            file_name: file_name.to_owned(),
+            root: Menu::new(root_name),
+            moves: vec![],
+            directories: vec![],
+        }
+    }
+
+    /// Returns the root menu and its changes.
+    pub fn root(&self) -> &Menu {
+        &self.root
+    }
+
+    /// Adds the entry to the menu, creating the menu if needed.
+    pub fn add_entry(&mut self, menu: &[&str], desktop_id: &str) {
+        let menu = self.root.find_or_create(menu);
+        menu.excluded.retain(|id| id != desktop_id);
+        if !menu.included.iter().any(|id| id == desktop_id) {
+            menu.included.push(desktop_id.to_owned());
+        }
+    }
+
+    /// Removes the entry from the menu.
+    pub fn remove_entry(&mut self, menu: &[&str], desktop_id: &str) {
+        let menu = self.root.find_or_create(menu);
+        menu.included.retain(|id| id != desktop_id);
+        if !menu.excluded.iter().any(|id| id == desktop_id) {
+            menu.excluded.push(desktop_id.to_owned());
+        }
+    }
+
+    /// Moves the entry from a menu to another.
+    pub fn move_entry(&mut self, from: &[&str], to: &[&str], desktop_id: &str) {
+        self.remove_entry(from, desktop_id);
+        self.add_entry(to, desktop_id);
+    }
+
+    /// Adds a menu described by a new `.directory` file, which is written with the `.menu` file.
+    pub fn add_menu(&mut self, menu: &[&str], name: &str, icon: Option<&str>) {
+        let file_stem = menu
+            .join("-")
+            .to_lowercase()
+            .replace(|c: char| !c.is_alphanumeric() && c != '-', "-");
+        let directory_id = format!("{file_stem}.directory");
+        let target = self.root.find_or_create(menu);
+        target.deleted = false;
+        target.directory = Some(directory_id.clone());
+
+        self.directories.retain(|(id, _)| *id != directory_id);
+        self.directories
+            .push((directory_id, directory_file(name, icon)));
+    }
+
+    /// Deletes the menu.
+    pub fn remove_menu(&mut self, menu: &[&str]) {
+        self.root.find_or_create(menu).deleted = true;
+    }
+
+    /// Moves the menu to another path, which can be in another parent menu.
+    pub fn move_menu(&mut self, from: &[&str], to: &[&str]) {
+        self.moves.push((from.join("/"), to.join("/")));
+    }
+
+    /// Returns the XML of the `.menu` file, which merges the menu of the same name found in the system directories.
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("{DOCTYPE}\n<Menu>\n");
+        let _ = writeln!(xml, "  <Name>{}</Name>", escape(&self.root.name));
+        // The system file is merged first, so that the changes apply to it
+        xml.push_str("  <MergeFile type=\"parent\"/>\n");
+        xml.push_str(&self.changes_xml());
+        xml.push_str("</Menu>\n");
+        xml
+    }
+
+    /// Returns the XML of the changes to the root menu.
+    fn changes_xml(&self) -> String {
+        let mut xml = String::new();
+        self.root.write_changes(&mut xml, "  ");
+        for (old, new) in &self.moves {
+            let _ = writeln!(
+                xml,
+                "  <Move>\n    <Old>{}</Old>\n    <New>{}</New>\n  </Move>",
+                escape(old),
+                escape(new)
+            );
+        }
+        xml
+    }
+
+    /// Returns the `.directory` files of the added menus, with their desktop file IDs.
+    pub fn directories(&self) -> &[(String, DesktopFile)] {
+        &self.directories
+    }
+
+    /// Writes the `.menu` file in `config_home/menus` and the `.directory` files in `data_home/desktop-directories`.
+    ///
+    /// These are usually `$XDG_CONFIG_HOME` and `$XDG_DATA_HOME`.
+    ///
+    /// If the user already has this `.menu` file, the changes are appended to its root menu,
+    /// where they take precedence over its own rules, and the rest of the file is kept.
+    /// Fails with [Error::InvalidValue] if the existing file has no root menu.
+    pub fn save<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        config_home: P,
+        data_home: Q,
+    ) -> Result<(), Error> {
+        let path = config_home.as_ref().join("menus").join(&self.file_name);
+        let xml = match fs::read_to_string(&path) {
+            Ok(existing) => {
+                let end = existing.rfind("</Menu>").ok_or_else(|| {
+                    Error::InvalidValue(format!("{} has no root menu", path.display()))
+                })?;
+                let separator = match existing[..end].ends_with('\n') {
+                    true => "",
+                    false => "\n",
+                };
+                format!(
+                    "{}{separator}{}{}",
+                    &existing[..end],
+                    self.changes_xml(),
+                    &existing[end..]
+                )
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => self.to_xml(),
+            Err(error) => return Err(Error::Io(error)),
+        };
+        secure_write(path, xml)?;
+
+        let directories = data_home.as_ref().join("desktop-directories");
+        for (id, file) in &self.directories {
+            secure_write(directories.join(id), format!("{file}\n"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates the `.directory` file describing a menu.
+fn directory_file(name: &str, icon: Option<&str>) -> DesktopFile {
+    let entry = |key: &str, value: &str| {
+        Entry::Content(ContentEntry {
+            key: key.to_owned(),
+            values: vec![value.to_owned()],
+            locale: None,
+            format: EntryFormat::default(),
+        })
+    };
+    let mut content = vec![entry("Type", "Directory"), entry("Name", name)];
+    content.extend(icon.map(|icon| entry("Icon", icon)));
+
+    DesktopFile {
+        content: vec![TopLevelEntry::Group(Group {
+            header: String::from("Desktop Entry"),
+            content,
+        })],
+        format: FileFormat::default(),
+    }
+}
+
+/// Escapes the characters which have a meaning in XML.
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_override() {
+        let mut editor = MenuEditor::new("applications.menu", "Applications");
+        editor.add_entry(&[], "top.desktop");
+        editor.add_entry(&["Games"], "chess.desktop");
+        editor.move_entry(&["Games"], &["Office"], "chess.desktop");
+        editor.add_menu(&["Tools & Co"], "Tools", Some("tools"));
+        editor.remove_menu(&["Education"]);
+        editor.move_menu(&["Office"], &["Work", "Office"]);
+
+        assert_eq!(
+            editor.root().find(&["Office"]).unwrap().included,
+            vec!["chess.desktop"]
+        );
+        assert_eq!(
+            editor.to_xml(),
+            format!(
+                "{DOCTYPE}
+<Menu>
+  <Name>Applications</Name>
+  <MergeFile type=\"parent\"/>
+  <Include>
+    <Filename>top.desktop</Filename>
+  </Include>
+  <Menu>
+    <Name>Games</Name>
+    <Exclude>
+      <Filename>chess.desktop</Filename>
+    </Exclude>
+  </Menu>
+  <Menu>
+    <Name>Office</Name>
+    <Include>
+      <Filename>chess.desktop</Filename>
+    </Include>
+  </Menu>
+  <Menu>
+    <Name>Tools &amp; Co</Name>
+    <Directory>tools---co.directory</Directory>
+  </Menu>
+  <Menu>
+    <Name>Education</Name>
+    <Deleted/>
+  </Menu>
+  <Move>
+    <Old>Office</Old>
+    <New>Work/Office</New>
+  </Move>
+</Menu>
+"
+            )
+        );
+        assert_eq!(
+            editor.directories()[0].1.to_string(),
+            "[Desktop Entry]\nType=Directory\nName=Tools\nIcon=tools"
+        );
+    }
+
+    #[test]
+    fn test_save_merges_existing_file() {
+        let dir = std::env::temp_dir().join(format!("freedesktop-rs-menu-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let menus = dir.join("config/menus");
+        fs::create_dir_all(&menus).unwrap();
+        let existing = format!(
+            "{DOCTYPE}
+<Menu>
+  <Name>Applications</Name>
+  <MergeFile type=\"parent\"/>
+  <Layout>
+    <Merge type=\"menus\"/>
+  </Layout>
+  <Include>
+    <Filename>editor.desktop</Filename>
+  </Include>
+</Menu>
+"
+        );
+        fs::write(menus.join("applications.menu"), &existing).unwrap();
+
+        let mut editor = MenuEditor::new("applications.menu", "Applications");
+        editor.remove_entry(&[], "chess.desktop");
+        editor.save(dir.join("config"), dir.join("data")).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(menus.join("applications.menu")).unwrap(),
+            existing.replace(
+                "</Menu>\n",
+                "  <Exclude>\n    <Filename>chess.desktop</Filename>\n  </Exclude>\n</Menu>\n"
+            )
+        );
+
+        fs::write(menus.join("applications.menu"), "<Menu/>").unwrap();
+        assert!(matches!(
+            editor.save(dir.join("config"), dir.join("data")),
+            Err(Error::InvalidValue(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
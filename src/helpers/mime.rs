@@ -0,0 +1,601 @@
+use std::{cmp::Reverse, fs, io::Read, path::Path};
+
+use crate::error::Error;
+
+/// The weight of a glob without an explicit one, as defined by the specification.
+const DEFAULT_GLOB_WEIGHT: u32 = 50;
+
+/// The number of bytes sniffed by default, which is enough for the rules shipped by shared-mime-info.
+const DEFAULT_MAX_SNIFFED_BYTES: usize = 4096;
+
+/// The stages used to detect a MIME type, and how their results are combined.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub enum MimeStages {
+    /// Only match the file name against the globs.
+    GlobOnly,
+    /// Only match the content against the magic rules.
+    MagicOnly,
+    /// Match the file name against the globs, and only sniff the content when the globs are inconclusive,
+    /// as recommended by the [specification](https://specifications.freedesktop.org/shared-mime-info-spec/latest/ar01s02.html#id-1.3.14).
+    #[default]
+    GlobAndMagic,
+}
+
+/// A file name pattern associated with a MIME type, as found in `globs2` files.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Glob {
+    /// The MIME type of the matching files.
+    pub mime_type: String,
+    /// The pattern, such as `*.txt`.
+    pub pattern: String,
+    /// The weight of the pattern, from 0 to 100. Defaults to 50.
+    pub weight: u32,
+    /// Whether the pattern is case-sensitive.
+    pub case_sensitive: bool,
+}
+
+impl Glob {
+    /// Creates a case-insensitive glob with the default weight.
+    pub fn new(pattern: &str, mime_type: &str) -> Self {
+        Self {
+            mime_type: mime_type.to_owned(),
+            pattern: pattern.to_owned(),
+            weight: DEFAULT_GLOB_WEIGHT,
+            case_sensitive: false,
+        }
+    }
+
+    /// Returns whether the file name matches the pattern.
+    pub fn matches(&self, file_name: &str) -> bool {
+        if self.case_sensitive {
+            fnmatch(self.pattern.as_bytes(), file_name.as_bytes())
+        } else {
+            fnmatch(
+                self.pattern.to_lowercase().as_bytes(),
+                file_name.to_lowercase().as_bytes(),
+            )
+        }
+    }
+}
+
+/// A content rule associated with a MIME type, as found in `magic` files.
+///
+/// The rule matches if any of its matches does.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MagicRule {
+    /// The MIME type of the matching content.
+    pub mime_type: String,
+    /// The priority of the rule, from 0 to 100.
+    pub priority: u32,
+    /// The alternative matches of the rule.
+    pub matches: Vec<MagicMatch>,
+}
+
+/// Bytes to be found in the content, at an offset or within a range of offsets.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MagicMatch {
+    /// The first offset at which the value is searched.
+    pub offset: usize,
+    /// The number of offsets at which the value is searched, starting at [Self::offset].
+    pub range_length: usize,
+    /// The bytes to be found.
+    pub value: Vec<u8>,
+    /// The mask applied to the content before comparing it to the value, if any.
+    pub mask: Option<Vec<u8>>,
+    /// The matches which must also match, nested under this one.
+    pub children: Vec<MagicMatch>,
+}
+
+impl MagicMatch {
+    /// Creates a match of the value at this offset.
+    pub fn new(offset: usize, value: &[u8]) -> Self {
+        Self {
+            offset,
+            range_length: 1,
+            value: value.to_vec(),
+            mask: None,
+            children: vec![],
+        }
+    }
+
+    /// Returns whether the content matches the value, and one of the children if there are any.
+    ///
+    /// Offsets beyond the addressable range never match.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        let Some(end) = self.offset.checked_add(self.range_length.max(1)) else {
+            return false;
+        };
+        let found = (self.offset..end).any(|start| {
+            let Some(bytes) = start
+                .checked_add(self.value.len())
+                .and_then(|end| data.get(start..end))
+            else {
+                return false;
+            };
+            match &self.mask {
+                Some(mask) => bytes
+                    .iter()
+                    .zip(mask)
+                    .zip(&self.value)
+                    .all(|((byte, mask), value)| byte & mask == value & mask),
+                None => bytes == self.value,
+            }
+        });
+
+        found && (self.children.is_empty() || self.children.iter().any(|c| c.matches(data)))
+    }
+}
+
+/// Detects the MIME type of files from their name and content.
+///
+/// The detector is built with the globs and magic rules to use, usually loaded from the shared MIME-info database,
+/// and with the stages that suit the caller: constrained environments can skip sniffing, or limit the number of sniffed bytes.
+#[derive(Debug, Clone)]
+pub struct MimeDetector {
+    globs: Vec<Glob>,
+    magic: Vec<MagicRule>,
+    stages: MimeStages,
+    max_sniffed_bytes: usize,
+}
+
+impl Default for MimeDetector {
+    fn default() -> Self {
+        Self {
+            globs: vec![],
+            magic: vec![],
+            stages: MimeStages::default(),
+            max_sniffed_bytes: DEFAULT_MAX_SNIFFED_BYTES,
+        }
+    }
+}
+
+impl MimeDetector {
+    /// Creates a detector without any glob nor magic rule.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a detector with the `globs2` and `magic` files of this shared MIME-info directory, such as `/usr/share/mime`.
+    ///
+    /// Missing files are ignored.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let mut detector = Self::new();
+
+        match fs::read_to_string(dir.join("globs2")) {
+            Ok(globs) => detector = detector.with_globs2(&globs),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(Error::Io(error)),
+        }
+        match fs::read(dir.join("magic")) {
+            Ok(magic) => detector = detector.with_magic_file(&magic)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(Error::Io(error)),
+        }
+
+        Ok(detector)
+    }
+
+    /// Changes the stages used to detect MIME types.
+    pub fn with_stages(mut self, stages: MimeStages) -> Self {
+        self.stages = stages;
+        self
+    }
+
+    /// Changes the maximum number of bytes sniffed by the magic rules. Defaults to 4096.
+    pub fn with_max_sniffed_bytes(mut self, max: usize) -> Self {
+        self.max_sniffed_bytes = max;
+        self
+    }
+
+    /// Adds a glob.
+    pub fn with_glob(mut self, glob: Glob) -> Self {
+        self.globs.push(glob);
+        self
+    }
+
+    /// Adds a magic rule.
+    pub fn with_magic(mut self, rule: MagicRule) -> Self {
+        self.magic.push(rule);
+        self
+    }
+
+    /// Adds the globs of a `globs2` file, whose lines look like `50:text/plain:*.txt`.
+    ///
+    /// Comments and malformed lines are ignored.
+    pub fn with_globs2(mut self, content: &str) -> Self {
+        for line in content.lines().filter(|l| !l.starts_with('#')) {
+            let mut fields = line.split(':');
+            let (Some(weight), Some(mime_type), Some(pattern)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(weight) = weight.parse() else {
+                continue;
+            };
+
+            self.globs.push(Glob {
+                mime_type: mime_type.to_owned(),
+                pattern: pattern.to_owned(),
+                weight,
+                case_sensitive: fields.next().is_some_and(|flags| flags.contains("cs")),
+            });
+        }
+        self
+    }
+
+    /// Adds the rules of a binary `magic` file.
+    pub fn with_magic_file(mut self, content: &[u8]) -> Result<Self, Error> {
+        self.magic.extend(parse_magic(content)?);
+        Ok(self)
+    }
+
+    /// Returns the MIME type of the file name according to the globs.
+    ///
+    /// The glob with the highest weight wins, then the longest pattern, then the case-sensitive one.
+    pub fn detect_name(&self, file_name: &str) -> Option<&str> {
+        self.glob_candidates(file_name).first().copied()
+    }
+
+    /// Returns the MIME type of the content according to the magic rules.
+    ///
+    /// Only the first bytes are sniffed, see [Self::with_max_sniffed_bytes].
+    /// Among the matching rules with the highest priority, the first one wins.
+    pub fn detect_data(&self, data: &[u8]) -> Option<&str> {
+        let data = &data[..data.len().min(self.max_sniffed_bytes)];
+        self.magic
+            .iter()
+            .filter(|rule| rule.matches.iter().any(|m| m.matches(data)))
+            .min_by_key(|rule| Reverse(rule.priority))
+            .map(|rule| rule.mime_type.as_str())
+    }
+
+    /// Returns the MIME type of a file from its name and content, according to the stages.
+    pub fn detect(&self, file_name: Option<&str>, data: Option<&[u8]>) -> Option<&str> {
+        let magic = || data.and_then(|data| self.detect_data(data));
+        match self.stages {
+            MimeStages::GlobOnly => file_name.and_then(|name| self.detect_name(name)),
+            MimeStages::MagicOnly => magic(),
+            MimeStages::GlobAndMagic => {
+                let candidates = file_name.map_or(vec![], |name| self.glob_candidates(name));
+                match candidates.as_slice() {
+                    [] => magic(),
+                    [single] => Some(single),
+                    // Globs conflict, so the content settles it
+                    [first, ..] => magic()
+                        .filter(|sniffed| candidates.contains(sniffed))
+                        .or(Some(first)),
+                }
+            }
+        }
+    }
+
+    /// Similar to [Self::detect], but reads the name and the first bytes of the file.
+    pub fn detect_path<P: AsRef<Path>>(&self, path: P) -> Result<Option<&str>, Error> {
+        let path = path.as_ref();
+        let file_name = path.file_name().map(|name| name.to_string_lossy());
+        let data = if self.stages == MimeStages::GlobOnly {
+            None
+        } else {
+            let mut data = vec![];
+            fs::File::open(path)
+                .and_then(|file| {
+                    file.take(self.max_sniffed_bytes as u64)
+                        .read_to_end(&mut data)
+                })
+                .map_err(Error::Io)?;
+            Some(data)
+        };
+
+        Ok(self.detect(file_name.as_deref(), data.as_deref()))
+    }
+
+    /// Returns the MIME types of the best globs matching the name, see [Self::detect_name].
+    fn glob_candidates(&self, file_name: &str) -> Vec<&str> {
+        let mut matching: Vec<&Glob> = self.globs.iter().filter(|g| g.matches(file_name)).collect();
+        matching.sort_by(|a, b| {
+            b.weight
+                .cmp(&a.weight)
+                .then(b.pattern.len().cmp(&a.pattern.len()))
+                .then(b.case_sensitive.cmp(&a.case_sensitive))
+        });
+
+        let mut candidates: Vec<&str> = vec![];
+        if let Some(best) = matching.first() {
+            for glob in matching.iter().take_while(|g| {
+                g.weight == best.weight
+                    && g.pattern.len() == best.pattern.len()
+                    && g.case_sensitive == best.case_sensitive
+            }) {
+                if !candidates.contains(&glob.mime_type.as_str()) {
+                    candidates.push(&glob.mime_type);
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// Matches the name against a shell pattern made of `*`, `?` and `[...]`.
+///
+/// When the rest of the pattern does not match, only the last `*` is retried with one more byte,
+/// so that the time is at most proportional to the product of the lengths, whatever the number of `*`.
+fn fnmatch(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The position in the pattern after the last `*`, and the position in the name it was last tried at
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&b'*') {
+            p += 1;
+            star = Some((p, n));
+            continue;
+        }
+        if let Some(length) = match_token(&pattern[p..], name[n]) {
+            p += length;
+            n += 1;
+            continue;
+        }
+        match &mut star {
+            Some((after_star, tried)) => {
+                *tried += 1;
+                (p, n) = (*after_star, *tried);
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|b| *b == b'*')
+}
+
+/// Matches the byte against the token starting the pattern, other than `*`, and returns the length of the token.
+fn match_token(pattern: &[u8], byte: u8) -> Option<usize> {
+    match pattern.split_first()? {
+        (b'?', _) => Some(1),
+        (b'[', rest) => {
+            let Some(end) = rest.iter().position(|b| *b == b']') else {
+                return (byte == b'[').then_some(1);
+            };
+            let (negated, class) = match rest[..end].split_first() {
+                Some((b'!', class)) => (true, class),
+                _ => (false, &rest[..end]),
+            };
+            (class_contains(class, byte) != negated).then_some(end + 2)
+        }
+        (c, _) => (*c == byte).then_some(1),
+    }
+}
+
+/// Returns whether the character class, such as `a-z0`, contains the byte.
+fn class_contains(class: &[u8], byte: u8) -> bool {
+    let mut i = 0;
+    while i < class.len() {
+        if class.get(i + 1) == Some(&b'-') && i + 2 < class.len() {
+            if (class[i]..=class[i + 2]).contains(&byte) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if class[i] == byte {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
+}
+
+/// Parses a binary `magic` file, made of sections such as `[50:text/plain]` followed by indented matches.
+fn parse_magic(content: &[u8]) -> Result<Vec<MagicRule>, Error> {
+    let invalid = || Error::InvalidValue(String::from("magic"));
+    let mut input = content
+        .strip_prefix(b"MIME-Magic\0\n")
+        .ok_or_else(invalid)?;
+    let mut rules: Vec<MagicRule> = vec![];
+
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix(b"[") {
+            let end = rest.iter().position(|b| *b == b'\n').ok_or_else(invalid)?;
+            let header = str::from_utf8(&rest[..end]).map_err(|_| invalid())?;
+            let (priority, mime_type) = header
+                .trim_end_matches(']')
+                .split_once(':')
+                .ok_or_else(invalid)?;
+            rules.push(MagicRule {
+                mime_type: mime_type.to_owned(),
+                priority: priority.parse().map_err(|_| invalid())?,
+                matches: vec![],
+            });
+            input = &rest[end + 1..];
+            continue;
+        }
+
+        let rule = rules.last_mut().ok_or_else(invalid)?;
+        let (indent, magic_match, rest) = parse_magic_line(input).ok_or_else(invalid)?;
+        input = rest;
+
+        // A match is nested under the last match with a lower indent
+        let mut siblings = &mut rule.matches;
+        for _ in 0..indent {
+            siblings = &mut siblings.last_mut().ok_or_else(invalid)?.children;
+        }
+        siblings.push(magic_match);
+    }
+
+    Ok(rules)
+}
+
+/// Parses a line such as `[indent]>offset=<length><value>[&mask][~word-size][+range-length]`.
+fn parse_magic_line(input: &[u8]) -> Option<(usize, MagicMatch, &[u8])> {
+    fn number(input: &[u8]) -> Option<(usize, &[u8])> {
+        let end = input.iter().position(|b| !b.is_ascii_digit())?;
+        Some((
+            str::from_utf8(&input[..end]).ok()?.parse().ok()?,
+            &input[end..],
+        ))
+    }
+
+    let (indent, input) = match input.first()? {
+        b'>' => (0, input),
+        _ => number(input)?,
+    };
+    let (offset, input) = number(input.strip_prefix(b">")?)?;
+    let input = input.strip_prefix(b"=")?;
+    let length = u16::from_be_bytes([*input.first()?, *input.get(1)?]) as usize;
+    let value = input.get(2..2 + length)?.to_vec();
+    let mut input = &input[2 + length..];
+
+    let mut magic_match = MagicMatch::new(offset, &value);
+    if let Some(rest) = input.strip_prefix(b"&") {
+        magic_match.mask = Some(rest.get(..length)?.to_vec());
+        input = &rest[length..];
+    }
+    if let Some(rest) = input.strip_prefix(b"~") {
+        let (word_size, rest) = number(rest)?;
+        // Values of host-endian types are stored big-endian, so they are swapped by words on little-endian hosts
+        if word_size > 1 && cfg!(target_endian = "little") {
+            if !length.is_multiple_of(word_size) {
+                return None;
+            }
+            magic_match
+                .value
+                .chunks_mut(word_size)
+                .for_each(<[u8]>::reverse);
+            if let Some(mask) = &mut magic_match.mask {
+                mask.chunks_mut(word_size).for_each(<[u8]>::reverse);
+            }
+        }
+        input = rest;
+    }
+    if let Some(rest) = input.strip_prefix(b"+") {
+        let (range_length, rest) = number(rest)?;
+        magic_match.range_length = range_length;
+        input = rest;
+    }
+
+    Some((indent, magic_match, input.strip_prefix(b"\n")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLOBS2: &str = "# Comment
+50:text/x-csrc:*.c
+50:text/x-c++src:*.C:cs
+60:text/x-readme:README*
+50:application/x-foo:*.dat
+50:application/x-bar:*.dat
+";
+
+    fn magic_file() -> Vec<u8> {
+        let mut magic = b"MIME-Magic\0\n[50:application/x-bar]\n>0=\0\x03BAR\n".to_vec();
+        magic.extend_from_slice(b"[80:image/png]\n>0=\0\x04\x89PNG\n1>8=\0\x01I+4\n");
+        magic
+    }
+
+    #[test]
+    fn test_globs() {
+        let detector = MimeDetector::new().with_globs2(GLOBS2);
+
+        assert_eq!(detector.detect_name("main.c"), Some("text/x-csrc"));
+        assert_eq!(detector.detect_name("MAIN.C"), Some("text/x-c++src"));
+        assert_eq!(detector.detect_name("README.c"), Some("text/x-readme"));
+        assert_eq!(detector.detect_name("file.txt"), None);
+        assert!(fnmatch(b"[!a-c]?.x", b"dz.x"));
+        assert!(fnmatch(b"*.tar.*", b"a.tar.tar.gz"));
+        assert!(fnmatch(b"a*b*", b"ab"));
+        assert!(fnmatch(b"[x", b"[x"));
+        assert!(!fnmatch(b"*.c", b"main.cc"));
+        assert!(!fnmatch(b"?", b""));
+
+        let name = [b'a'; 4096];
+        assert!(!fnmatch(
+            &[b"*a".repeat(64).as_slice(), b"b"].concat(),
+            &name
+        ));
+    }
+
+    #[test]
+    fn test_magic() {
+        let detector = MimeDetector::new().with_magic_file(&magic_file()).unwrap();
+
+        assert_eq!(detector.detect_data(b"BAR..."), Some("application/x-bar"));
+        assert_eq!(
+            detector.detect_data(b"\x89PNG\r\n\x1a\n\0\0I"),
+            Some("image/png")
+        );
+        assert_eq!(detector.detect_data(b"\x89PNG\r\n\x1a\n\0\0"), None);
+        assert_eq!(
+            detector
+                .clone()
+                .with_max_sniffed_bytes(2)
+                .detect_data(b"BAR"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_magic_word_size() {
+        let magic =
+            b"MIME-Magic\0\n[50:application/x-host16]\n>0=\0\x04\x12\x34\xff\x00&\0\0\xff\xff~2\n";
+        let detector = MimeDetector::new().with_magic_file(magic).unwrap();
+        let host = [0x1234u16.to_ne_bytes(), 0xff00u16.to_ne_bytes()].concat();
+
+        assert_eq!(detector.detect_data(&host), Some("application/x-host16"));
+        assert!(
+            MimeDetector::new()
+                .with_magic_file(b"MIME-Magic\0\n[50:a/b]\n>0=\0\x03abc~2\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_magic_edge_cases() {
+        let magic = b"MIME-Magic\0\n[50:a/first]\n>0=\0\x01A\n[50:a/second]\n>0=\0\x01A\n";
+        let detector = MimeDetector::new().with_magic_file(magic).unwrap();
+        assert_eq!(detector.detect_data(b"A"), Some("a/first"));
+
+        let magic = b"MIME-Magic\0\n[50:a/b]\n>18446744073709551615=\0\x01A\n";
+        let detector = MimeDetector::new().with_magic_file(magic).unwrap();
+        assert_eq!(detector.detect_data(b"A"), None);
+
+        let mut overflowing = MagicMatch::new(usize::MAX, b"A");
+        assert!(!overflowing.matches(b"A"));
+        overflowing.offset = usize::MAX - 1;
+        overflowing.range_length = 2;
+        assert!(!overflowing.matches(b"A"));
+    }
+
+    #[test]
+    fn test_stages() {
+        let detector = MimeDetector::new()
+            .with_globs2(GLOBS2)
+            .with_magic_file(&magic_file())
+            .unwrap();
+
+        assert_eq!(
+            detector.detect(Some("file.dat"), Some(b"BAR")),
+            Some("application/x-bar")
+        );
+        assert_eq!(
+            detector.detect(Some("file.dat"), Some(b"???")),
+            Some("application/x-foo")
+        );
+        assert_eq!(
+            detector.detect(Some("main.c"), Some(b"BAR")),
+            Some("text/x-csrc")
+        );
+        assert_eq!(
+            detector.detect(Some("file"), Some(b"BAR")),
+            Some("application/x-bar")
+        );
+
+        let magic_only = detector.clone().with_stages(MimeStages::MagicOnly);
+        assert_eq!(
+            magic_only.detect(Some("main.c"), Some(b"BAR")),
+            Some("application/x-bar")
+        );
+        let glob_only = detector.with_stages(MimeStages::GlobOnly);
+        assert_eq!(glob_only.detect(Some("file"), Some(b"BAR")), None);
+    }
+}
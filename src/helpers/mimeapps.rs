@@ -0,0 +1,433 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::Error,
+    helpers::permissions::secure_write,
+    parser::models::{ContentEntry, DesktopFile, Entry, EntryFormat, EntrySet, FileFormat},
+};
+
+const DEFAULT_APPLICATIONS: &str = "Default Applications";
+
+const ADDED_ASSOCIATIONS: &str = "Added Associations";
+
+const REMOVED_ASSOCIATIONS: &str = "Removed Associations";
+
+const FILE_NAME: &str = "mimeapps.list";
+
+const LEGACY_FILE_NAME: &str = "defaults.list";
+
+/// Representation of a `mimeapps.list` file, which associates MIME types with applications.
+///
+/// Legacy `defaults.list` files can also be read, as they only contain the `[Default Applications]` group.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MimeAppsList {
+    desktop_file: DesktopFile,
+}
+
+impl Default for MimeAppsList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MimeAppsList {
+    /// Creates a list without any association.
+    pub fn new() -> Self {
+        Self {
+            desktop_file: DesktopFile {
+                content: vec![],
+                format: FileFormat::default(),
+            },
+        }
+    }
+
+    /// Returns the desktop file IDs of the default applications for the MIME type, by order of preference.
+    pub fn default_applications(&self, mime_type: &str) -> Vec<&str> {
+        self.values(DEFAULT_APPLICATIONS, mime_type)
+    }
+
+    /// Returns the desktop file IDs of the applications added to the MIME type.
+    pub fn added_associations(&self, mime_type: &str) -> Vec<&str> {
+        self.values(ADDED_ASSOCIATIONS, mime_type)
+    }
+
+    /// Returns the desktop file IDs of the applications removed from the MIME type.
+    pub fn removed_associations(&self, mime_type: &str) -> Vec<&str> {
+        self.values(REMOVED_ASSOCIATIONS, mime_type)
+    }
+
+    /// Sets the default applications for the MIME type, by order of preference.
+    pub fn set_default_applications(&mut self, mime_type: &str, applications: &[&str]) {
+        self.set_values(
+            DEFAULT_APPLICATIONS,
+            mime_type,
+            applications.iter().map(|a| (*a).to_owned()).collect(),
+        );
+    }
+
+    /// Adds the associations of the other list, which has a lower precedence.
+    ///
+    /// The applications of this list come first, followed by the ones only found in the other list.
+    /// As the specification requires, the associations removed by this list are not added from the other list,
+    /// and the associations added by this list are not removed by the other list,
+    /// so that lists can be merged one after the other by order of precedence.
+    pub fn merge(&mut self, other: &MimeAppsList) {
+        let higher = self.clone();
+        for header in [
+            DEFAULT_APPLICATIONS,
+            ADDED_ASSOCIATIONS,
+            REMOVED_ASSOCIATIONS,
+        ] {
+            let Some(group) = other.desktop_file.find(header) else {
+                continue;
+            };
+
+            for entry in group.without_comments() {
+                let overridden = match header {
+                    ADDED_ASSOCIATIONS => higher.removed_associations(&entry.key),
+                    REMOVED_ASSOCIATIONS => higher.added_associations(&entry.key),
+                    _ => vec![],
+                };
+                let mut values: Vec<String> = self
+                    .values(header, &entry.key)
+                    .into_iter()
+                    .map(str::to_owned)
+                    .collect();
+                let count = values.len();
+                for value in &entry.values {
+                    if !values.contains(value) && !overridden.contains(&value.as_str()) {
+                        values.push(value.clone());
+                    }
+                }
+                if values.len() > count {
+                    self.set_values(header, &entry.key, values);
+                }
+            }
+        }
+    }
+
+    fn values(&self, header: &str, mime_type: &str) -> Vec<&str> {
+        self.desktop_file
+            .find(header)
+            .and_then(|group| group.find(mime_type))
+            .map_or(vec![], |entry| {
+                entry
+                    .values
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|v| !v.is_empty())
+                    .collect()
+            })
+    }
+
+    fn set_values(&mut self, header: &str, mime_type: &str, values: Vec<String>) {
+        let group = self.desktop_file.ensure_group(header);
+
+        match group.find_mut(mime_type) {
+            Some(entry) => {
+                entry.values = values;
+                entry.format.raw_values = None;
+            }
+            None => group.content.push(Entry::Content(ContentEntry {
+                key: mime_type.to_owned(),
+                values,
+                locale: None,
+                format: EntryFormat::default(),
+            })),
+        }
+    }
+}
+
+impl From<DesktopFile> for MimeAppsList {
+    fn from(desktop_file: DesktopFile) -> Self {
+        Self { desktop_file }
+    }
+}
+
+impl From<MimeAppsList> for DesktopFile {
+    fn from(list: MimeAppsList) -> Self {
+        list.desktop_file
+    }
+}
+
+/// The directories in which `mimeapps.list` files are looked up, as defined by the
+/// [specification](https://specifications.freedesktop.org/mime-apps-spec/latest/file.html).
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MimeAppsLocations {
+    /// The user configuration directory, `$XDG_CONFIG_HOME`.
+    pub config_home: PathBuf,
+    /// The system configuration directories, `$XDG_CONFIG_DIRS`.
+    pub config_dirs: Vec<PathBuf>,
+    /// The user data directory, `$XDG_DATA_HOME`.
+    pub data_home: PathBuf,
+    /// The system data directories, `$XDG_DATA_DIRS`.
+    pub data_dirs: Vec<PathBuf>,
+    /// The names of the current desktop environments, `$XDG_CURRENT_DESKTOP`, in lowercase.
+    pub desktops: Vec<String>,
+}
+
+impl MimeAppsLocations {
+    /// Reads the locations from the XDG environment variables, with the defaults of the Base Directory specification.
+    pub fn from_env() -> Result<Self, Error> {
+        let home = env::var_os("HOME")
+            .map(PathBuf::from)
+            .ok_or(Error::NotFound(String::from("HOME")))?;
+        let dir = |name: &str, default: &str| {
+            env::var_os(name)
+                .filter(|dir| !dir.is_empty())
+                .map_or_else(|| home.join(default), PathBuf::from)
+        };
+        let dirs = |name: &str, default: &str| {
+            let value = env::var(name).ok().filter(|dirs| !dirs.is_empty());
+            value
+                .as_deref()
+                .unwrap_or(default)
+                .split(':')
+                .map(PathBuf::from)
+                .collect()
+        };
+
+        Ok(Self {
+            config_home: dir("XDG_CONFIG_HOME", ".config"),
+            config_dirs: dirs("XDG_CONFIG_DIRS", "/etc/xdg"),
+            data_home: dir("XDG_DATA_HOME", ".local/share"),
+            data_dirs: dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share"),
+            desktops: env::var("XDG_CURRENT_DESKTOP")
+                .map(|d| d.split(':').map(str::to_lowercase).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Returns the current locations of `mimeapps.list` files, by order of precedence.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![];
+        for dir in std::iter::once(&self.config_home).chain(&self.config_dirs) {
+            paths.extend(self.files_in(dir));
+        }
+        paths
+    }
+
+    /// Returns the deprecated locations of `mimeapps.list` and `defaults.list` files, by order of precedence.
+    ///
+    /// They come after the [current ones](Self::paths).
+    pub fn legacy_paths(&self) -> Vec<PathBuf> {
+        let application_dirs: Vec<PathBuf> = std::iter::once(&self.data_home)
+            .chain(&self.data_dirs)
+            .map(|dir| dir.join("applications"))
+            .collect();
+
+        let mut paths = vec![];
+        for dir in &application_dirs {
+            paths.extend(self.files_in(dir));
+        }
+        paths.extend(
+            application_dirs
+                .iter()
+                .map(|dir| dir.join(LEGACY_FILE_NAME)),
+        );
+        paths
+    }
+
+    /// Reads and merges every `mimeapps.list` file, including the legacy ones if requested.
+    ///
+    /// Missing files are ignored.
+    pub fn load(&self, include_legacy: bool) -> Result<MimeAppsList, Error> {
+        let mut paths = self.paths();
+        if include_legacy {
+            paths.extend(self.legacy_paths());
+        }
+
+        let mut list = MimeAppsList::new();
+        for path in paths {
+            if let Some(other) = read_list(&path)? {
+                list.merge(&other);
+            }
+        }
+        Ok(list)
+    }
+
+    /// Consolidates the user `mimeapps.list` and `defaults.list` files of the deprecated
+    /// `$XDG_DATA_HOME/applications` directory into `$XDG_CONFIG_HOME/mimeapps.list`.
+    ///
+    /// The associations already found in `$XDG_CONFIG_HOME/mimeapps.list` take precedence.
+    /// Only the groups gaining associations are replaced, as by [DesktopFile::save_group],
+    /// so that the other groups, the comments and the layout of the file are kept.
+    /// The legacy files are left in place for the applications still reading them.
+    /// Returns the path of the written file, or `None` if there was nothing to migrate.
+    pub fn migrate(&self) -> Result<Option<PathBuf>, Error> {
+        let legacy_dir = self.data_home.join("applications");
+        let mut legacy = vec![];
+        for path in [
+            legacy_dir.join(FILE_NAME),
+            legacy_dir.join(LEGACY_FILE_NAME),
+        ] {
+            legacy.extend(read_list(&path)?);
+        }
+        if legacy.is_empty() {
+            return Ok(None);
+        }
+
+        let destination = self.config_home.join(FILE_NAME);
+        let current = match read_list(&destination)? {
+            Some(current) => current,
+            None => {
+                // Created first, so that the file and its directories are private
+                secure_write(&destination, "")?;
+                MimeAppsList::new()
+            }
+        };
+        let mut list = current.clone();
+        for other in &legacy {
+            list.merge(other);
+        }
+
+        for header in [
+            DEFAULT_APPLICATIONS,
+            ADDED_ASSOCIATIONS,
+            REMOVED_ASSOCIATIONS,
+        ] {
+            let Some(group) = list.desktop_file.find(header) else {
+                continue;
+            };
+            if current.desktop_file.find(header) != Some(group) {
+                DesktopFile::save_group(&destination, group)?;
+            }
+        }
+        Ok(Some(destination))
+    }
+
+    /// Returns the desktop-specific files in the directory, then the generic one.
+    fn files_in(&self, dir: &Path) -> Vec<PathBuf> {
+        self.desktops
+            .iter()
+            .map(|desktop| dir.join(format!("{desktop}-{FILE_NAME}")))
+            .chain(std::iter::once(dir.join(FILE_NAME)))
+            .collect()
+    }
+}
+
+/// Reads the list at this path, or returns `None` if there is no such file.
+fn read_list(path: &Path) -> Result<Option<MimeAppsList>, Error> {
+    match fs::read(path) {
+        Ok(content) => DesktopFile::parse_mimeapps(&content)
+            .map(|file| Some(MimeAppsList::from(file)))
+            .map_err(Error::Parsing),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(Error::Io(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locations(root: &Path) -> MimeAppsLocations {
+        MimeAppsLocations {
+            config_home: root.join("config"),
+            config_dirs: vec![root.join("etc")],
+            data_home: root.join("data"),
+            data_dirs: vec![root.join("usr")],
+            desktops: vec![String::from("gnome")],
+        }
+    }
+
+    #[test]
+    fn test_lookup_order() {
+        let locations = locations(Path::new("/root"));
+
+        assert_eq!(
+            locations.paths(),
+            vec![
+                PathBuf::from("/root/config/gnome-mimeapps.list"),
+                PathBuf::from("/root/config/mimeapps.list"),
+                PathBuf::from("/root/etc/gnome-mimeapps.list"),
+                PathBuf::from("/root/etc/mimeapps.list"),
+            ]
+        );
+        assert_eq!(
+            locations.legacy_paths()[4..],
+            [
+                PathBuf::from("/root/data/applications/defaults.list"),
+                PathBuf::from("/root/usr/applications/defaults.list"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let mut list = MimeAppsList::from(
+            DesktopFile::parse_mimeapps(b"[Added Associations]\nimage/png=viewer.desktop;\n[Removed Associations]\ntext/plain=editor.desktop;\n")
+                .unwrap(),
+        );
+        list.merge(&MimeAppsList::from(
+            DesktopFile::parse_mimeapps(b"[Added Associations]\ntext/plain=editor.desktop;notes.desktop;\n[Removed Associations]\nimage/png=viewer.desktop;gimp.desktop;\n")
+                .unwrap(),
+        ));
+
+        assert_eq!(list.added_associations("text/plain"), vec!["notes.desktop"]);
+        assert_eq!(list.added_associations("image/png"), vec!["viewer.desktop"]);
+        assert_eq!(
+            list.removed_associations("text/plain"),
+            vec!["editor.desktop"]
+        );
+        assert_eq!(list.removed_associations("image/png"), vec!["gimp.desktop"]);
+    }
+
+    #[test]
+    fn test_load_and_migrate() {
+        let root = env::temp_dir().join(format!("freedesktop-rs-mimeapps-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let locations = locations(&root);
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::create_dir_all(root.join("data/applications")).unwrap();
+        fs::write(
+            root.join("config/mimeapps.list"),
+            "# Mine\n[Default Applications]\ntext/plain=editor.desktop;\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("data/applications/mimeapps.list"),
+            "[Added Associations]\ntext/plain=viewer.desktop;\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("data/applications/defaults.list"),
+            "[Default Applications]\ntext/plain=legacy.desktop\nimage/png=images.desktop\n",
+        )
+        .unwrap();
+
+        assert!(
+            locations
+                .load(false)
+                .unwrap()
+                .added_associations("text/plain")
+                .is_empty()
+        );
+        let merged = locations.load(true).unwrap();
+        assert_eq!(
+            merged.default_applications("text/plain"),
+            vec!["editor.desktop", "legacy.desktop"]
+        );
+
+        assert_eq!(
+            locations.migrate().unwrap(),
+            Some(root.join("config/mimeapps.list"))
+        );
+        let migrated = locations.load(false).unwrap();
+        assert_eq!(migrated, merged);
+        assert!(
+            fs::read_to_string(root.join("config/mimeapps.list"))
+                .unwrap()
+                .starts_with("# Mine\n[Default Applications]\n")
+        );
+        assert_eq!(
+            migrated.default_applications("image/png"),
+            vec!["images.desktop"]
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
-use std::ops::Deref;
+use alloc::{borrow::ToOwned, string::String};
+use core::ops::Deref;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "trash")))]
 /// Representation of a trash file.
@@ -7,6 +8,76 @@ pub mod trash;
 #[cfg(feature = "trash")]
 pub use trash::TrashFile;
 
+/// Creation of files and directories only accessible by their owner.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod permissions;
+#[cfg(feature = "std")]
+pub use permissions::secure_create;
+
+/// Percent-encoding of paths, in URIs and trash info files.
+#[cfg(feature = "std")]
+pub(crate) mod percent;
+
+/// Representation of the `[Desktop Entry]` group.
+pub mod desktop_entry;
+
+/// Collection of installed applications.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod applications;
+#[cfg(feature = "std")]
+pub use applications::ApplicationDatabase;
+
+/// Management of the applications started with the session.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod autostart;
+#[cfg(feature = "std")]
+pub use autostart::{Autostart, AutostartChange, AutostartEntries};
+
+/// Edition of the menus of the user.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod menu;
+#[cfg(feature = "std")]
+pub use menu::MenuEditor;
+
+/// Detection of the MIME type of files.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod mime;
+#[cfg(feature = "std")]
+pub use mime::{MimeDetector, MimeStages};
+
+/// Representation of the `mimeapps.list` files.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod mimeapps;
+#[cfg(feature = "std")]
+pub use mimeapps::{MimeAppsList, MimeAppsLocations};
+
+/// Representation of the list of recently used files.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod recent;
+#[cfg(feature = "std")]
+pub use recent::{RecentFile, RecentFiles};
+
+/// Management of the thumbnail cache.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod thumbnails;
+#[cfg(feature = "std")]
+pub use thumbnails::{Thumbnail, ThumbnailCache, ThumbnailSize};
+
+/// Representation of the interfaces implemented by a desktop entry.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod interface;
+#[cfg(feature = "std")]
+pub use interface::{Interface, InterfaceFile};
+
 /// Representation of an ASCII-valid string
 pub struct AsciiString {
     value: String,
@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+/// Percent-encodes the bytes of the path, except ASCII letters, digits and ``-._~/!$&'()*+,;=:@``.
+///
+/// These are the characters GLib leaves in the paths of URIs, so the URIs of thumbnails and their hashes
+/// are the same as those of GLib applications.
+pub(crate) fn encode_path(path: &Path) -> String {
+    encode_except(path, b"-._~/!$&'()*+,;=:@")
+}
+
+/// Percent-encodes the bytes of the path, except ASCII letters, digits and `-._~/`.
+///
+/// This is how GLib writes the `Path` key of trash info files, which are also read as lists, so `;` must be escaped.
+#[cfg(feature = "trash")]
+pub(crate) fn encode_trash_path(path: &Path) -> String {
+    encode_except(path, b"-._~/")
+}
+
+fn encode_except(path: &Path, kept: &[u8]) -> String {
+    let mut encoded = String::new();
+    for byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            byte if byte.is_ascii_alphanumeric() || kept.contains(byte) => {
+                encoded.push(*byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Returns the `file://` URI of the absolute path.
+pub(crate) fn file_uri(path: &Path) -> String {
+    format!("file://{}", encode_path(path))
+}
+
+/// Decodes a percent-encoded path, keeping the bytes which are not valid UTF-8 on Unix.
+pub(crate) fn decode_path(value: &str) -> PathBuf {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        PathBuf::from(std::ffi::OsStr::from_bytes(&decoded))
+    }
+    #[cfg(not(unix))]
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let path = Path::new("/tmp/my file%.txt");
+        assert_eq!(encode_path(path), "/tmp/my%20file%25.txt");
+        assert_eq!(file_uri(path), "file:///tmp/my%20file%25.txt");
+        assert_eq!(decode_path(&encode_path(path)), path);
+        assert_eq!(decode_path("/tmp/%zz%2"), Path::new("/tmp/%zz%2"));
+
+        let path = Path::new("/tmp/a!$&'()*+,;=:@b #?[]");
+        assert_eq!(file_uri(path), "file:///tmp/a!$&'()*+,;=:@b%20%23%3F%5B%5D");
+        assert_eq!(decode_path(&encode_path(path)), path);
+    }
+
+    #[cfg(feature = "trash")]
+    #[test]
+    fn test_trash_path() {
+        let path = Path::new("/tmp/a!$&'()*+,;=:@b #?[]");
+        assert_eq!(
+            encode_trash_path(path),
+            "/tmp/a%21%24%26%27%28%29%2A%2B%2C%3B%3D%3A%40b%20%23%3F%5B%5D"
+        );
+        assert_eq!(decode_path(&encode_trash_path(path)), path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = decode_path("/tmp/caf%E9%20bar");
+        assert_eq!(path.as_os_str().as_bytes(), b"/tmp/caf\xE9 bar");
+        assert_eq!(decode_path(&encode_path(&path)), path);
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+
+use crate::error::Error;
+
+/// The mode of the files created by the helpers, only readable and writable by their owner.
+pub const FILE_MODE: u32 = 0o600;
+
+/// The mode of the directories created by the helpers, only accessible by their owner.
+pub const DIR_MODE: u32 = 0o700;
+
+/// Counts the temporary files, so that threads of the same process never share one.
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Creates a new file with the [FILE_MODE], regardless of the umask.
+///
+/// Fails if the path already exists, so that an existing file or symbolic link is never written through.
+pub fn secure_create<P: AsRef<Path>>(path: P) -> Result<File, Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(FILE_MODE);
+
+    let file = options.open(path).map_err(Error::Io)?;
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(FILE_MODE))
+        .map_err(Error::Io)?;
+    Ok(file)
+}
+
+/// Creates the directory and its missing parents with the [DIR_MODE], regardless of the umask.
+///
+/// The modes of the directories which already exist are not changed.
+pub fn secure_create_dir_all<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+    let missing: Vec<&Path> = path
+        .as_ref()
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .collect();
+
+    for dir in missing.into_iter().rev() {
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        builder.mode(DIR_MODE);
+        match builder.create(dir) {
+            Ok(()) => {}
+            // Created by another process in the meantime
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(Error::Io(error)),
+        }
+        #[cfg(unix)]
+        fs::set_permissions(dir, fs::Permissions::from_mode(DIR_MODE)).map_err(Error::Io)?;
+    }
+    Ok(())
+}
+
+/// Replaces the content of the file, which is created with the [FILE_MODE] along with its missing parents.
+///
+/// The content is written to a new file which is then renamed, so that readers never see a partial file.
+pub fn secure_write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<(), Error> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        secure_create_dir_all(parent)?;
+    }
+    replace_file(path, true, |writer| writer.write_all(contents.as_ref()))
+}
+
+/// Replaces the file at the path atomically with what is written by the function.
+///
+/// The content is written to a temporary file in the same directory, synchronized to the disk,
+/// renamed over the file, and the directory is synchronized as well, so that a crash leaves either file whole.
+/// If the path is a symbolic link, the file it points to is replaced and the link is kept.
+/// A private file gets the [FILE_MODE], otherwise the permissions of the previous file are kept.
+pub(crate) fn replace_file(
+    path: &Path,
+    private: bool,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> Result<(), Error> {
+    let path = resolve_links(path).map_err(Error::Io)?;
+    let permissions = fs::metadata(&path).ok().map(|m| m.permissions());
+
+    let temporary = temporary_path(&path);
+    let file = match private {
+        true => secure_create(&temporary)?,
+        false => create_with_permissions(&temporary, permissions.as_ref()).map_err(Error::Io)?,
+    };
+    let saved = (|| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        // Read-only files could not be written if their permissions were set first
+        #[cfg(not(unix))]
+        if let (false, Some(permissions)) = (private, permissions) {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()?;
+        fs::rename(&temporary, &path)
+    })();
+    if saved.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    saved.map_err(Error::Io)?;
+
+    // The rename is only durable once the directory is
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let parent = match parent.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => parent,
+        };
+        File::open(parent)
+            .and_then(|dir| dir.sync_all())
+            .map_err(Error::Io)?;
+    }
+    Ok(())
+}
+
+/// Creates a new file which, on Unix, has the permissions of the file it replaces before anything is written to it,
+/// so that the content of a private file is never readable by others.
+fn create_with_permissions(path: &Path, permissions: Option<&fs::Permissions>) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if let Some(permissions) = permissions {
+        options.mode(permissions.mode());
+    }
+
+    let file = options.open(path)?;
+    // The umask may have removed some of the permissions
+    #[cfg(unix)]
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions.clone())?;
+    }
+    Ok(file)
+}
+
+/// Follows the symbolic links until a path which is not one, which may not exist.
+fn resolve_links(path: &Path) -> io::Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    // As many links as the kernel follows
+    for _ in 0..40 {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = fs::read_link(&path)?;
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            Ok(_) => return Ok(path),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(path),
+            Err(error) => return Err(error),
+        }
+    }
+    Err(io::Error::other(format!(
+        "too many symbolic links in {}",
+        path.display()
+    )))
+}
+
+/// Returns a path next to this one, unique to the process and the call.
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_secure_files() {
+        let root = env::temp_dir().join(format!("freedesktop-rs-secure-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let file = root.join("config/app/settings");
+
+        secure_write(&file, "first").unwrap();
+        secure_write(&file, "second").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "second");
+        assert!(secure_create(&file).is_err());
+        assert_eq!(fs::read_dir(file.parent().unwrap()).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&file), FILE_MODE);
+            assert_eq!(mode(&root.join("config")), DIR_MODE);
+            assert_eq!(mode(&root.join("config/app")), DIR_MODE);
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replace_file_through_link() {
+        let root = env::temp_dir().join(format!("freedesktop-rs-replace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("dotfiles")).unwrap();
+        let target = root.join("dotfiles/settings");
+        let link = root.join("settings");
+        fs::write(&target, "first").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink("dotfiles/settings", &link).unwrap();
+
+        replace_file(&link, false, |writer| {
+            let temporary = fs::read_dir(root.join("dotfiles"))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .find(|path| path != &target)
+                .unwrap();
+            assert_eq!(
+                fs::metadata(temporary).unwrap().permissions().mode() & 0o777,
+                0o640
+            );
+            writer.write_all(b"second")
+        })
+        .unwrap();
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "second");
+        assert_eq!(
+            fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o640
+        );
+        assert_eq!(fs::read_dir(root.join("dotfiles")).unwrap().count(), 1);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
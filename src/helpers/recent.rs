@@ -0,0 +1,532 @@
+use std::{
+    env,
+    fmt::{Display, Write},
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::error::Error;
+
+use super::{
+    menu::escape,
+    permissions::{secure_create, secure_create_dir_all, secure_write},
+};
+
+/// The name of the file listing the recently used files, in `$XDG_DATA_HOME`.
+pub const RECENT_FILES_NAME: &str = "recently-used.xbel";
+
+/// How long to wait between two attempts to take the lock.
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// The age after which a lock file is considered left by a writer which crashed, as saving never takes that long.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// An application which used a recent file.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct RecentApplication {
+    /// The name of the application.
+    pub name: String,
+    /// The command line used to open the file, where `%u` is the URI of the file.
+    pub exec: String,
+    /// When the application last used the file, as an ISO 8601 date.
+    pub modified: String,
+    /// How many times the application used the file.
+    pub count: u32,
+}
+
+/// A recently used file, stored as a bookmark of the XBEL file.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct RecentFile {
+    /// The URI of the file.
+    pub href: String,
+    /// When the file was added, as an ISO 8601 date.
+    pub added: String,
+    /// When the bookmark was last modified, as an ISO 8601 date.
+    pub modified: String,
+    /// When the file was last visited, as an ISO 8601 date.
+    pub visited: String,
+    /// The MIME type of the file.
+    pub mime_type: Option<String>,
+    /// The groups of the bookmark, such as the name of the application owning it.
+    pub groups: Vec<String>,
+    /// The applications which used the file.
+    pub applications: Vec<RecentApplication>,
+    /// Whether the bookmark should only be shown to the applications which registered it.
+    pub private: bool,
+}
+
+impl RecentFile {
+    /// Merges another version of the bookmark, keeping the most recent of each of its parts.
+    ///
+    /// Dates are compared as written, which orders the ISO 8601 dates in UTC used by the XBEL files.
+    fn merge(&mut self, other: RecentFile) {
+        for application in other.applications {
+            match self
+                .applications
+                .iter_mut()
+                .find(|a| a.name == application.name)
+            {
+                Some(existing) if existing.modified < application.modified => {
+                    *existing = application
+                }
+                Some(_) => {}
+                None => self.applications.push(application),
+            }
+        }
+        for group in other.groups {
+            if !self.groups.contains(&group) {
+                self.groups.push(group);
+            }
+        }
+        if self.modified < other.modified {
+            self.modified = other.modified;
+            self.mime_type = other.mime_type.or(self.mime_type.take());
+            self.private = other.private;
+        }
+        self.visited = self.visited.clone().max(other.visited);
+        if self.added.is_empty() || (!other.added.is_empty() && other.added < self.added) {
+            self.added = other.added;
+        }
+    }
+}
+
+/// The list of recently used files, stored in `recently-used.xbel`
+/// as defined by the [Desktop Bookmark specification](https://www.freedesktop.org/wiki/Specifications/desktop-bookmark-spec/).
+///
+/// Several applications update the file concurrently, so [RecentFiles::save] locks it and merges
+/// the changes into its current content instead of overwriting the bookmarks added by the others.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct RecentFiles {
+    files: Vec<RecentFile>,
+    removed: Vec<String>,
+}
+
+impl RecentFiles {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the path of the list of the user, `$XDG_DATA_HOME/recently-used.xbel`.
+    pub fn default_path() -> Result<PathBuf, Error> {
+        match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+            Some(data_home) => Ok(PathBuf::from(data_home).join(RECENT_FILES_NAME)),
+            None => env::var_os("HOME")
+                .map(|home| {
+                    PathBuf::from(home)
+                        .join(".local/share")
+                        .join(RECENT_FILES_NAME)
+                })
+                .ok_or(Error::NotFound(String::from("HOME"))),
+        }
+    }
+
+    /// Reads the list from the file, which is empty if the file does not exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::new()),
+            Err(error) => Err(Error::Io(error)),
+        }
+    }
+
+    /// Parses the content of an XBEL file.
+    ///
+    /// Only the elements defined by the specification are read, others are ignored.
+    pub fn parse(xbel: &str) -> Result<Self, Error> {
+        let mut files = vec![];
+        let mut current: Option<RecentFile> = None;
+        let mut rest = xbel;
+
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..]
+                .find('>')
+                .map(|end| start + end)
+                .ok_or_else(|| Error::InvalidValue(String::from("Unterminated XML tag")))?;
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+
+            let (name, attributes) = tag
+                .trim_end_matches('/')
+                .split_once(char::is_whitespace)
+                .unwrap_or((tag.trim_end_matches('/'), ""));
+            match name {
+                "bookmark" => {
+                    current = Some(RecentFile {
+                        href: attribute(attributes, "href")?,
+                        added: attribute(attributes, "added").unwrap_or_default(),
+                        modified: attribute(attributes, "modified").unwrap_or_default(),
+                        visited: attribute(attributes, "visited").unwrap_or_default(),
+                        ..RecentFile::default()
+                    })
+                }
+                "/bookmark" => files.extend(current.take()),
+                "mime:mime-type" => {
+                    if let Some(file) = &mut current {
+                        file.mime_type = Some(attribute(attributes, "type")?);
+                    }
+                }
+                "bookmark:group" => {
+                    let text_end = rest.find('<').unwrap_or(rest.len());
+                    if let Some(file) = &mut current {
+                        file.groups.push(unescape(rest[..text_end].trim()));
+                    }
+                }
+                "bookmark:application" => {
+                    if let Some(file) = &mut current {
+                        file.applications.push(RecentApplication {
+                            name: attribute(attributes, "name")?,
+                            exec: attribute(attributes, "exec").unwrap_or_default(),
+                            modified: attribute(attributes, "modified").unwrap_or_default(),
+                            count: attribute(attributes, "count")
+                                .ok()
+                                .and_then(|count| count.parse().ok())
+                                .unwrap_or(1),
+                        });
+                    }
+                }
+                "bookmark:private" => {
+                    if let Some(file) = &mut current {
+                        file.private = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            files,
+            removed: vec![],
+        })
+    }
+
+    /// Returns the bookmark of the file with this URI.
+    pub fn get(&self, href: &str) -> Option<&RecentFile> {
+        self.files.iter().find(|f| f.href == href)
+    }
+
+    /// Iterates over the bookmarks.
+    pub fn iter(&self) -> impl Iterator<Item = &RecentFile> {
+        self.files.iter()
+    }
+
+    /// Returns the number of bookmarks.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns whether there is no bookmark.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Adds the bookmark, or merges it into the bookmark of the same file.
+    pub fn insert(&mut self, file: RecentFile) {
+        self.removed.retain(|href| *href != file.href);
+        match self.files.iter_mut().find(|f| f.href == file.href) {
+            Some(existing) => existing.merge(file),
+            None => self.files.push(file),
+        }
+    }
+
+    /// Removes the bookmark of the file with this URI.
+    ///
+    /// The bookmark is also removed from the file when the list is saved.
+    pub fn remove(&mut self, href: &str) -> Option<RecentFile> {
+        if !self.removed.iter().any(|removed| removed == href) {
+            self.removed.push(href.to_owned());
+        }
+        let position = self.files.iter().position(|f| f.href == href)?;
+        Some(self.files.remove(position))
+    }
+
+    /// Merges another list, such as the current content of the file, into this one.
+    ///
+    /// Bookmarks removed from this list are not added back.
+    pub fn merge(&mut self, other: RecentFiles) {
+        for file in other.files {
+            if !self.removed.contains(&file.href) {
+                let removed = std::mem::take(&mut self.removed);
+                self.insert(file);
+                self.removed = removed;
+            }
+        }
+    }
+
+    /// Saves the list, merged with the current content of the file.
+    ///
+    /// The file is locked while it is updated, by creating a `.lock` file next to it, which contains the PID of the writer.
+    /// A lock file whose process is not running anymore, or older than a minute, was left by a writer which crashed,
+    /// so it is removed. If the lock cannot be taken before the timeout, an error of kind [ErrorKind::TimedOut] is returned.
+    /// The new content is written to a temporary file which is then renamed, so readers never see a partial file.
+    ///
+    /// Returns the merged list, as written.
+    pub fn save<P: AsRef<Path>>(&self, path: P, lock_timeout: Duration) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            secure_create_dir_all(parent)?;
+        }
+        let _lock = Lock::acquire(path, lock_timeout)?;
+
+        let mut merged = self.clone();
+        merged.merge(Self::load(path)?);
+        merged.removed.clear();
+
+        secure_write(path, merged.to_string())?;
+        Ok(merged)
+    }
+}
+
+impl Display for RecentFiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<xbel version=\"1.0\"
+      xmlns:bookmark=\"http://www.freedesktop.org/standards/desktop-bookmarks\"
+      xmlns:mime=\"http://www.freedesktop.org/standards/shared-mime-info\"
+>
+",
+        );
+        for file in &self.files {
+            let _ = writeln!(
+                xml,
+                "  <bookmark href=\"{}\" added=\"{}\" modified=\"{}\" visited=\"{}\">",
+                escape(&file.href),
+                escape(&file.added),
+                escape(&file.modified),
+                escape(&file.visited)
+            );
+            xml.push_str("    <info>\n      <metadata owner=\"http://freedesktop.org\">\n");
+            if let Some(mime_type) = &file.mime_type {
+                let _ = writeln!(
+                    xml,
+                    "        <mime:mime-type type=\"{}\"/>",
+                    escape(mime_type)
+                );
+            }
+            if !file.groups.is_empty() {
+                xml.push_str("        <bookmark:groups>\n");
+                for group in &file.groups {
+                    let _ = writeln!(
+                        xml,
+                        "          <bookmark:group>{}</bookmark:group>",
+                        escape(group)
+                    );
+                }
+                xml.push_str("        </bookmark:groups>\n");
+            }
+            if !file.applications.is_empty() {
+                xml.push_str("        <bookmark:applications>\n");
+                for application in &file.applications {
+                    let _ = writeln!(
+                        xml,
+                        "          <bookmark:application name=\"{}\" exec=\"{}\" modified=\"{}\" count=\"{}\"/>",
+                        escape(&application.name),
+                        escape(&application.exec),
+                        escape(&application.modified),
+                        application.count
+                    );
+                }
+                xml.push_str("        </bookmark:applications>\n");
+            }
+            if file.private {
+                xml.push_str("        <bookmark:private/>\n");
+            }
+            xml.push_str("      </metadata>\n    </info>\n  </bookmark>\n");
+        }
+        xml.push_str("</xbel>\n");
+        f.write_str(&xml)
+    }
+}
+
+/// A lock file, removed when dropped.
+struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    /// Creates the lock file of the path, waiting for the other writers to remove theirs.
+    fn acquire(path: &Path, timeout: Duration) -> Result<Self, Error> {
+        let lock_path = sibling(path, ".lock");
+        let deadline = Instant::now() + timeout;
+        loop {
+            match secure_create(&lock_path) {
+                Ok(mut file) => {
+                    let lock = Self { path: lock_path };
+                    io::Write::write_all(&mut file, std::process::id().to_string().as_bytes())
+                        .map_err(Error::Io)?;
+                    return Ok(lock);
+                }
+                Err(Error::Io(error)) if error.kind() == ErrorKind::AlreadyExists => {
+                    if is_stale(&lock_path) {
+                        match fs::remove_file(&lock_path) {
+                            Err(error) if error.kind() != ErrorKind::NotFound => {
+                                return Err(Error::Io(error));
+                            }
+                            _ => continue,
+                        }
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(Error::Io(io::Error::new(
+                            ErrorKind::TimedOut,
+                            format!("{} is locked", path.display()),
+                        )));
+                    }
+                    thread::sleep(LOCK_RETRY_DELAY);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Whether the lock file was left by a writer which crashed, because its process is not running or it is too old.
+///
+/// Processes are only checked where `/proc` lists them.
+fn is_stale(lock_path: &Path) -> bool {
+    let dead = fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|content| content.trim().parse::<u32>().ok())
+        .is_some_and(|pid| {
+            Path::new("/proc/self").exists() && !Path::new(&format!("/proc/{pid}")).exists()
+        });
+    let old = fs::metadata(lock_path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > STALE_LOCK_AGE));
+    dead || old
+}
+
+/// Returns the path of a file next to this one, whose name has this suffix.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Returns the unescaped value of the attribute of an XML tag.
+fn attribute(attributes: &str, name: &str) -> Result<String, Error> {
+    let mut rest = attributes;
+    while let Some((key, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\''))
+            .ok_or_else(|| Error::InvalidValue(format!("Unquoted value of attribute {key}")))?;
+        let (content, after) = value[1..]
+            .split_once(quote)
+            .ok_or_else(|| Error::InvalidValue(format!("Unterminated attribute {key}")))?;
+        if key.trim() == name {
+            return Ok(unescape(content));
+        }
+        rest = after;
+    }
+    Err(Error::NotFound(name.to_owned()))
+}
+
+/// Replaces the predefined XML entities.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(href: &str, application: &str, modified: &str) -> RecentFile {
+        RecentFile {
+            href: href.to_owned(),
+            added: modified.to_owned(),
+            modified: modified.to_owned(),
+            visited: modified.to_owned(),
+            mime_type: Some(String::from("text/plain")),
+            groups: vec![application.to_owned()],
+            applications: vec![RecentApplication {
+                name: application.to_owned(),
+                exec: format!("'{application} %u'"),
+                modified: modified.to_owned(),
+                count: 1,
+            }],
+            private: false,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut list = RecentFiles::new();
+        let mut file = bookmark("file:///tmp/a&b.txt", "gedit", "2024-01-01T10:00:00Z");
+        file.private = true;
+        list.insert(file);
+
+        let parsed = RecentFiles::parse(&list.to_string()).unwrap();
+        assert_eq!(parsed, list);
+        assert_eq!(
+            parsed.get("file:///tmp/a&b.txt").unwrap().applications[0].exec,
+            "'gedit %u'"
+        );
+    }
+
+    #[test]
+    fn test_concurrent_save() {
+        let dir = env::temp_dir().join(format!("freedesktop-rs-recent-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join(RECENT_FILES_NAME);
+        let timeout = Duration::from_secs(5);
+
+        // Both applications start from the same content
+        let mut initial = RecentFiles::new();
+        initial.insert(bookmark("file:///old", "gedit", "2024-01-01T10:00:00Z"));
+        initial.save(&path, timeout).unwrap();
+
+        let mut first = RecentFiles::load(&path).unwrap();
+        let mut second = RecentFiles::load(&path).unwrap();
+        first.insert(bookmark("file:///first", "gedit", "2024-01-02T10:00:00Z"));
+        second.insert(bookmark("file:///second", "eog", "2024-01-02T11:00:00Z"));
+        second.insert(bookmark("file:///old", "eog", "2024-01-03T10:00:00Z"));
+        first.remove("file:///old");
+
+        thread::scope(|scope| {
+            scope.spawn(|| first.save(&path, timeout).unwrap());
+            scope.spawn(|| second.save(&path, timeout).unwrap());
+        });
+
+        let saved = RecentFiles::load(&path).unwrap();
+        assert!(saved.get("file:///first").is_some());
+        assert!(saved.get("file:///second").is_some());
+        assert!(!sibling(&path, ".lock").exists());
+
+        // The lock of another writer makes the save wait, then fail
+        fs::write(sibling(&path, ".lock"), "").unwrap();
+        let error = saved.save(&path, Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(error, Error::Io(ref e) if e.kind() == ErrorKind::TimedOut));
+
+        // The lock of a writer which crashed is removed
+        let lock = fs::File::options()
+            .write(true)
+            .open(sibling(&path, ".lock"))
+            .unwrap();
+        lock.set_modified(std::time::SystemTime::now() - 2 * STALE_LOCK_AGE)
+            .unwrap();
+        saved.save(&path, Duration::from_millis(50)).unwrap();
+        if Path::new("/proc/self").exists() {
+            fs::write(sibling(&path, ".lock"), "4294967295").unwrap();
+            saved.save(&path, Duration::from_millis(50)).unwrap();
+        }
+        assert!(!sibling(&path, ".lock").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,490 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use md5::{Digest, Md5};
+
+use crate::{
+    error::Error,
+    helpers::{
+        percent::{decode_path, file_uri},
+        permissions::secure_write,
+    },
+};
+
+/// The directories of the thumbnails of each size, in the cache of the user.
+pub const SIZE_DIRS: [&str; 4] = ["normal", "large", "x-large", "xx-large"];
+
+/// The directory in which thumbnailers record the files they failed to thumbnail, in a directory per application.
+pub const FAIL_DIR: &str = "fail";
+
+/// The directory of a shared repository, next to the files whose thumbnails it contains.
+pub const SHARED_DIR: &str = ".sh_thumbnails";
+
+/// The sizes of thumbnails defined by the specification.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum ThumbnailSize {
+    /// At most 128x128 pixels.
+    Normal,
+    /// At most 256x256 pixels.
+    Large,
+    /// At most 512x512 pixels.
+    XLarge,
+    /// At most 1024x1024 pixels.
+    XXLarge,
+}
+
+impl ThumbnailSize {
+    /// Returns the maximum width and height of the thumbnails of this size, in pixels.
+    pub fn pixels(&self) -> u32 {
+        match self {
+            Self::Normal => 128,
+            Self::Large => 256,
+            Self::XLarge => 512,
+            Self::XXLarge => 1024,
+        }
+    }
+
+    /// Returns the name of the directory of the thumbnails of this size.
+    pub fn dir_name(&self) -> &'static str {
+        SIZE_DIRS[*self as usize]
+    }
+}
+
+/// Where a thumbnail was found.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ThumbnailLocation {
+    /// In the shared repository next to the original file.
+    Shared,
+    /// In the cache of the user.
+    Personal,
+}
+
+/// A thumbnail of a file, found by [ThumbnailCache::lookup].
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Thumbnail {
+    /// The path of the PNG image.
+    pub path: PathBuf,
+    /// Where the thumbnail was found.
+    pub location: ThumbnailLocation,
+}
+
+/// The thumbnail cache of the user, as defined by the
+/// [Thumbnail Managing Standard](https://specifications.freedesktop.org/thumbnail-spec/latest/).
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ThumbnailCache {
+    root: PathBuf,
+}
+
+/// A thumbnail of the cache, with what is needed to decide whether to prune it.
+struct CachedThumbnail {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl ThumbnailCache {
+    /// Creates the cache stored in this directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Returns the cache of the user, `$XDG_CACHE_HOME/thumbnails`.
+    pub fn from_env() -> Result<Self, Error> {
+        let cache_home = match env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+            Some(cache_home) => PathBuf::from(cache_home),
+            None => env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".cache"))
+                .ok_or(Error::NotFound(String::from("HOME")))?,
+        };
+        Ok(Self::new(cache_home.join("thumbnails")))
+    }
+
+    /// Returns the directory of the cache.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path of the thumbnail of the file with this URI in the cache, which may not exist.
+    ///
+    /// Its name is the MD5 hash of the URI.
+    pub fn personal_path(&self, uri: &str, size: ThumbnailSize) -> PathBuf {
+        self.root
+            .join(size.dir_name())
+            .join(format!("{}.png", hex_md5(uri.as_bytes())))
+    }
+
+    /// Stores the PNG image as the thumbnail of the file with this URI, and returns its path.
+    ///
+    /// The image must contain the `Thumb::URI` and `Thumb::MTime` keys. As required by the specification,
+    /// it is only readable by the user, and written to a temporary file which is then renamed.
+    pub fn store(&self, uri: &str, size: ThumbnailSize, png: &[u8]) -> Result<PathBuf, Error> {
+        let path = self.personal_path(uri, size);
+        secure_write(&path, png)?;
+        Ok(path)
+    }
+
+    /// Finds an up-to-date thumbnail of the local file.
+    ///
+    /// The shared repository next to the file is looked up first, as its thumbnails follow the file on
+    /// removable media and network shares, then the cache of the user.
+    /// A thumbnail is up-to-date if its `Thumb::MTime` key is the modification time of the file.
+    /// Shared thumbnails may omit it, in which case they are always up-to-date.
+    pub fn lookup(&self, original: &Path, size: ThumbnailSize) -> Option<Thumbnail> {
+        let modified = fs::metadata(original)
+            .and_then(|metadata| metadata.modified())
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_secs()
+            .to_string();
+        let is_valid = |path: &Path, required: bool| {
+            let Ok(png) = fs::read(path) else {
+                return false;
+            };
+            match png_text(&png, "Thumb::MTime") {
+                Some(mtime) => mtime == modified,
+                None => !required && png.starts_with(PNG_SIGNATURE),
+            }
+        };
+
+        if let Some(path) = shared_path(original, size).filter(|path| is_valid(path, false)) {
+            return Some(Thumbnail {
+                path,
+                location: ThumbnailLocation::Shared,
+            });
+        }
+        let absolute = std::path::absolute(original).ok()?;
+        let path = self.personal_path(&file_uri(&absolute), size);
+        is_valid(&path, true).then_some(Thumbnail {
+            path,
+            location: ThumbnailLocation::Personal,
+        })
+    }
+
+    /// Removes the thumbnails which are not needed anymore, and returns the number of bytes reclaimed.
+    ///
+    /// As recommended by the specification, the thumbnails of local files which do not exist anymore are removed,
+    /// unless their file may be on a volume which is not mounted, then the thumbnails not used for `max_age`. If the cache is still larger than `max_size` bytes,
+    /// the least recently used thumbnails are removed until it fits.
+    /// A thumbnail is used when it is read or written, so the latest of its access and modification times is used,
+    /// as access times are often not updated.
+    ///
+    /// Only the cache of the user is pruned: the thumbnails of shared repositories are managed along with the files.
+    pub fn prune(&self, max_age: Duration, max_size: u64) -> Result<u64, Error> {
+        let now = SystemTime::now();
+        let mut reclaimed = 0;
+        let mut kept = vec![];
+
+        for thumbnail in self.thumbnails()? {
+            let orphan =
+                original_path(&thumbnail.path).is_some_and(|original| is_deleted(&original));
+            let expired = now
+                .duration_since(thumbnail.last_used)
+                .is_ok_and(|age| age > max_age);
+            if orphan || expired {
+                reclaimed += remove(&thumbnail)?;
+            } else {
+                kept.push(thumbnail);
+            }
+        }
+
+        let mut size: u64 = kept.iter().map(|t| t.size).sum();
+        kept.sort_by_key(|t| t.last_used);
+        for thumbnail in kept {
+            if size <= max_size {
+                break;
+            }
+            size -= thumbnail.size;
+            reclaimed += remove(&thumbnail)?;
+        }
+
+        Ok(reclaimed)
+    }
+
+    /// Lists the thumbnails of every size, and the failures of every application.
+    fn thumbnails(&self) -> Result<Vec<CachedThumbnail>, Error> {
+        let mut dirs: Vec<PathBuf> = SIZE_DIRS.iter().map(|dir| self.root.join(dir)).collect();
+        match fs::read_dir(self.root.join(FAIL_DIR)) {
+            Ok(entries) => {
+                for entry in entries {
+                    dirs.push(entry.map_err(Error::Io)?.path());
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(Error::Io(error)),
+        }
+
+        let mut thumbnails = vec![];
+        for dir in dirs {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(error)
+                    if matches!(error.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) =>
+                {
+                    continue;
+                }
+                Err(error) => return Err(Error::Io(error)),
+            };
+            for entry in entries {
+                let path = entry.map_err(Error::Io)?.path();
+                if path.extension().is_none_or(|extension| extension != "png") {
+                    continue;
+                }
+                let metadata = fs::metadata(&path).map_err(Error::Io)?;
+                let modified = metadata.modified().map_err(Error::Io)?;
+                let last_used = metadata
+                    .accessed()
+                    .map_or(modified, |accessed| accessed.max(modified));
+                thumbnails.push(CachedThumbnail {
+                    path,
+                    size: metadata.len(),
+                    last_used,
+                });
+            }
+        }
+        Ok(thumbnails)
+    }
+}
+
+/// Returns the path of the thumbnail of the file in its shared repository, which may not exist.
+///
+/// Its name is the MD5 hash of the name of the file, so that it stays valid when the directory is moved.
+pub fn shared_path(original: &Path, size: ThumbnailSize) -> Option<PathBuf> {
+    let name = original.file_name()?;
+    Some(
+        original
+            .parent()?
+            .join(SHARED_DIR)
+            .join(size.dir_name())
+            .join(format!("{}.png", hex_md5(name.as_encoded_bytes()))),
+    )
+}
+
+/// Prunes the thumbnail cache of the user, see [ThumbnailCache::prune].
+pub fn prune(max_age: Duration, max_size: u64) -> Result<u64, Error> {
+    ThumbnailCache::from_env()?.prune(max_age, max_size)
+}
+
+/// Removes the thumbnail, and returns its size.
+fn remove(thumbnail: &CachedThumbnail) -> Result<u64, Error> {
+    match fs::remove_file(&thumbnail.path) {
+        Ok(()) => Ok(thumbnail.size),
+        // Another application pruned it first
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(0),
+        Err(error) => Err(Error::Io(error)),
+    }
+}
+
+/// Returns the path of the local file whose thumbnail this is, stored in its `Thumb::URI` key.
+fn original_path(thumbnail: &Path) -> Option<PathBuf> {
+    let uri = png_text(&fs::read(thumbnail).ok()?, "Thumb::URI")?;
+    uri.strip_prefix("file://").map(decode_path)
+}
+
+/// Whether the local file was deleted, rather than being on a volume which is not mounted.
+///
+/// The directory of a deleted file still exists, while the mount point of an unmounted volume
+/// is either removed or left empty, so the file is only considered deleted if its directory has other files.
+fn is_deleted(original: &Path) -> bool {
+    original.symlink_metadata().is_err()
+        && original
+            .parent()
+            .and_then(|parent| fs::read_dir(parent).ok())
+            .is_some_and(|mut entries| entries.next().is_some())
+}
+
+/// Returns the value of a text chunk of a PNG image.
+fn png_text(png: &[u8], key: &str) -> Option<String> {
+    let mut chunks = png.strip_prefix(PNG_SIGNATURE)?;
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes(chunks[..4].try_into().ok()?) as usize;
+        let kind = &chunks[4..8];
+        let data = chunks.get(8..8 + length)?;
+        match kind {
+            b"tEXt" => {
+                if let Some(value) = data
+                    .strip_prefix(key.as_bytes())
+                    .and_then(|rest| rest.strip_prefix(b"\0"))
+                {
+                    return Some(String::from_utf8_lossy(value).into_owned());
+                }
+            }
+            b"IDAT" | b"IEND" => return None,
+            _ => {}
+        }
+        chunks = chunks.get(12 + length..)?;
+    }
+    None
+}
+
+/// The bytes starting every PNG image.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Returns the MD5 hash of the data as lowercase hexadecimal, which names the thumbnails.
+fn hex_md5(data: &[u8]) -> String {
+    Md5::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::FileTimes;
+
+    use super::*;
+
+    /// Creates a PNG image without pixels, with the text chunks of a thumbnail.
+    fn thumbnail_png(texts: &[(&str, &str)]) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (key, value) in texts {
+            let text = format!("{key}\0{value}");
+            png.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            png.extend_from_slice(b"tEXt");
+            png.extend_from_slice(text.as_bytes());
+            png.extend_from_slice(&[0; 4]);
+        }
+        png.extend_from_slice(&[0, 0, 0, 0]);
+        png.extend_from_slice(b"IEND");
+        png.extend_from_slice(&[0; 4]);
+        png
+    }
+
+    fn write_thumbnail(path: &Path, uri: &str, age: Duration) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, thumbnail_png(&[("Thumb::URI", uri)])).unwrap();
+        let time = SystemTime::now() - age;
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_times(FileTimes::new().set_accessed(time).set_modified(time))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_md5() {
+        assert_eq!(hex_md5(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            hex_md5(b"file:///home/jens/photos/me.png"),
+            "c6ee772d9e49320e97ec29a7eb5b1697"
+        );
+        assert_eq!(
+            shared_path(Path::new("/home/jens/photos/me.png"), ThumbnailSize::Large).unwrap(),
+            Path::new(
+                "/home/jens/photos/.sh_thumbnails/large/7accaff1d29c5d074218919d4150d1e5.png"
+            )
+        );
+    }
+
+    #[test]
+    fn test_lookup() {
+        let root = env::temp_dir().join(format!("freedesktop-rs-lookup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let original = root.join("media/photo.jpg");
+        fs::create_dir_all(original.parent().unwrap()).unwrap();
+        fs::write(&original, "").unwrap();
+        let mtime = fs::metadata(&original)
+            .unwrap()
+            .modified()
+            .unwrap()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let cache = ThumbnailCache::new(root.join("thumbnails"));
+        let size = ThumbnailSize::Normal;
+        assert_eq!(cache.lookup(&original, size), None);
+
+        let uri = file_uri(&original);
+        let personal = cache
+            .store(&uri, size, &thumbnail_png(&[("Thumb::MTime", "0")]))
+            .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&personal).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(cache.lookup(&original, size), None);
+        fs::write(
+            &personal,
+            thumbnail_png(&[("Thumb::MTime", &mtime.to_string())]),
+        )
+        .unwrap();
+        assert_eq!(cache.lookup(&original, size).unwrap().path, personal);
+
+        let shared = shared_path(&original, size).unwrap();
+        fs::create_dir_all(shared.parent().unwrap()).unwrap();
+        fs::write(&shared, thumbnail_png(&[])).unwrap();
+        assert_eq!(
+            cache.lookup(&original, size),
+            Some(Thumbnail {
+                path: shared,
+                location: ThumbnailLocation::Shared
+            })
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_prune() {
+        let root =
+            env::temp_dir().join(format!("freedesktop-rs-thumbnails-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let original = root.join("original file.txt");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(&original, "").unwrap();
+        let uri = file_uri(&original);
+        let hour = Duration::from_secs(3600);
+
+        let cache = ThumbnailCache::new(root.join("thumbnails"));
+        let recent = cache.root().join("normal/recent.png");
+        let older = cache.root().join("large/older.png");
+        let expired = cache.root().join("x-large/expired.png");
+        let orphan = cache.root().join("normal/orphan.png");
+        let unmounted = cache.root().join("normal/unmounted.png");
+        let unmounted_root = cache.root().join("normal/unmounted-root.png");
+        let failure = cache.root().join("fail/app/failure.png");
+        fs::create_dir_all(root.join("media/usb")).unwrap();
+        write_thumbnail(&recent, &uri, Duration::ZERO);
+        write_thumbnail(&older, &uri, hour);
+        write_thumbnail(&expired, &uri, 48 * hour);
+        write_thumbnail(
+            &orphan,
+            &file_uri(&root.join("deleted.txt")),
+            Duration::ZERO,
+        );
+        write_thumbnail(
+            &unmounted,
+            &file_uri(&root.join("media/usb/photo.jpg")),
+            Duration::ZERO,
+        );
+        write_thumbnail(
+            &unmounted_root,
+            &file_uri(&root.join("media/disk/photo.jpg")),
+            Duration::ZERO,
+        );
+        write_thumbnail(&failure, "https://example.com/remote", 48 * hour);
+        let size = |path: &Path| fs::metadata(path).unwrap().len();
+        let removed_size = size(&older) + size(&expired) + size(&orphan) + size(&failure);
+        let kept_size = size(&recent) + size(&unmounted) + size(&unmounted_root);
+
+        let reclaimed = cache.prune(24 * hour, kept_size).unwrap();
+
+        assert_eq!(reclaimed, removed_size);
+        assert!(recent.exists());
+        assert!(unmounted.exists());
+        assert!(unmounted_root.exists());
+        assert!(!older.exists());
+        assert!(!expired.exists());
+        assert!(!orphan.exists());
+        assert!(!failure.exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,6 +1,10 @@
-use time::{format_description::BorrowedFormatItem, macros::format_description, PrimitiveDateTime};
+use time::{PrimitiveDateTime, format_description::BorrowedFormatItem, macros::format_description};
 
-use crate::parser::models::{ContentEntry, DesktopFile, Entry, EntrySet, Group, TopLevelEntry};
+use crate::parser::models::{ContentEntry, DesktopFile, Entry, EntryFormat, EntrySet};
+
+/// Operations on trash directories.
+mod operations;
+pub use operations::{CrossDevicePolicy, Progress, Trash};
 
 const DATE_FORMAT: &[BorrowedFormatItem] =
     format_description!("[year]-[month]-[day]T[hour repr:24]:[minute]:[second]");
@@ -10,6 +14,7 @@ const GROUP_NAME: &str = "Trash Info";
 #[cfg_attr(docsrs, doc(cfg(feature = "trash")))]
 /// Representation of a freedesktop trash file.
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrashFile {
     desktop_file: DesktopFile,
     /// Path of the trashed file.
@@ -23,45 +28,26 @@ impl TryFrom<TrashFile> for DesktopFile {
 
     fn try_from(trash_file: TrashFile) -> Result<Self, Self::Error> {
         let mut desktop_file = trash_file.desktop_file;
-        let group = desktop_file.find_mut(GROUP_NAME);
 
         let raw_date = trash_file
             .deletion_date
             .format(&DATE_FORMAT)
             .map_err(crate::error::Error::DateFormat)?;
 
-        let new_deletion_date = Entry::Content(ContentEntry {
-            key: String::from("DeletionDate"),
-            values: vec![raw_date.clone()],
-            locale: None,
-        });
-
-        let new_path = Entry::Content(ContentEntry {
-            key: String::from("Path"),
-            values: vec![trash_file.path.clone()],
-            locale: None,
-        });
-
-        if let Some(group) = group {
-            let path = group.find_mut("Path");
-            if let Some(path) = path {
-                path.values = vec![trash_file.path];
-            } else {
-                group.content.push(new_path);
-            }
-
-            let date = group.find_mut("DeletionDate");
-            if let Some(date) = date {
-                date.values = vec![raw_date];
-            } else {
-                group.content.push(new_deletion_date);
+        let group = desktop_file.ensure_group(GROUP_NAME);
+        for (key, value) in [("Path", trash_file.path), ("DeletionDate", raw_date)] {
+            match group.find_mut(key) {
+                Some(entry) => {
+                    entry.values = vec![value];
+                    entry.format.raw_values = None;
+                }
+                None => group.content.push(Entry::Content(ContentEntry {
+                    key: String::from(key),
+                    values: vec![value],
+                    locale: None,
+                    format: EntryFormat::default(),
+                })),
             }
-        } else {
-            let group = Group {
-                header: String::from(GROUP_NAME),
-                content: vec![new_path, new_deletion_date],
-            };
-            desktop_file.content.push(TopLevelEntry::Group(group));
         }
 
         Ok(desktop_file)
@@ -76,10 +62,19 @@ impl TryFrom<DesktopFile> for TrashFile {
         let raw_date = group.get("DeletionDate")?;
         let raw_path = group.get("Path")?;
 
-        let date = PrimitiveDateTime::parse(&raw_date.values[0], &DATE_FORMAT)
+        let empty = |key: &str| crate::error::Error::InvalidValue(format!("{key} is empty"));
+
+        let raw_date = raw_date
+            .values
+            .first()
+            .ok_or_else(|| empty("DeletionDate"))?;
+        let date = PrimitiveDateTime::parse(raw_date, &DATE_FORMAT)
             .map_err(crate::error::Error::DateParsing)?;
 
-        let path = raw_path.values[0].to_owned();
+        let path = raw_path.as_string()?.into_owned();
+        if path.is_empty() {
+            return Err(empty("Path"));
+        }
 
         Ok(Self {
             desktop_file: desktop,
@@ -93,7 +88,7 @@ impl TryFrom<DesktopFile> for TrashFile {
 mod test {
     use time::macros::datetime;
 
-    use crate::parser::models::CommentEntry;
+    use crate::parser::models::{CommentEntry, FileFormat, Group, TopLevelEntry};
 
     use super::*;
 
@@ -113,15 +108,18 @@ DeletionDate=2025-08-12T00:14:20";
                             Entry::Content(ContentEntry {
                                 key: String::from("Path"),
                                 values: vec![String::from("~/Downloads/file")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("DeletionDate"),
                                 values: vec![String::from("2025-08-12T00:14:20")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             })
                         ],
                     })],
+                    format: FileFormat::default(),
                 },
                 path: String::from("~/Downloads/file"),
                 deletion_date: datetime!(2025-08-12 00:14:20)
@@ -147,25 +145,30 @@ DeletionDate=2025-08-14T00:00:00
                             Entry::Content(ContentEntry {
                                 key: String::from("DeletionDate"),
                                 values: vec![String::from("2025-08-12T00:14:20")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("Path"),
                                 values: vec![String::from("~/Downloads/file")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("Path"),
                                 values: vec![String::from("/wrong/")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("DeletionDate"),
                                 values: vec![String::from("2025-08-14T00:00:00")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             })
                         ],
                     })],
+                    format: FileFormat::default(),
                 },
                 path: String::from("~/Downloads/file"),
                 deletion_date: datetime!(2025-08-12 00:14:20)
@@ -184,24 +187,29 @@ DeletionDate=2025-08-14T00:00:00
                             key: String::from("DeletionDate"),
                             values: vec![String::from("2025-08-12T00:14:20")],
                             locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Content(ContentEntry {
                             key: String::from("Path"),
                             values: vec![String::from("~/Downloads/file")],
                             locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Content(ContentEntry {
                             key: String::from("Path"),
                             values: vec![String::from("/wrong/")],
                             locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Content(ContentEntry {
                             key: String::from("DeletionDate"),
                             values: vec![String::from("2025-08-14T00:00:00")],
                             locale: None,
+                            format: EntryFormat::default(),
                         }),
                     ],
                 })],
+                format: FileFormat::default(),
             },
             path: String::from("~/Downloads/file"),
             deletion_date: datetime!(2025-08-12 00:14:20),
@@ -257,7 +265,8 @@ DeletionDate=2025-08-14T00:00:00";
                             Entry::Content(ContentEntry {
                                 key: String::from("DeletionDate"),
                                 values: vec![String::from("2025-08-12T00:14:20")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Comment(CommentEntry::Text(String::from(
                                 "Here is an awesome comment"
@@ -265,21 +274,25 @@ DeletionDate=2025-08-14T00:00:00";
                             Entry::Content(ContentEntry {
                                 key: String::from("Path"),
                                 values: vec![String::from("~/Downloads/file")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("Path"),
                                 values: vec![String::from("/wrong/")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Comment(CommentEntry::Blank(String::from("\n\n"))),
                             Entry::Content(ContentEntry {
                                 key: String::from("DeletionDate"),
                                 values: vec![String::from("2025-08-14T00:00:00")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             })
                         ],
                     })],
+                    format: FileFormat::default(),
                 },
                 path: String::from("~/Downloads/file"),
                 deletion_date: datetime!(2025-08-12 00:14:20)
@@ -291,4 +304,30 @@ DeletionDate=2025-08-14T00:00:00";
             trash_file
         );
     }
+
+    #[test]
+    fn reject_empty_values() {
+        for trash_file in [
+            "[Trash Info]\nPath=\nDeletionDate=2025-08-12T00:14:20\n",
+            "[Trash Info]\nPath=/home/user/file\nDeletionDate=\n",
+        ] {
+            assert!(matches!(
+                TrashFile::try_from(DesktopFile::try_from(trash_file).unwrap()),
+                Err(crate::error::Error::InvalidValue(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_file() {
+        let trash_file = "[Trash Info]\nPath=/home/user/file\nDeletionDate=2025-08-12T00:14:20\n";
+        let trash_object = TrashFile::try_from(DesktopFile::try_from(trash_file).unwrap()).unwrap();
+
+        let json = serde_json::to_string(&trash_object).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TrashFile>(&json).unwrap(),
+            trash_object
+        );
+    }
 }
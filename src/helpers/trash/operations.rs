@@ -0,0 +1,595 @@
+use std::{
+    env, fs,
+    io::{self, ErrorKind, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+use crate::{
+    error::Error,
+    parser::models::{DesktopFile, FileFormat},
+};
+
+use super::TrashFile;
+use crate::helpers::{
+    percent::{decode_path, encode_trash_path},
+    permissions::{secure_create, secure_create_dir_all},
+};
+
+const FILES_DIR: &str = "files";
+
+const INFO_DIR: &str = "info";
+
+const INFO_EXTENSION: &str = ".trashinfo";
+
+/// Progress of a trash operation, given to its callback after each processed item.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Progress<'a> {
+    /// The number of files and directories processed so far.
+    pub items: usize,
+    /// The number of bytes moved or removed so far.
+    pub bytes: u64,
+    /// The path of the item that was just processed.
+    pub path: &'a Path,
+}
+
+/// A trash directory, as defined by the [Trash specification](https://specifications.freedesktop.org/trash-spec/latest/).
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Trash {
+    root: PathBuf,
+    cross_device_policy: CrossDevicePolicy,
+}
+
+/// What to do when a file cannot be renamed into or out of the trash because it is on another filesystem.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub enum CrossDevicePolicy {
+    /// Fail with the error of the rename.
+    #[default]
+    Fail,
+    /// Copy the file and synchronize the copy to disk, then delete the original.
+    ///
+    /// If the copy fails or is cancelled, the partial copy is removed and the original is left untouched.
+    CopyAndDelete,
+}
+
+impl Trash {
+    /// Creates a trash located in this directory, which is created when needed.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            cross_device_policy: CrossDevicePolicy::default(),
+        }
+    }
+
+    /// Changes what to do when a file is on another filesystem than the trash.
+    pub fn with_cross_device_policy(mut self, policy: CrossDevicePolicy) -> Self {
+        self.cross_device_policy = policy;
+        self
+    }
+
+    /// Returns the home trash of the user, located in `$XDG_DATA_HOME/Trash`.
+    pub fn home() -> Result<Self, Error> {
+        let data_home = env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .ok_or(Error::NotFound(String::from("HOME")))?;
+
+        Ok(Self::new(data_home.join("Trash")))
+    }
+
+    /// Returns the directory of the trash.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Moves the file or directory to the trash, and returns its name in the trash.
+    ///
+    /// The deletion date is in local time, so this fails with [Error::Io] if the local offset cannot be determined,
+    /// which happens in multi-threaded programs on some platforms.
+    pub fn put(&self, path: &Path) -> Result<String, Error> {
+        self.put_with_progress(path, |_| ControlFlow::Continue(()))
+    }
+
+    /// Similar to [Self::put], but reports the progress to the callback, which can cancel the operation.
+    ///
+    /// When cancelled, the file is left in place and [Error::Cancelled] is returned.
+    pub fn put_with_progress(
+        &self,
+        path: &Path,
+        mut progress: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<String, Error> {
+        let path = std::path::absolute(path).map_err(Error::Io)?;
+        let file_name = path
+            .file_name()
+            .ok_or(Error::NotFound(path.display().to_string()))?
+            .to_string_lossy()
+            .into_owned();
+        let deletion_date = now()?;
+        secure_create_dir_all(self.root.join(FILES_DIR))?;
+        secure_create_dir_all(self.root.join(INFO_DIR))?;
+
+        let (name, mut info) = self.reserve_name(&file_name)?;
+        let info_path = self.info_path(&name);
+        let trash_file = TrashFile {
+            desktop_file: DesktopFile {
+                content: vec![],
+                format: FileFormat::default(),
+            },
+            path: encode_trash_path(&path),
+            deletion_date,
+        };
+        let written = DesktopFile::try_from(trash_file)
+            .and_then(|file| writeln!(info, "{file}").map_err(Error::Io));
+
+        let bytes = size_of(&path);
+        let result = written.and_then(|_| {
+            if progress(&Progress {
+                items: 1,
+                bytes,
+                path: &path,
+            })
+            .is_break()
+            {
+                return Err(Error::Cancelled);
+            }
+            self.move_path(&path, &self.files_path(&name), &mut progress)
+        });
+
+        match result {
+            Ok(()) => Ok(name),
+            Err(error) => {
+                // The info file is useless without the trashed file
+                let _ = fs::remove_file(info_path);
+                Err(error)
+            }
+        }
+    }
+
+    /// Moves the trashed file back to its original location.
+    ///
+    /// Fails with [ErrorKind::AlreadyExists] if a file is at this location, even if it was created during the restoration.
+    pub fn restore(&self, name: &str) -> Result<PathBuf, Error> {
+        self.restore_with_progress(name, |_| ControlFlow::Continue(()))
+    }
+
+    /// Similar to [Self::restore], but reports the progress to the callback, which can cancel the operation.
+    ///
+    /// When cancelled, the file is left in the trash and [Error::Cancelled] is returned.
+    pub fn restore_with_progress(
+        &self,
+        name: &str,
+        mut progress: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<PathBuf, Error> {
+        let info_path = self.info_path(name);
+        let info = fs::read(&info_path).map_err(Error::Io)?;
+        let trash_file =
+            TrashFile::try_from(DesktopFile::try_from(info.as_slice()).map_err(Error::Parsing)?)?;
+        let destination = decode_path(&trash_file.path);
+        if destination.symlink_metadata().is_ok() {
+            return Err(Error::Io(ErrorKind::AlreadyExists.into()));
+        }
+
+        let source = self.files_path(name);
+        if progress(&Progress {
+            items: 1,
+            bytes: size_of(&source),
+            path: &source,
+        })
+        .is_break()
+        {
+            return Err(Error::Cancelled);
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(Error::Io)?;
+        }
+        self.move_new(&source, &destination, &mut progress)?;
+        fs::remove_file(info_path).map_err(Error::Io)?;
+
+        Ok(destination)
+    }
+
+    /// Permanently removes every trashed file.
+    pub fn empty(&self) -> Result<(), Error> {
+        self.empty_with_progress(|_| ControlFlow::Continue(()))
+    }
+
+    /// Similar to [Self::empty], but reports the progress to the callback after each removed file, which can cancel the operation.
+    ///
+    /// When cancelled, the trashed files that were not completely removed stay listed in the trash and [Error::Cancelled] is returned.
+    pub fn empty_with_progress(
+        &self,
+        progress: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<(), Error> {
+        let mut remover = Reporter::new(progress);
+        let files = match fs::read_dir(self.root.join(FILES_DIR)) {
+            Ok(files) => files,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(Error::Io(error)),
+        };
+
+        for file in files {
+            let file = file.map_err(Error::Io)?;
+            remover.remove(&file.path())?;
+
+            let info_path = self.info_path(&file.file_name().to_string_lossy());
+            match fs::remove_file(info_path) {
+                Err(error) if error.kind() != ErrorKind::NotFound => return Err(Error::Io(error)),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renames the file, or copies then deletes it if allowed by the policy when it is on another filesystem.
+    fn move_path(
+        &self,
+        from: &Path,
+        to: &Path,
+        progress: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<(), Error> {
+        match fs::rename(from, to) {
+            Err(error)
+                if error.kind() == ErrorKind::CrossesDevices
+                    && self.cross_device_policy == CrossDevicePolicy::CopyAndDelete =>
+            {
+                copy_and_delete(from, to, progress)
+            }
+            result => result.map_err(Error::Io),
+        }
+    }
+
+    /// Similar to [Self::move_path], but fails with [ErrorKind::AlreadyExists] instead of replacing the destination,
+    /// even if it is created during the move.
+    fn move_new(
+        &self,
+        from: &Path,
+        to: &Path,
+        progress: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<(), Error> {
+        let moved = if from.symlink_metadata().map_err(Error::Io)?.is_dir() {
+            // Renaming a directory only replaces an empty one, so creating it first reserves the name
+            fs::create_dir(to).and_then(|_| {
+                fs::rename(from, to).inspect_err(|_| {
+                    let _ = fs::remove_dir(to);
+                })
+            })
+        } else {
+            // Unlike renaming, linking fails if the destination exists
+            fs::hard_link(from, to).and_then(|_| fs::remove_file(from))
+        };
+        match moved {
+            Err(error)
+                if error.kind() == ErrorKind::CrossesDevices
+                    && self.cross_device_policy == CrossDevicePolicy::CopyAndDelete =>
+            {
+                copy_and_delete(from, to, progress)
+            }
+            result => result.map_err(Error::Io),
+        }
+    }
+
+    /// Finds a name that is not used in the trash yet, and atomically creates its info file.
+    fn reserve_name(&self, file_name: &str) -> Result<(String, fs::File), Error> {
+        for index in 1.. {
+            let name = match index {
+                1 => file_name.to_owned(),
+                _ => format!("{file_name}.{index}"),
+            };
+            if self.files_path(&name).symlink_metadata().is_ok() {
+                continue;
+            }
+
+            match secure_create(self.info_path(&name)) {
+                Ok(info) => return Ok((name, info)),
+                Err(Error::Io(error)) if error.kind() == ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        unreachable!("There is always an unused name")
+    }
+
+    fn files_path(&self, name: &str) -> PathBuf {
+        self.root.join(FILES_DIR).join(name)
+    }
+
+    fn info_path(&self, name: &str) -> PathBuf {
+        self.root
+            .join(INFO_DIR)
+            .join(format!("{name}{INFO_EXTENSION}"))
+    }
+}
+
+/// Copies the file or directory, then deletes the original.
+///
+/// The partial copy is removed if the copy fails. If the original cannot be deleted, the copy is kept.
+fn copy_and_delete(
+    from: &Path,
+    to: &Path,
+    progress: impl FnMut(&Progress) -> ControlFlow<()>,
+) -> Result<(), Error> {
+    let mut copier = Reporter::new(progress);
+    let copied = copier.copy(from, to).and_then(|_| match to.parent() {
+        Some(parent) => sync(parent),
+        None => Ok(()),
+    });
+    if let Err(error) = copied {
+        let _ = match to.symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(to),
+            _ => fs::remove_file(to),
+        };
+        return Err(error);
+    }
+
+    match from.symlink_metadata().map_err(Error::Io)? {
+        metadata if metadata.is_dir() => fs::remove_dir_all(from),
+        _ => fs::remove_file(from),
+    }
+    .map_err(Error::Io)
+}
+
+/// Flushes the file or directory to disk.
+fn sync(path: &Path) -> Result<(), Error> {
+    fs::File::open(path)
+        .and_then(|file| file.sync_all())
+        .map_err(Error::Io)
+}
+
+/// Walks files and directories recursively, reporting the progress after each item.
+struct Reporter<F> {
+    items: usize,
+    bytes: u64,
+    progress: F,
+}
+
+impl<F: FnMut(&Progress) -> ControlFlow<()>> Reporter<F> {
+    fn new(progress: F) -> Self {
+        Self {
+            items: 0,
+            bytes: 0,
+            progress,
+        }
+    }
+
+    fn report(&mut self, path: &Path, bytes: u64) -> Result<(), Error> {
+        self.items += 1;
+        self.bytes += bytes;
+        let progress = Progress {
+            items: self.items,
+            bytes: self.bytes,
+            path,
+        };
+        match (self.progress)(&progress) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(Error::Cancelled),
+        }
+    }
+
+    fn remove(&mut self, path: &Path) -> Result<(), Error> {
+        let metadata = path.symlink_metadata().map_err(Error::Io)?;
+        if metadata.is_dir() {
+            for child in fs::read_dir(path).map_err(Error::Io)? {
+                self.remove(&child.map_err(Error::Io)?.path())?;
+            }
+            fs::remove_dir(path).map_err(Error::Io)?;
+            self.report(path, 0)
+        } else {
+            fs::remove_file(path).map_err(Error::Io)?;
+            self.report(path, metadata.len())
+        }
+    }
+
+    fn copy(&mut self, from: &Path, to: &Path) -> Result<(), Error> {
+        let metadata = from.symlink_metadata().map_err(Error::Io)?;
+        if metadata.is_dir() {
+            fs::create_dir(to).map_err(Error::Io)?;
+            for child in fs::read_dir(from).map_err(Error::Io)? {
+                let child = child.map_err(Error::Io)?;
+                self.copy(&child.path(), &to.join(child.file_name()))?;
+            }
+            fs::set_permissions(to, metadata.permissions()).map_err(Error::Io)?;
+            sync(to)?;
+            self.report(from, 0)
+        } else if metadata.is_symlink() {
+            copy_symlink(from, to)?;
+            self.report(from, 0)
+        } else {
+            let mut source = fs::File::open(from).map_err(Error::Io)?;
+            let mut target = fs::File::create_new(to).map_err(Error::Io)?;
+            let bytes = io::copy(&mut source, &mut target).map_err(Error::Io)?;
+            target
+                .set_permissions(metadata.permissions())
+                .and_then(|_| target.sync_all())
+                .map_err(Error::Io)?;
+            self.report(from, bytes)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), Error> {
+    let target = fs::read_link(from).map_err(Error::Io)?;
+    std::os::unix::fs::symlink(target, to).map_err(Error::Io)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::copy(from, to).map(|_| ()).map_err(Error::Io)
+}
+
+/// Returns the size of the file, or of every file in the directory.
+fn size_of(path: &Path) -> u64 {
+    match path.symlink_metadata() {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(path)
+            .map(|children| {
+                children
+                    .filter_map(Result::ok)
+                    .map(|child| size_of(&child.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Returns the current local time, as required by the `DeletionDate` key.
+///
+/// Fails with [Error::Io] if the local offset cannot be determined, rather than writing a date in another time zone.
+fn now() -> Result<PrimitiveDateTime, Error> {
+    let now = OffsetDateTime::now_local().map_err(|error| {
+        Error::Io(io::Error::other(format!(
+            "the deletion date could not be determined, {error}"
+        )))
+    })?;
+    Ok(PrimitiveDateTime::new(now.date(), now.time()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("freedesktop-rs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn put_and_restore() {
+        let dir = temp_dir("trash-put");
+        let trash = Trash::new(dir.join("Trash"));
+        let file = dir.join("my file.txt");
+        fs::write(&file, "content").unwrap();
+
+        let name = trash.put(&file).unwrap();
+        assert_eq!(name, "my file.txt");
+        assert!(!file.exists());
+        let info = fs::read_to_string(trash.info_path(&name)).unwrap();
+        assert!(info.contains("my%20file.txt"));
+
+        fs::write(&file, "other").unwrap();
+        assert_eq!(trash.put(&file).unwrap(), "my file.txt.2");
+
+        assert_eq!(trash.restore("my file.txt.2").unwrap(), file);
+        assert!(trash.restore(&name).is_err());
+
+        let file = dir.join("a;b.txt");
+        fs::write(&file, "content").unwrap();
+        let name = trash.put(&file).unwrap();
+        let info = fs::read_to_string(trash.info_path(&name)).unwrap();
+        assert!(info.contains("a%3Bb.txt"));
+        assert_eq!(trash.restore(&name).unwrap(), file);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn move_without_replacing() {
+        let dir = temp_dir("trash-move-new");
+        let trash = Trash::new(dir.join("Trash"));
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+        fs::create_dir_all(dir.join("tree/sub")).unwrap();
+        fs::create_dir(dir.join("other")).unwrap();
+
+        let never = |_: &Progress| ControlFlow::Continue(());
+        for (from, to) in [("a", "b"), ("tree", "other"), ("tree", "a")] {
+            let result = trash.move_new(&dir.join(from), &dir.join(to), never);
+            assert!(
+                matches!(result, Err(Error::Io(ref error)) if error.kind() == ErrorKind::AlreadyExists),
+                "{from} {to}"
+            );
+        }
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "b");
+
+        trash
+            .move_new(&dir.join("a"), &dir.join("c"), never)
+            .unwrap();
+        trash
+            .move_new(&dir.join("tree"), &dir.join("copy"), never)
+            .unwrap();
+        assert_eq!(fs::read_to_string(dir.join("c")).unwrap(), "a");
+        assert!(!dir.join("a").exists());
+        assert!(dir.join("copy/sub").is_dir());
+        assert!(!dir.join("tree").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn empty_with_progress_and_cancel() {
+        let dir = temp_dir("trash-empty");
+        let trash = Trash::new(dir.join("Trash"));
+        let tree = dir.join("tree");
+        fs::create_dir_all(tree.join("sub")).unwrap();
+        fs::write(tree.join("a"), "12345").unwrap();
+        fs::write(tree.join("sub/b"), "123").unwrap();
+
+        let mut cancelled = 0;
+        trash
+            .put_with_progress(&tree, |_| {
+                cancelled += 1;
+                ControlFlow::Break(())
+            })
+            .unwrap_err();
+        assert_eq!(cancelled, 1);
+        assert!(tree.exists());
+        trash.put(&tree).unwrap();
+
+        let mut last = (0, 0);
+        let result = trash.empty_with_progress(|progress| {
+            last = (progress.items, progress.bytes);
+            if progress.items == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(last.0, 2);
+        assert!(trash.info_path("tree").exists());
+
+        trash
+            .empty_with_progress(|progress| {
+                last = (progress.items, progress.bytes);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(last.0, 2);
+        assert!(!trash.info_path("tree").exists());
+        assert_eq!(fs::read_dir(dir.join("Trash/files")).unwrap().count(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copy_and_delete_with_rollback() {
+        let dir = temp_dir("trash-copy");
+        let tree = dir.join("tree");
+        let copy = dir.join("copy");
+        fs::create_dir_all(tree.join("sub")).unwrap();
+        fs::write(tree.join("a"), "12345").unwrap();
+        fs::write(tree.join("sub/b"), "123").unwrap();
+
+        let result = copy_and_delete(&tree, &copy, |progress| match progress.items {
+            2 => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        });
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(!copy.exists());
+        assert!(tree.join("sub/b").exists());
+
+        let mut last = (0, 0);
+        copy_and_delete(&tree, &copy, |progress| {
+            last = (progress.items, progress.bytes);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(last, (4, 8));
+        assert!(!tree.exists());
+        assert_eq!(fs::read_to_string(copy.join("sub/b")).unwrap(), "123");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
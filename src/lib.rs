@@ -1,5 +1,6 @@
 #![warn(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 //! This crate is a Rust parser of Desktop Entry files.
 //!
 //! It follows the specification found on [their website](https://specifications.freedesktop.org/desktop-entry-spec/1.1/)
@@ -18,6 +19,12 @@
 //!      content.as_slice().try_into()
 //! }
 //! ```
+//!
+//! # `no_std`
+//! The parser core only requires `alloc` when the default `std` feature is disabled.
+//! The helpers reading the file system, the registries and the features depending on other crates require `std`.
+
+extern crate alloc;
 
 /// Models and low level parser
 pub mod parser;
@@ -27,3 +34,27 @@ pub mod error;
 
 /// High level representations of specific Freedesktop structures
 pub mod helpers;
+
+/// Deserialization of key-files into user types
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "serde")]
+pub use de::from_str;
+
+/// Serialization of user types as key-files
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "serde")]
+pub use ser::to_string;
+
+/// Process-global registries of known values
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod registry;
+
+/// Compatibility testing against corpora of real-world files
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -0,0 +1,148 @@
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::{
+    error::Error,
+    parser::models::{DesktopFile, FileFormat, LineEnding, TopLevelEntry},
+};
+
+use super::{
+    ParserOptions, detect_line_ending, normalize_lines, parse_entries, strip_byte_order_mark,
+};
+
+impl DesktopFile {
+    /// Parses the content of an asynchronous reader.
+    ///
+    /// The input is read line by line and parsed one group at a time, so that only the current group is buffered.
+    /// Each group is normalized as by [Self::parse_with_options] before being parsed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: R) -> Result<Self, Error> {
+        Self::from_async_reader_with_options(reader, &ParserOptions::default()).await
+    }
+
+    /// Similar to [Self::from_async_reader], but with the given options.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn from_async_reader_with_options<R: AsyncRead + Unpin>(
+        reader: R,
+        options: &ParserOptions,
+    ) -> Result<Self, Error> {
+        let mut reader = BufReader::new(reader);
+        let mut content = vec![];
+        let mut chunk = vec![];
+        let mut line = vec![];
+        let mut byte_order_mark = false;
+        let mut line_ending = LineEnding::default();
+
+        loop {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .await
+                .map_err(Error::Io)?;
+
+            // A group ends where the next one starts
+            if read == 0 || (line.first() == Some(&b'[') && !chunk.is_empty()) {
+                let normalized = normalize_lines(&chunk, line_ending);
+                if !parse_chunk(&normalized, &mut content, options)? {
+                    break;
+                }
+                chunk.clear();
+            }
+            if read == 0 {
+                break;
+            }
+            if content.is_empty() && chunk.is_empty() {
+                let (first_line, has_byte_order_mark) = strip_byte_order_mark(&line);
+                byte_order_mark = has_byte_order_mark;
+                // The line ending of the first line is the one of the file
+                line_ending = detect_line_ending(first_line);
+                chunk.extend_from_slice(first_line);
+                continue;
+            }
+            chunk.extend_from_slice(&line);
+        }
+
+        let mut file = Self {
+            content,
+            format: FileFormat {
+                byte_order_mark,
+                line_ending,
+            },
+        };
+        file.apply_options(options);
+        Ok(file)
+    }
+}
+
+/// Parses the chunk into the content, and returns whether the whole chunk could be parsed.
+fn parse_chunk(
+    chunk: &[u8],
+    content: &mut Vec<TopLevelEntry>,
+    options: &ParserOptions,
+) -> Result<bool, Error> {
+    let (rest, mut entries) =
+        parse_entries(chunk, options).map_err(|e| Error::Parsing(e.to_owned()))?;
+    content.append(&mut entries);
+
+    Ok(rest.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_async_parsing() {
+        let file = "# Outside comment
+[Desktop Entry]
+Type=Application
+
+[Desktop Action new]
+Name=  New window
+Exec=app --new
+";
+
+        assert_eq!(
+            DesktopFile::from_async_reader(file.as_bytes())
+                .await
+                .unwrap(),
+            DesktopFile::try_from(file).unwrap()
+        );
+        assert_eq!(
+            DesktopFile::from_async_reader_with_options(
+                file.as_bytes(),
+                &ParserOptions::new().untrimmed_values()
+            )
+            .await
+            .unwrap(),
+            DesktopFile::parse_with_options(
+                file.as_bytes(),
+                &ParserOptions::new().untrimmed_values()
+            )
+            .unwrap()
+        );
+
+        let crlf = file.replace('\n', "\r\n");
+        let parsed = DesktopFile::from_async_reader(crlf.as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(parsed.format.line_ending, LineEnding::CrLf);
+        assert_eq!(
+            parsed.to_string(),
+            DesktopFile::try_from(crlf.as_str()).unwrap().to_string()
+        );
+    }
+
+    #[cfg(feature = "encoding")]
+    #[tokio::test]
+    async fn test_async_legacy_encoding() {
+        let mut file = b"[Desktop Entry]\r\nEncoding=Legacy-Mixed\r\nComment=Caf\xE9\r\n".to_vec();
+        file.extend_from_slice(b"Name[ru_RU.KOI8-R]=\xF0\xD2\xC9\xCC\xCF\xD6\xC5\xCE\xC9\xC5\r\n");
+
+        assert_eq!(
+            DesktopFile::from_async_reader(file.as_slice())
+                .await
+                .unwrap(),
+            DesktopFile::try_from(file.as_slice()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,457 @@
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::{
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::parser::models::{
+    CommentEntry, ContentEntry, DesktopFile, Entry, EntryFormat, FileFormat, Group, Locale,
+    MAIN_GROUP, TopLevelEntry, escape_kept_value,
+};
+
+/// The start of the banner, which tells it apart from the comments written by hand.
+const MARKER: &str = "@generated by";
+
+/// The notice written by [Banner::with_notice].
+const NOTICE: &str = "Do not edit, this file is generated and will be overwritten";
+
+/// A comment written at the start of generated files, describing how they were generated.
+///
+/// It starts with `@generated by`, so that it is only mistaken for a comment written in the same way.
+/// The timestamp is the time of the generation, or `SOURCE_DATE_EPOCH` if it is set so that builds are reproducible.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Banner {
+    tool: String,
+    timestamp: bool,
+    notice: bool,
+}
+
+impl Banner {
+    /// Creates the banner of a tool, such as `my-generator 1.0`.
+    pub fn new(tool: &str) -> Self {
+        Self {
+            tool: tool.to_owned(),
+            timestamp: true,
+            notice: false,
+        }
+    }
+
+    /// Adds a line asking not to edit the file, as it will be generated again.
+    pub fn with_notice(mut self) -> Self {
+        self.notice = true;
+        self
+    }
+
+    /// Leaves the timestamp out, so that the generated files only change when their content changes.
+    pub fn without_timestamp(mut self) -> Self {
+        self.timestamp = false;
+        self
+    }
+
+    /// Returns whether the text has a timestamp, which changes each time it is written.
+    #[cfg(feature = "std")]
+    pub(crate) fn has_timestamp(&self) -> bool {
+        self.timestamp
+    }
+
+    /// Returns the lines of the comment: its [text](Self::text), then the notice if any.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![self.text()];
+        if self.notice {
+            lines.push(String::from(NOTICE));
+        }
+        lines
+    }
+
+    /// Returns the text of the first line of the comment.
+    ///
+    /// Without the `std` feature, there is no clock to read, so the timestamp is left out.
+    pub fn text(&self) -> String {
+        #[cfg(feature = "std")]
+        if self.timestamp {
+            return self.text_with_timestamp();
+        }
+        format!("{MARKER} {}", self.tool)
+    }
+
+    #[cfg(feature = "std")]
+    fn text_with_timestamp(&self) -> String {
+        let seconds = env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+            });
+        format!("{MARKER} {} on {}", self.tool, format_timestamp(seconds))
+    }
+}
+
+impl DesktopFile {
+    /// Writes the banner at the start of the file, replacing the banner already written there, if any.
+    ///
+    /// Only a first comment starting with `@generated by` is replaced, with the notice following it,
+    /// so that the comments written by hand are kept.
+    /// Stamping a file again only changes its timestamp, so that generated files can be compared.
+    /// ```
+    /// use freedesktop_rs::parser::{Banner, models::DesktopFile};
+    ///
+    /// let mut file = DesktopFile::try_from("# @generated by old 0.1\n[Desktop Entry]\nName=App\n").unwrap();
+    /// file.stamp(&Banner::new("generator 1.0").without_timestamp().with_notice());
+    ///
+    /// assert_eq!(
+    ///     file.to_string(),
+    ///     "# @generated by generator 1.0\n# Do not edit, this file is generated and will be overwritten\n[Desktop Entry]\nName=App"
+    /// );
+    /// ```
+    pub fn stamp(&mut self, banner: &Banner) {
+        let is_text = |index: usize, is_stamp: &dyn Fn(&str) -> bool| {
+            matches!(self.content.get(index),
+                Some(TopLevelEntry::Comment(CommentEntry::Text(text))) if is_stamp(text))
+        };
+        let stamped = match is_text(0, &|text| text.starts_with(MARKER)) {
+            true if is_text(1, &|text| text == NOTICE) => 2,
+            true => 1,
+            false => 0,
+        };
+        self.content.splice(
+            ..stamped,
+            banner
+                .lines()
+                .into_iter()
+                .map(|line| TopLevelEntry::Comment(CommentEntry::Text(line))),
+        );
+    }
+}
+
+/// Formats seconds since the Unix epoch as an ISO 8601 date in UTC.
+#[cfg(feature = "std")]
+fn format_timestamp(seconds: u64) -> String {
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+    // Converts the days to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Builds a [DesktopFile] from its groups, escaping the values as they are added:
+/// ```
+/// use freedesktop_rs::parser::DesktopFileBuilder;
+///
+/// let file = DesktopFileBuilder::new()
+///     .group("Desktop Action new")
+///     .key("Exec", "app --new")
+///     .group("Desktop Entry")
+///     .key("Type", "Application")
+///     .key("Comment", "First line\nSecond line")
+///     .list("Categories", ["Game", "Arcade"])
+///     .build();
+///
+/// assert_eq!(
+///     file.to_string(),
+///     "[Desktop Entry]
+/// Type=Application
+/// Comment=First line\\nSecond line
+/// Categories=Game;Arcade;
+///
+/// [Desktop Action new]
+/// Exec=app --new"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DesktopFileBuilder {
+    banner: Option<Banner>,
+    comments: Vec<TopLevelEntry>,
+    groups: Vec<Group>,
+}
+
+/// Builds a group of a [DesktopFileBuilder], see [DesktopFileBuilder::group].
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    file: DesktopFileBuilder,
+    group: Group,
+}
+
+impl DesktopFileBuilder {
+    /// Creates a builder of an empty file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the banner as the first line of the file.
+    pub fn banner(mut self, banner: Banner) -> Self {
+        self.banner = Some(banner);
+        self
+    }
+
+    /// Adds a comment at the start of the file, before any group.
+    pub fn comment(mut self, text: &str) -> Self {
+        self.comments
+            .push(TopLevelEntry::Comment(CommentEntry::Text(text.to_owned())));
+        self
+    }
+
+    /// Starts a group, or continues it if it was already started.
+    pub fn group(mut self, header: &str) -> GroupBuilder {
+        let group = match self.groups.iter().position(|g| g.header == header) {
+            Some(position) => self.groups.remove(position),
+            None => Group {
+                header: header.to_owned(),
+                content: vec![],
+            },
+        };
+        GroupBuilder { file: self, group }
+    }
+
+    /// Builds the file.
+    ///
+    /// The `[Desktop Entry]` group is written first, as required by the specification,
+    /// and the other groups are written in the order in which they were started.
+    pub fn build(self) -> DesktopFile {
+        let mut groups = self.groups;
+        if let Some(position) = groups.iter().position(|g| g.header == MAIN_GROUP) {
+            let main_group = groups.remove(position);
+            groups.insert(0, main_group);
+        }
+
+        let group_count = groups.len();
+        let mut content: Vec<TopLevelEntry> = self.comments;
+        for (i, mut group) in groups.into_iter().enumerate() {
+            // Separate the groups with a blank line
+            if i + 1 < group_count {
+                group
+                    .content
+                    .push(Entry::Comment(CommentEntry::Blank(String::from("\n"))));
+            }
+            content.push(TopLevelEntry::Group(group));
+        }
+
+        let mut file = DesktopFile {
+            content,
+            format: FileFormat::default(),
+        };
+        if let Some(banner) = &self.banner {
+            file.stamp(banner);
+        }
+        file
+    }
+}
+
+impl GroupBuilder {
+    /// Sets the value of the key, replacing its previous value.
+    pub fn key<T: Display>(self, key: &str, value: T) -> Self {
+        self.insert(
+            key,
+            None,
+            vec![escape_kept_value(&value.to_string(), None)],
+            false,
+        )
+    }
+
+    /// Sets the value of the key for the locale, replacing its previous value.
+    ///
+    /// The entry is written after the other entries of the key.
+    pub fn localized_key<T: Display>(self, key: &str, locale: Locale, value: T) -> Self {
+        self.insert(
+            key,
+            Some(locale),
+            vec![escape_kept_value(&value.to_string(), None)],
+            false,
+        )
+    }
+
+    /// Sets the values of the key, replacing its previous values.
+    ///
+    /// The values are followed by a separator, as recommended by the specification.
+    pub fn list<T: Display>(self, key: &str, values: impl IntoIterator<Item = T>) -> Self {
+        let values = values
+            .into_iter()
+            .map(|v| escape_kept_value(&v.to_string(), Some(';')))
+            .collect();
+        self.insert(key, None, values, true)
+    }
+
+    /// Adds a comment after the entries of the group.
+    pub fn comment(mut self, text: &str) -> Self {
+        self.group
+            .content
+            .push(Entry::Comment(CommentEntry::Text(text.to_owned())));
+        self
+    }
+
+    /// Adds a comment describing an entry of the group, just before its first entry.
+    ///
+    /// If the group has no such entry yet, the comment is added after the entries of the group,
+    /// so that it precedes the entry once it is added.
+    pub fn annotate(mut self, key: &str, text: &str) -> Self {
+        let comment = Entry::Comment(CommentEntry::Text(text.to_owned()));
+        let content = &mut self.group.content;
+        match content
+            .iter()
+            .position(|e| matches!(e, Entry::Content(c) if c.key == key))
+        {
+            Some(position) => content.insert(position, comment),
+            None => content.push(comment),
+        }
+        self
+    }
+
+    /// Finishes this group and starts another one, see [DesktopFileBuilder::group].
+    pub fn group(self, header: &str) -> GroupBuilder {
+        self.finish().group(header)
+    }
+
+    /// Finishes this group and builds the file, see [DesktopFileBuilder::build].
+    pub fn build(self) -> DesktopFile {
+        self.finish().build()
+    }
+
+    /// Finishes this group, to add comments at the start of the file.
+    pub fn finish(mut self) -> DesktopFileBuilder {
+        self.file.groups.push(self.group);
+        self.file
+    }
+
+    fn insert(
+        mut self,
+        key: &str,
+        locale: Option<Locale>,
+        values: Vec<String>,
+        trailing_separator: bool,
+    ) -> Self {
+        let entry = ContentEntry {
+            key: key.to_owned(),
+            values,
+            locale,
+            format: EntryFormat {
+                trailing_separator,
+                ..EntryFormat::default()
+            },
+        };
+
+        let content = &mut self.group.content;
+        let same_key = |e: &Entry| matches!(e, Entry::Content(c) if c.key == entry.key);
+        if let Some(existing) = content.iter_mut().find(
+            |e| matches!(e, Entry::Content(c) if c.key == entry.key && c.locale == entry.locale),
+        ) {
+            *existing = Entry::Content(entry);
+        } else if let Some(last) = content.iter().rposition(same_key) {
+            content.insert(last + 1, Entry::Content(entry));
+        } else {
+            content.push(Entry::Content(entry));
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let french = Locale {
+            lang: String::from("fr"),
+            country: None,
+            encoding: None,
+            modifiers: None,
+        };
+        let file = DesktopFileBuilder::new()
+            .comment("Generated")
+            .group("Desktop Entry")
+            .key("Name", "App")
+            .key("Terminal", false)
+            .localized_key("Name", french, "Appli")
+            .key("Name", " Spaced\\App")
+            .list("Keywords", ["a;b", "c"])
+            .comment("End")
+            .build();
+
+        assert_eq!(
+            file.to_string(),
+            "# Generated
+[Desktop Entry]
+Name=\\sSpaced\\\\App
+Name[fr]=Appli
+Terminal=false
+Keywords=a\\;b;c;
+# End"
+        );
+        assert_eq!(
+            DesktopFile::try_from(format!("{file}\n").as_str()).unwrap(),
+            file
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let file = DesktopFileBuilder::new()
+            .banner(
+                Banner::new("generator 1.0")
+                    .without_timestamp()
+                    .with_notice(),
+            )
+            .group("Desktop Entry")
+            .key("Type", "Application")
+            .key("Exec", "app")
+            .annotate("Exec", "Started by the launcher")
+            .annotate("Icon", "Themed icon")
+            .key("Icon", "app")
+            .build();
+
+        assert_eq!(
+            file.to_string(),
+            "# @generated by generator 1.0
+# Do not edit, this file is generated and will be overwritten
+[Desktop Entry]
+Type=Application
+# Started by the launcher
+Exec=app
+# Themed icon
+Icon=app"
+        );
+
+        let mut file = DesktopFile::try_from("# Generated by hand\n[Desktop Entry]\n").unwrap();
+        file.stamp(&Banner::new("generator 1.0").without_timestamp());
+        file.stamp(&Banner::new("generator 2.0").without_timestamp());
+        assert_eq!(
+            file.to_string(),
+            "# @generated by generator 2.0\n# Generated by hand\n[Desktop Entry]\n"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+}
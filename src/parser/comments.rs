@@ -0,0 +1,148 @@
+use crate::parser::models::{CommentEntry, DesktopFile, Entry, Group};
+use alloc::{vec, vec::Vec};
+
+impl Group {
+    /// Attaches the comment lines right before each entry to it, see [DesktopFile::attach_comments].
+    pub fn attach_comments(&mut self) {
+        let mut content = Vec::with_capacity(self.content.len());
+        let mut pending = vec![];
+        for entry in self.content.drain(..) {
+            match entry {
+                Entry::Comment(CommentEntry::Text(comment)) => pending.push(comment),
+                Entry::Content(mut entry) => {
+                    pending.append(&mut entry.format.doc_comments);
+                    entry.format.doc_comments = core::mem::take(&mut pending);
+                    content.push(Entry::Content(entry));
+                }
+                other => {
+                    content.extend(
+                        pending
+                            .drain(..)
+                            .map(|c| Entry::Comment(CommentEntry::Text(c))),
+                    );
+                    content.push(other);
+                }
+            }
+        }
+        content.extend(
+            pending
+                .into_iter()
+                .map(|c| Entry::Comment(CommentEntry::Text(c))),
+        );
+        self.content = content;
+    }
+
+    /// Detaches the comments of the entries, putting them back as [Entry::Comment] right before the entries.
+    pub fn detach_comments(&mut self) {
+        let mut content = Vec::with_capacity(self.content.len());
+        for entry in self.content.drain(..) {
+            if let Entry::Content(mut entry) = entry {
+                let comments = core::mem::take(&mut entry.format.doc_comments);
+                content.extend(
+                    comments
+                        .into_iter()
+                        .map(|c| Entry::Comment(CommentEntry::Text(c))),
+                );
+                content.push(Entry::Content(entry));
+            } else {
+                content.push(entry);
+            }
+        }
+        self.content = content;
+    }
+}
+
+impl DesktopFile {
+    /// Attaches the comment lines right before each entry to it, as documentation of the entry.
+    ///
+    /// The attached comments are returned by [ContentEntry::doc_comments](crate::parser::models::ContentEntry::doc_comments)
+    /// and written right before the entry, so they follow it when it is moved, merged into another file or sorted by
+    /// [DesktopFile::normalize]. Comments separated from the next entry by a blank line, and the ones at the end of a group,
+    /// are not attached. The file is written the same way.
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let mut file = DesktopFile::try_from("[Desktop Entry]\n# Shown in menus\nName=App\n").unwrap();
+    /// file.attach_comments();
+    ///
+    /// let name = &file["Desktop Entry"]["Name"];
+    /// assert_eq!(name.doc_comments(), ["Shown in menus"]);
+    /// assert_eq!(name.to_string(), "# Shown in menus\nName=App");
+    /// ```
+    pub fn attach_comments(&mut self) {
+        self.groups_mut().for_each(Group::attach_comments);
+    }
+
+    /// Detaches the comments of the entries, see [Group::detach_comments].
+    pub fn detach_comments(&mut self) {
+        self.groups_mut().for_each(Group::detach_comments);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::parser::{FormatOptions, ParserOptions, models::EntrySet};
+
+    use super::*;
+
+    const FILE: &str = "# Header
+[Desktop Entry]
+# Command
+# with arguments
+Exec=app
+
+# Name
+Name=App
+# Trailing
+[Desktop Action new]
+Exec=app --new";
+
+    #[test]
+    fn test_attach_comments() {
+        let mut file = DesktopFile::try_from(FILE).unwrap();
+        file.attach_comments();
+
+        let group = file.find("Desktop Entry").unwrap();
+        assert_eq!(
+            group.find("Exec").unwrap().doc_comments(),
+            ["Command", "with arguments"]
+        );
+        assert_eq!(group.find("Name").unwrap().doc_comments(), ["Name"]);
+        assert_eq!(group.only_comments().len(), 2);
+        assert_eq!(file.to_string(), FILE);
+
+        let attached = DesktopFile::parse_with_options(
+            FILE.as_bytes(),
+            &ParserOptions::new().attach_comments(),
+        )
+        .unwrap();
+        assert_eq!(attached.to_string(), FILE);
+        assert_eq!(
+            attached
+                .find("Desktop Entry")
+                .unwrap()
+                .only_comments()
+                .len(),
+            2
+        );
+
+        file.normalize(&FormatOptions::new());
+        assert_eq!(
+            file.to_string(),
+            "# Header\n[Desktop Entry]\n# Name\nName=App\n# Command\n# with arguments\nExec=app\n# Trailing\n\n[Desktop Action new]\nExec=app --new"
+        );
+
+        file.detach_comments();
+        assert!(
+            file.find("Desktop Entry")
+                .unwrap()
+                .find("Name")
+                .unwrap()
+                .doc_comments()
+                .is_empty()
+        );
+        assert_eq!(file.find("Desktop Entry").unwrap().only_comments().len(), 5);
+    }
+}
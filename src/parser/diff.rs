@@ -0,0 +1,224 @@
+use alloc::{borrow::ToOwned, format, string::String, vec, vec::Vec};
+use core::fmt::Display;
+
+use crate::parser::models::{ContentEntry, DesktopFile, EntrySet, Group, Locale};
+
+/// A difference between two files, see [DesktopFile::diff].
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Change {
+    /// The group is only in the other file.
+    GroupAdded(String),
+    /// The group is only in the original file.
+    GroupRemoved(String),
+    /// The key is only in the group of the other file.
+    KeyAdded {
+        /// The header of the group.
+        group: String,
+        /// The key.
+        key: String,
+        /// The locale of the key.
+        locale: Option<Locale>,
+        /// The values of the key in the other file.
+        values: Vec<String>,
+    },
+    /// The key is only in the group of the original file.
+    KeyRemoved {
+        /// The header of the group.
+        group: String,
+        /// The key.
+        key: String,
+        /// The locale of the key.
+        locale: Option<Locale>,
+        /// The values of the key in the original file.
+        values: Vec<String>,
+    },
+    /// The key has different values in the two files.
+    KeyModified {
+        /// The header of the group.
+        group: String,
+        /// The key.
+        key: String,
+        /// The locale of the key.
+        locale: Option<Locale>,
+        /// The values of the key in the original file.
+        old: Vec<String>,
+        /// The values of the key in the other file.
+        new: Vec<String>,
+    },
+}
+
+impl Display for Change {
+    /// Writes the change as a line of a patch, such as `~ [Desktop Entry] Name[fr]: Appli -> Application`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let key = |key: &str, locale: &Option<Locale>| match locale {
+            Some(locale) => format!("{key}[{locale}]"),
+            None => key.to_owned(),
+        };
+        match self {
+            Change::GroupAdded(group) => write!(f, "+ [{group}]"),
+            Change::GroupRemoved(group) => write!(f, "- [{group}]"),
+            Change::KeyAdded {
+                group,
+                key: k,
+                locale,
+                values,
+            } => write!(f, "+ [{group}] {}={}", key(k, locale), values.join(";")),
+            Change::KeyRemoved {
+                group,
+                key: k,
+                locale,
+                values,
+            } => write!(f, "- [{group}] {}={}", key(k, locale), values.join(";")),
+            Change::KeyModified {
+                group,
+                key: k,
+                locale,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ [{group}] {}: {} -> {}",
+                key(k, locale),
+                old.join(";"),
+                new.join(";")
+            ),
+        }
+    }
+}
+
+impl DesktopFile {
+    /// Returns the groups and keys added, removed or modified in the other file.
+    ///
+    /// Keys are compared with their locale, and only the first group with a header and the first entry
+    /// with a key and a locale are compared, as they are the ones which are found.
+    /// Comments and formatting are ignored.
+    /// The changes are ordered as the groups and keys of this file, followed by the added ones.
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let vendor = DesktopFile::try_from("[Desktop Entry]\nName=App\nExec=app\n").unwrap();
+    /// let patched = DesktopFile::try_from("[Desktop Entry]\nName=App\nExec=app --safe\n").unwrap();
+    /// let changes: Vec<String> = vendor.diff(&patched).iter().map(ToString::to_string).collect();
+    ///
+    /// assert_eq!(changes, ["~ [Desktop Entry] Exec: app -> app --safe"]);
+    /// ```
+    pub fn diff(&self, other: &DesktopFile) -> Vec<Change> {
+        let mut changes = vec![];
+        let mut headers: Vec<&str> = vec![];
+
+        for group in self.groups() {
+            if headers.contains(&group.header.as_str()) {
+                continue;
+            }
+            headers.push(&group.header);
+            match other.find(&group.header) {
+                Some(other_group) => diff_groups(group, other_group, &mut changes),
+                None => changes.push(Change::GroupRemoved(group.header.clone())),
+            }
+        }
+        for group in other.groups() {
+            if !headers.contains(&group.header.as_str()) {
+                headers.push(&group.header);
+                changes.push(Change::GroupAdded(group.header.clone()));
+            }
+        }
+
+        changes
+    }
+}
+
+/// Finds the first entry with the key and the locale of the entry.
+fn find_same<'a>(group: &'a Group, entry: &ContentEntry) -> Option<&'a ContentEntry> {
+    group
+        .entries()
+        .find(|e| e.key == entry.key && e.locale == entry.locale)
+}
+
+fn diff_groups(group: &Group, other: &Group, changes: &mut Vec<Change>) {
+    let is_first = |group: &Group, entry: &ContentEntry| {
+        find_same(group, entry).is_some_and(|first| core::ptr::eq(first, entry))
+    };
+
+    for entry in group.entries().filter(|e| is_first(group, e)) {
+        match find_same(other, entry) {
+            Some(other_entry) if other_entry.values == entry.values => {}
+            Some(other_entry) => changes.push(Change::KeyModified {
+                group: group.header.clone(),
+                key: entry.key.clone(),
+                locale: entry.locale.clone(),
+                old: entry.values.clone(),
+                new: other_entry.values.clone(),
+            }),
+            None => changes.push(Change::KeyRemoved {
+                group: group.header.clone(),
+                key: entry.key.clone(),
+                locale: entry.locale.clone(),
+                values: entry.values.clone(),
+            }),
+        }
+    }
+    for entry in other.entries().filter(|e| is_first(other, e)) {
+        if find_same(group, entry).is_none() {
+            changes.push(Change::KeyAdded {
+                group: group.header.clone(),
+                key: entry.key.clone(),
+                locale: entry.locale.clone(),
+                values: entry.values.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let vendor = DesktopFile::try_from(
+            "[Desktop Entry]
+Name=App
+Name[fr]=Appli
+Exec=app
+Exec=ignored duplicate
+Terminal=false
+
+[Desktop Action old]
+Exec=app --old
+",
+        )
+        .unwrap();
+        let patched = DesktopFile::try_from(
+            "# Patched
+[Desktop Entry]
+Name = App
+Name[fr]=Application
+Exec=app
+NoDisplay=true
+
+[Desktop Action new]
+Exec=app --new
+",
+        )
+        .unwrap();
+
+        let changes: Vec<String> = vendor
+            .diff(&patched)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "~ [Desktop Entry] Name[fr]: Appli -> Application",
+                "- [Desktop Entry] Terminal=false",
+                "+ [Desktop Entry] NoDisplay=true",
+                "- [Desktop Action old]",
+                "+ [Desktop Action new]",
+            ]
+        );
+        assert!(vendor.diff(&vendor).is_empty());
+    }
+}
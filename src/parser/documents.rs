@@ -0,0 +1,166 @@
+use alloc::vec::Vec;
+use nom::error::Error;
+
+use crate::parser::models::DesktopFile;
+
+/// Defines where a document ends and the next one starts in a stream of concatenated documents.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum DocumentBoundary<'a> {
+    /// Documents are separated by a line equal to this delimiter. The delimiter is not part of any document.
+    Delimiter(&'a str),
+    /// A new document starts each time the header of the first group of the stream is found again.
+    ///
+    /// This suits streams of files of the same kind, such as `.desktop` files which all start with `[Desktop Entry]`.
+    LeadingGroup,
+}
+
+/// Iterator over the documents of a stream, created by [DesktopFile::parse_many].
+#[derive(Debug, Clone)]
+pub struct Documents<'a> {
+    input: &'a [u8],
+    boundary: DocumentBoundary<'a>,
+    leading_header: Option<&'a [u8]>,
+}
+
+impl<'a> Documents<'a> {
+    /// Splits the next document from the rest of the input.
+    fn split_next(&self) -> (&'a [u8], &'a [u8]) {
+        let mut position = 0;
+        let mut in_group = false;
+
+        for line in self.input.split_inclusive(|b| *b == b'\n') {
+            let line_content = line.trim_ascii_end();
+            let is_boundary = match (&self.boundary, self.leading_header) {
+                (DocumentBoundary::Delimiter(delimiter), _) => {
+                    if line_content == delimiter.as_bytes() {
+                        return (
+                            &self.input[..position],
+                            &self.input[position + line.len()..],
+                        );
+                    }
+                    false
+                }
+                (DocumentBoundary::LeadingGroup, Some(header)) => line_content == header,
+                (DocumentBoundary::LeadingGroup, None) => false,
+            };
+            if is_boundary {
+                if in_group {
+                    return self.input.split_at(position);
+                }
+                in_group = true;
+            }
+            position += line.len();
+        }
+
+        (self.input, &[])
+    }
+}
+
+impl Iterator for Documents<'_> {
+    type Item = Result<DesktopFile, nom::Err<Error<Vec<u8>>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.input.is_empty() {
+            let (document, rest) = self.split_next();
+            self.input = rest;
+
+            match DesktopFile::try_from(document) {
+                Ok(file) if file.content.is_empty() => continue,
+                result => return Some(result),
+            }
+        }
+        None
+    }
+}
+
+impl DesktopFile {
+    /// Lazily parses a stream of concatenated documents, one document at a time.
+    ///
+    /// Documents without any entry (e.g. after a trailing delimiter) are skipped.
+    /// A document that cannot be parsed yields an error, but does not stop the iteration.
+    pub fn parse_many<'a>(input: &'a [u8], boundary: &DocumentBoundary<'a>) -> Documents<'a> {
+        let leading_header = input
+            .split(|b| *b == b'\n')
+            .map(|line| line.trim_ascii_end())
+            .find(|line| line.starts_with(b"["));
+
+        Documents {
+            input,
+            boundary: boundary.clone(),
+            leading_header,
+        }
+    }
+
+    /// Parses a stream of concatenated documents.
+    ///
+    /// Documents without any entry (e.g. after a trailing delimiter) are ignored.
+    /// See [Self::parse_many] to parse documents one at a time.
+    pub fn parse_all(
+        input: &[u8],
+        boundary: &DocumentBoundary,
+    ) -> Result<Vec<DesktopFile>, nom::Err<Error<Vec<u8>>>> {
+        Self::parse_many(input, boundary).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+
+    use crate::parser::models::EntrySet;
+
+    use super::*;
+
+    const FIRST: &str = "[Desktop Entry]
+Name=First
+";
+    const SECOND: &str = "[Desktop Entry]
+Name=Second
+[Desktop Action new]
+Exec=second --new
+";
+
+    #[test]
+    fn test_parse_with_delimiter() {
+        let stream = format!("{FIRST}---\n{SECOND}---\n");
+        let files =
+            DesktopFile::parse_all(stream.as_bytes(), &DocumentBoundary::Delimiter("---")).unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                DesktopFile::try_from(FIRST).unwrap(),
+                DesktopFile::try_from(SECOND).unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_with_leading_group() {
+        let stream = format!("# Stream\n{FIRST}{SECOND}");
+        let files =
+            DesktopFile::parse_all(stream.as_bytes(), &DocumentBoundary::LeadingGroup).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].only_comments().len(), 1);
+        assert_eq!(files[1], DesktopFile::try_from(SECOND).unwrap());
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let stream = format!("{FIRST}---\n[Desktop Entry]\nName[fr_]=Invalide\n---\n{SECOND}");
+        let mut files =
+            DesktopFile::parse_many(stream.as_bytes(), &DocumentBoundary::Delimiter("---"));
+
+        assert_eq!(
+            files.next(),
+            Some(Ok(DesktopFile::try_from(FIRST).unwrap()))
+        );
+        assert!(files.next().unwrap().is_err());
+        assert_eq!(
+            files.next(),
+            Some(Ok(DesktopFile::try_from(SECOND).unwrap()))
+        );
+        assert_eq!(files.next(), None);
+    }
+}
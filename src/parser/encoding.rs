@@ -0,0 +1,63 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+use encoding_rs::{Encoding, WINDOWS_1252};
+
+/// Transcodes to UTF-8 the lines of the input that are not valid UTF-8.
+///
+/// Files written before the specification required UTF-8 declare `Encoding=Legacy-Mixed`,
+/// in which each localized value is written in the encoding of its locale, e.g. `Name[ru_RU.KOI8-R]`.
+/// Lines without a known encoding in their locale are considered Latin-1.
+pub(super) fn transcode_legacy(input: &[u8]) -> Cow<'_, [u8]> {
+    if str::from_utf8(input).is_ok() {
+        return Cow::Borrowed(input);
+    }
+
+    let mut transcoded = Vec::with_capacity(input.len());
+    for line in input.split_inclusive(|b| *b == b'\n') {
+        if str::from_utf8(line).is_ok() {
+            transcoded.extend_from_slice(line);
+        } else {
+            let encoding = locale_encoding(line).unwrap_or(WINDOWS_1252);
+            let (decoded, _) = encoding.decode_without_bom_handling(line);
+            transcoded.extend_from_slice(decoded.as_bytes());
+        }
+    }
+
+    Cow::Owned(transcoded)
+}
+
+/// Finds the encoding of the locale of the key, as in `Name[lang_COUNTRY.ENCODING@MODIFIER]`.
+fn locale_encoding(line: &[u8]) -> Option<&'static Encoding> {
+    let key = &line[..memchr::memchr(b'=', line)?];
+    let locale = key.get(memchr::memchr(b'[', key)? + 1..memchr::memchr(b']', key)?)?;
+    let label = &locale[memchr::memchr(b'.', locale)? + 1..];
+    let label = match memchr::memchr(b'@', label) {
+        Some(end) => &label[..end],
+        None => label,
+    };
+
+    Encoding::for_label(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::models::{DesktopFile, EntrySet};
+
+    #[test]
+    fn test_legacy_encodings() {
+        let mut file = b"[Desktop Entry]\nEncoding=Legacy-Mixed\nComment=Caf\xE9\n".to_vec();
+        file.extend_from_slice(b"Name[ru_RU.KOI8-R]=\xF0\xD2\xC9\xCC\xCF\xD6\xC5\xCE\xC9\xC5\n");
+
+        let parsed = DesktopFile::try_from(file.as_slice()).unwrap();
+        let group = parsed.get("Desktop Entry").unwrap();
+
+        assert_eq!(group.get("Comment").unwrap().values, vec!["Café"]);
+        assert_eq!(group.get("Name").unwrap().values, vec!["Приложение"]);
+    }
+
+    #[test]
+    fn test_misplaced_brackets() {
+        assert_eq!(super::locale_encoding(b"K]x[y=\xff\n"), None);
+        assert!(DesktopFile::try_from(b"[A]\nK]x[y=\xff\n".as_slice()).is_ok());
+    }
+}
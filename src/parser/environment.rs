@@ -0,0 +1,117 @@
+use crate::parser::models::Locale;
+
+/// The variables defining the locale of messages, by order of precedence.
+const LOCALE_VARIABLES: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Returns the locale of messages defined by the variables, ignoring the `C` and `POSIX` locales which are not translations.
+fn locale_from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Locale> {
+    let value = LOCALE_VARIABLES
+        .into_iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()))?;
+    match value.as_str() {
+        "C" | "POSIX" => None,
+        value if value.starts_with("C.") => None,
+        value => value.parse().ok(),
+    }
+}
+
+/// Returns the locales of messages defined by the variables, by order of preference, see [Locale::preferred_from_env].
+fn preferred_from_vars(var: impl Fn(&str) -> Option<String>) -> Vec<Locale> {
+    let Some(locale) = locale_from_vars(&var) else {
+        return Vec::new();
+    };
+    let mut preferred = Locale::parse_list(&var("LANGUAGE").unwrap_or_default());
+    preferred.push(locale);
+
+    let mut distinct: Vec<Locale> = Vec::with_capacity(preferred.len());
+    for locale in preferred {
+        if !distinct.contains(&locale) {
+            distinct.push(locale);
+        }
+    }
+    distinct
+}
+
+impl Locale {
+    /// Returns the locale of messages of the current process, from the first variable set among `LC_ALL`, `LC_MESSAGES` and `LANG`.
+    ///
+    /// Returns `None` if none is set, if the locale is `C` or `POSIX`, which are not translations, or if it is malformed.
+    /// ```no_run
+    /// use freedesktop_rs::parser::models::{DesktopFile, EntrySet, Locale};
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\nName[fr]=Appli\n").unwrap();
+    /// let group = &file["Desktop Entry"];
+    /// let name = match Locale::from_env() {
+    ///     Some(locale) => group.find_localized("Name", &locale),
+    ///     None => group.find("Name"),
+    /// };
+    /// ```
+    pub fn from_env() -> Option<Locale> {
+        locale_from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Returns the locales of messages of the current process, by order of preference.
+    ///
+    /// The locales of the colon-separated `LANGUAGE` variable come first, followed by the one of [Self::from_env].
+    /// As with gettext, `LANGUAGE` is ignored if there is no locale or if it is `C`, in which case the list is empty.
+    /// Malformed locales and duplicates are left out. The list can be given to [Group::find_preferred](crate::parser::models::Group::find_preferred).
+    pub fn preferred_from_env() -> Vec<Locale> {
+        preferred_from_vars(|name| std::env::var(name).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = pairs
+            .iter()
+            .map(|(name, value)| (String::from(*name), String::from(*value)))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_locale_from_vars() {
+        let locale = |pairs: &[(&str, &str)]| locale_from_vars(vars(pairs)).map(|l| l.to_string());
+
+        assert_eq!(
+            locale(&[("LANG", "fr_FR.UTF-8")]),
+            Some(String::from("fr_FR.UTF-8"))
+        );
+        assert_eq!(
+            locale(&[
+                ("LANG", "fr_FR.UTF-8"),
+                ("LC_MESSAGES", "de_DE"),
+                ("LC_ALL", "")
+            ]),
+            Some(String::from("de_DE"))
+        );
+        assert_eq!(locale(&[("LANG", "fr_FR"), ("LC_ALL", "C.UTF-8")]), None);
+        assert_eq!(locale(&[("LANG", "POSIX")]), None);
+        assert_eq!(locale(&[]), None);
+    }
+
+    #[test]
+    fn test_preferred_from_vars() {
+        let preferred = |pairs: &[(&str, &str)]| {
+            preferred_from_vars(vars(pairs))
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            preferred(&[("LANGUAGE", "sr@latin:de::fr_"), ("LANG", "de")]),
+            ["sr@latin", "de"]
+        );
+        assert_eq!(
+            preferred(&[("LANGUAGE", "de"), ("LANG", "fr_FR")]),
+            ["de", "fr_FR"]
+        );
+        assert!(preferred(&[("LANGUAGE", "de"), ("LANG", "C")]).is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::parser::models::{
+    CommentEntry, ContentEntry, DesktopFile, Entry, Group, Locale, TopLevelEntry,
+};
+
+/// The prefix of the keys and groups added by implementations, outside of the specification.
+const EXTENSION_PREFIX: &str = "X-";
+
+impl Group {
+    /// Keeps only the key-value entries for which the predicate returns `true`.
+    ///
+    /// Comments and invalid lines are kept.
+    pub fn retain_entries<F: FnMut(&ContentEntry) -> bool>(&mut self, mut predicate: F) {
+        self.content.retain(|entry| match entry {
+            Entry::Content(content) => predicate(content),
+            Entry::Comment(_) | Entry::Invalid(_) => true,
+        });
+    }
+}
+
+impl DesktopFile {
+    /// Keeps only the groups for which the predicate returns `true`.
+    ///
+    /// The comments outside of the groups are kept.
+    pub fn retain_groups<F: FnMut(&Group) -> bool>(&mut self, mut predicate: F) {
+        self.content.retain(|entry| match entry {
+            TopLevelEntry::Group(group) => predicate(group),
+            TopLevelEntry::Comment(_) => true,
+        });
+    }
+
+    /// Removes the comments of the file, inside and outside of the groups, including the ones attached to entries.
+    ///
+    /// Blank lines are kept, as they separate the groups.
+    pub fn strip_comments(&mut self) {
+        let is_text = |comment: &CommentEntry| matches!(comment, CommentEntry::Text(_));
+        self.content.retain(|entry| match entry {
+            TopLevelEntry::Comment(comment) => !is_text(comment),
+            TopLevelEntry::Group(_) => true,
+        });
+        for group in self.groups_mut() {
+            group.content.retain(|entry| match entry {
+                Entry::Comment(comment) => !is_text(comment),
+                Entry::Content(_) | Entry::Invalid(_) => true,
+            });
+            for entry in group.entries_mut() {
+                entry.format.doc_comments.clear();
+            }
+        }
+    }
+
+    /// Removes the extension groups and keys, whose names start with `X-`, such as `[X-Vendor]` or `X-GNOME-Autostart-enabled`.
+    pub fn strip_extension_keys(&mut self) {
+        self.retain_groups(|group| !group.header.starts_with(EXTENSION_PREFIX));
+        for group in self.groups_mut() {
+            group.retain_entries(|entry| !entry.key.starts_with(EXTENSION_PREFIX));
+        }
+    }
+
+    /// Returns a copy of the file keeping the default values and only the translations used by the locales.
+    ///
+    /// As when looking up a value, a translation is used by a locale if it has its language,
+    /// and either no country or its country, and either no modifier or its modifier. The encoding is ignored.
+    /// So `fr_FR` keeps `Name[fr_FR]` and `Name[fr]`, but `fr` does not keep `Name[fr_CA]`.
+    /// ```
+    /// use freedesktop_rs::parser::models::{DesktopFile, Locale};
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\nName[de]=Anw\nName[fr]=Appli\n").unwrap();
+    /// let locale: Locale = "fr_FR".parse().unwrap();
+    ///
+    /// assert_eq!(file.with_locales(&[locale]).to_string(), "[Desktop Entry]\nName=App\nName[fr]=Appli");
+    /// ```
+    pub fn with_locales(&self, locales: &[Locale]) -> DesktopFile {
+        let used = |translation: &Locale| {
+            locales.iter().any(|locale| {
+                translation.lang == locale.lang
+                    && translation
+                        .country
+                        .as_ref()
+                        .is_none_or(|c| Some(c) == locale.country.as_ref())
+                    && translation
+                        .modifiers
+                        .as_ref()
+                        .is_none_or(|m| Some(m) == locale.modifiers.as_ref())
+            })
+        };
+
+        let mut file = self.clone();
+        for group in file.groups_mut() {
+            group.retain_entries(|entry| entry.locale.as_ref().is_none_or(used));
+        }
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::*;
+
+    const FILE: &str = "# Header
+[Desktop Entry]
+# Name
+Name=App
+X-Vendor-Id=1
+Exec=app
+
+[X-Vendor Settings]
+Key=1
+
+[Desktop Action new]
+Exec=app --new";
+
+    #[test]
+    fn test_strip() {
+        let mut file = DesktopFile::try_from(FILE).unwrap();
+        file.strip_comments();
+        file.strip_extension_keys();
+
+        assert_eq!(
+            file.to_string(),
+            "[Desktop Entry]\nName=App\nExec=app\n\n[Desktop Action new]\nExec=app --new"
+        );
+    }
+
+    #[test]
+    fn test_with_locales() {
+        let file = DesktopFile::try_from(
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\nName[fr_CA]=Appli CA\nName[de@formal]=Anw\nName[de]=Anw\nName[sr@latin]=Apl",
+        )
+        .unwrap();
+        let stripped = |locales: &[&str]| {
+            let locales: Vec<Locale> = locales.iter().map(|l| l.parse().unwrap()).collect();
+            file.with_locales(&locales).to_string()
+        };
+
+        assert_eq!(stripped(&[]), "[Desktop Entry]\nName=App");
+        assert_eq!(
+            stripped(&["fr", "de_DE@formal"]),
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\nName[de@formal]=Anw\nName[de]=Anw"
+        );
+        assert_eq!(
+            stripped(&["fr_CA.UTF-8", "sr"]),
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\nName[fr_CA]=Appli CA"
+        );
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut file = DesktopFile::try_from(FILE).unwrap();
+        file.retain_groups(|group| group.header.starts_with("Desktop "));
+        for group in file.groups_mut() {
+            group.retain_entries(|entry| entry.key == "Exec");
+        }
+
+        assert_eq!(
+            file.to_string(),
+            "# Header\n[Desktop Entry]\n# Name\nExec=app\n\n[Desktop Action new]\nExec=app --new"
+        );
+    }
+}
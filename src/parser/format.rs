@@ -0,0 +1,856 @@
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "std")]
+use crate::helpers::permissions::replace_file;
+
+use crate::{
+    error::Error,
+    helpers::desktop_entry::{Key, ValueType},
+    parser::Banner,
+    parser::models::{
+        CanBeComment, CommentEntry, ContentEntry, DesktopFile, Entry, Group, LineEnding,
+        TopLevelEntry,
+    },
+    parser::raw::is_key_character,
+};
+
+/// The extensions of the files formatted by default by [format_tree].
+const DEFAULT_EXTENSIONS: [&str; 3] = ["desktop", "directory", "trashinfo"];
+
+/// Options changing how files are formatted, see [DesktopFile::normalize], [DesktopFile::to_string_with] and [format_tree].
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Whether the keys of each group are sorted in the order of the specification, see [DesktopFile::normalize].
+    /// Defaults to `true`.
+    pub sort_keys: bool,
+
+    /// Whether [format_tree] only reports the files which are not formatted, without writing them.
+    /// Defaults to `false`.
+    pub check: bool,
+
+    /// The extensions of the files formatted by [format_tree], without the leading dot.
+    /// Defaults to `desktop`, `directory` and `trashinfo`.
+    pub extensions: Vec<String>,
+
+    /// The `=` between the keys and the values with its surrounding spaces, such as ` = `, see [DesktopFile::to_string_with].
+    /// Defaults to `None`, which keeps the one of each entry.
+    pub assignment: Option<String>,
+
+    /// Whether lists are followed by a separator, see [DesktopFile::to_string_with].
+    /// Defaults to `None`, which keeps the one of each entry.
+    pub trailing_separator: Option<bool>,
+
+    /// The number of blank lines between groups, see [DesktopFile::to_string_with].
+    /// Defaults to `None`, which keeps the ones of the file.
+    pub blank_lines_between_groups: Option<usize>,
+
+    /// Whether a blank line is added between the groups, and before the first one, when there is none,
+    /// as `GKeyFile` does, see [DesktopFile::to_string_with]. Defaults to `false`.
+    pub separate_groups: bool,
+
+    /// Whether a space is written after the `#` of comments, see [DesktopFile::to_string_with]. Defaults to `true`.
+    pub comment_space: bool,
+
+    /// The line ending of the written files, see [DesktopFile::to_string_with].
+    /// Defaults to `None`, which keeps the one detected when the file was parsed.
+    pub line_ending: Option<LineEnding>,
+
+    /// The banner stamped at the start of the written files, see [DesktopFile::stamp]. Defaults to `None`.
+    ///
+    /// [format_tree] rejects a banner with a timestamp when [Self::check] is set,
+    /// as every file would then be reported, the timestamp having changed.
+    pub banner: Option<Banner>,
+
+    /// What [DesktopFile::try_to_string_with] does with the line breaks and tabulations written as is in values.
+    /// Defaults to [ControlCharacters::Escape].
+    pub control_characters: ControlCharacters,
+}
+
+/// What to do with the control characters of values, which would corrupt the written file,
+/// see [DesktopFile::try_to_string_with].
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub enum ControlCharacters {
+    /// Line breaks and tabulations are written as their escape sequences.
+    #[default]
+    Escape,
+    /// The file is not written.
+    Reject,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            sort_keys: true,
+            check: false,
+            extensions: DEFAULT_EXTENSIONS.map(String::from).to_vec(),
+            assignment: None,
+            trailing_separator: None,
+            blank_lines_between_groups: None,
+            separate_groups: false,
+            comment_space: true,
+            line_ending: None,
+            banner: None,
+            control_characters: ControlCharacters::default(),
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates options writing files as GLib's `GKeyFile` does, for the files read with [ParserOptions::gkeyfile](crate::parser::ParserOptions::gkeyfile).
+    ///
+    /// The keys keep their order and are written with a bare `=`, the comments are written as they were read,
+    /// and a blank line is added before the groups which are not already preceded by one.
+    pub fn gkeyfile() -> Self {
+        Self {
+            assignment: Some(String::from("=")),
+            separate_groups: true,
+            ..Self::default()
+        }
+        .unsorted_keys()
+        .comments_without_space()
+    }
+
+    /// Keeps the keys in their original order.
+    pub fn unsorted_keys(mut self) -> Self {
+        self.sort_keys = false;
+        self
+    }
+
+    /// Only reports the files which are not formatted, as a pre-commit check would.
+    pub fn check(mut self) -> Self {
+        self.check = true;
+        self
+    }
+
+    /// Changes the extensions of the files formatted by [format_tree], such as `theme` for icon themes.
+    pub fn with_extensions<S: Into<String>>(
+        mut self,
+        extensions: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Writes the `=` between the keys and the values with these surrounding spaces, such as ` = `.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the assignment is not a `=` surrounded by spaces or tabulations, as the files could not be read back.
+    pub fn with_assignment(mut self, assignment: &str) -> Self {
+        assert!(
+            assignment.trim_matches([' ', '\t']) == "=",
+            "{assignment:?} is not a `=` surrounded by spaces"
+        );
+        self.assignment = Some(assignment.to_owned());
+        self
+    }
+
+    /// Writes a separator after lists if `trailing` is set, or removes it.
+    ///
+    /// The lists are the entries with several values, the ones already followed by a separator,
+    /// and the standard keys whose values are lists.
+    pub fn with_trailing_separator(mut self, trailing: bool) -> Self {
+        self.trailing_separator = Some(trailing);
+        self
+    }
+
+    /// Separates the groups with this number of blank lines.
+    pub fn with_blank_lines_between_groups(mut self, lines: usize) -> Self {
+        self.blank_lines_between_groups = Some(lines);
+        self
+    }
+
+    /// Adds a blank line before the groups which are not already preceded by one.
+    pub fn separate_groups(mut self) -> Self {
+        self.separate_groups = true;
+        self
+    }
+
+    /// Writes the files with this line ending, such as [LineEnding::CrLf] for files shared with Windows tools.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = Some(line_ending);
+        self
+    }
+
+    /// Stamps the banner at the start of the written files, replacing their previous banner.
+    pub fn with_banner(mut self, banner: Banner) -> Self {
+        self.banner = Some(banner);
+        self
+    }
+
+    /// Fails to write values containing line breaks or tabulations instead of escaping them.
+    pub fn reject_control_characters(mut self) -> Self {
+        self.control_characters = ControlCharacters::Reject;
+        self
+    }
+
+    /// Writes comments without a space after their `#`.
+    pub fn comments_without_space(mut self) -> Self {
+        self.comment_space = false;
+        self
+    }
+}
+
+impl DesktopFile {
+    /// Normalizes the layout of the file, so that equivalent files are written the same way.
+    ///
+    /// Entries are written with a bare `=` and their trimmed values, indentations and trailing spaces of comments
+    /// are removed, repeated blank lines are collapsed,
+    /// the groups are separated by a single blank line, and the blank lines at the start of the file
+    /// and of the groups are removed.
+    ///
+    /// Unless [FormatOptions::sort_keys] is unset, the keys of each group are sorted as `desktop-file-edit` writes them:
+    /// the standard keys in the order of the specification, then the other keys and finally the `X-` extension keys,
+    /// both alphabetically. The localized values of a key follow its default value, sorted by locale.
+    /// The comments before an entry are moved with it, the blank lines between entries are removed
+    /// as they would end up separating unrelated entries, and duplicate keys keep their order.
+    /// ```
+    /// use freedesktop_rs::parser::{FormatOptions, models::DesktopFile};
+    ///
+    /// let mut file = DesktopFile::try_from("[Desktop Entry]\n# Command\nExec=app\n\n\nName[fr]=Appli\nName = App\n").unwrap();
+    /// file.normalize(&FormatOptions::new());
+    ///
+    /// assert_eq!(file.to_string(), "[Desktop Entry]\nName=App\nName[fr]=Appli\n# Command\nExec=app");
+    /// ```
+    pub fn normalize(&mut self, options: &FormatOptions) {
+        if options.sort_keys {
+            self.groups_mut().for_each(sort_keys);
+        }
+
+        let group_count = self.groups().count();
+        let mut previous_blank = true;
+        self.content.retain_mut(|entry| match entry {
+            TopLevelEntry::Comment(comment) => keep_blank(comment, &mut previous_blank),
+            TopLevelEntry::Group(_) => {
+                previous_blank = false;
+                true
+            }
+        });
+
+        for (i, group) in self.groups_mut().enumerate() {
+            let mut previous_blank = true;
+            group.content.retain_mut(|entry| match entry {
+                Entry::Comment(comment) => keep_blank(comment, &mut previous_blank),
+                Entry::Content(content) => {
+                    previous_blank = false;
+                    content.format.raw_values = None;
+                    content.format.assignment = None;
+                    true
+                }
+                Entry::Invalid(_) => {
+                    previous_blank = false;
+                    true
+                }
+            });
+
+            if group.content.last().is_some_and(Entry::is_blank) {
+                group.content.pop();
+            }
+            if i + 1 < group_count {
+                group
+                    .content
+                    .push(Entry::Comment(CommentEntry::Blank(String::from("\n"))));
+            }
+        }
+    }
+}
+
+impl DesktopFile {
+    /// Formats the file as a code formatter would, keeping its comments and the order of its keys.
+    ///
+    /// The file is [normalized](Self::normalize) without sorting its keys: indentations and trailing spaces are removed,
+    /// entries are written with a bare `=`, blank lines are collapsed and the groups are separated by a single blank line.
+    /// The text ends with a line break, as it would be saved.
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\n  Name = App  \n#  Comment \n\n\n  Exec=app\n[Desktop Action new]\nExec=app --new\n").unwrap();
+    ///
+    /// assert_eq!(
+    ///     file.fmt(),
+    ///     "[Desktop Entry]\nName=App\n# Comment\n\nExec=app\n\n[Desktop Action new]\nExec=app --new\n"
+    /// );
+    /// ```
+    pub fn fmt(&self) -> String {
+        let mut file = self.clone();
+        file.normalize(&FormatOptions::new().unsorted_keys());
+        format!("{file}{}", file.format.line_ending.as_str())
+    }
+
+    /// Writes the file in the style of the options, which are not applied to the file itself.
+    ///
+    /// Only the [FormatOptions::assignment], [FormatOptions::trailing_separator], [FormatOptions::blank_lines_between_groups],
+    /// [FormatOptions::separate_groups], [FormatOptions::comment_space], [FormatOptions::line_ending] and [FormatOptions::banner] options are used, so the file can be written following the conventions
+    /// of a desktop environment. The default options write the file as [Display](core::fmt::Display) does.
+    /// ```
+    /// use freedesktop_rs::parser::{FormatOptions, models::DesktopFile};
+    ///
+    /// let file = DesktopFile::try_from("#Generated\n[Desktop Entry]\nName=App\nCategories=Utility\n[Desktop Action new]\nExec=app\n").unwrap();
+    /// let options = FormatOptions::new()
+    ///     .with_assignment(" = ")
+    ///     .with_trailing_separator(true)
+    ///     .with_blank_lines_between_groups(1)
+    ///     .comments_without_space();
+    ///
+    /// assert_eq!(
+    ///     file.to_string_with(&options),
+    ///     "#Generated\n[Desktop Entry]\nName = App\nCategories = Utility;\n\n[Desktop Action new]\nExec = app"
+    /// );
+    /// ```
+    pub fn to_string_with(&self, options: &FormatOptions) -> String {
+        let mut file = self.clone();
+        if let Some(line_ending) = options.line_ending {
+            file.format.line_ending = line_ending;
+        }
+        if let Some(banner) = &options.banner {
+            file.stamp(banner);
+        }
+        let group_count = file.groups().count();
+        for (i, group) in file.groups_mut().enumerate() {
+            if let Some(lines) = options
+                .blank_lines_between_groups
+                .filter(|_| i + 1 < group_count)
+            {
+                while group.content.last().is_some_and(Entry::is_blank) {
+                    group.content.pop();
+                }
+                if lines > 0 {
+                    group
+                        .content
+                        .push(Entry::Comment(CommentEntry::Blank("\n".repeat(lines))));
+                }
+            }
+            for entry in group.entries_mut() {
+                style_entry(entry, options);
+            }
+        }
+        if options.separate_groups {
+            separate_groups(&mut file);
+        }
+
+        let written = file.to_string();
+        if options.comment_space {
+            return written;
+        }
+        let mut styled = String::with_capacity(written.len());
+        for line in written.split_inclusive('\n') {
+            match line.strip_prefix("# ") {
+                Some(comment) => {
+                    styled.push('#');
+                    styled.push_str(comment);
+                }
+                None => styled.push_str(line),
+            }
+        }
+        styled
+    }
+}
+
+impl DesktopFile {
+    /// Similar to [Self::to_string_with], but checks the values before writing them.
+    ///
+    /// Line breaks and tabulations written as is in the values are escaped, unless [FormatOptions::control_characters]
+    /// is [ControlCharacters::Reject]. Fails with [Error::InvalidValue] on the first value containing a control character
+    /// which is rejected or cannot be escaped, or on the first comment, group header, key or invalid line which
+    /// could not be read back as written, such as a comment containing a line break.
+    /// ```
+    /// use freedesktop_rs::parser::{FormatOptions, models::{DesktopFile, EntrySet}};
+    ///
+    /// let mut file = DesktopFile::new();
+    /// let group = file.ensure_group("Desktop Entry");
+    /// group.set("Name", "App");
+    /// group.find_mut("Name").unwrap().values[0] = String::from("Two\tcolumns");
+    ///
+    /// assert_eq!(
+    ///     file.try_to_string_with(&FormatOptions::new()).unwrap(),
+    ///     "[Desktop Entry]\nName=Two\\tcolumns"
+    /// );
+    /// assert!(file.try_to_string_with(&FormatOptions::new().reject_control_characters()).is_err());
+    /// ```
+    pub fn try_to_string_with(&self, options: &FormatOptions) -> Result<String, Error> {
+        let mut file = self.clone();
+        for top_level_entry in &file.content {
+            match top_level_entry {
+                TopLevelEntry::Comment(comment) => check_comment(comment)?,
+                TopLevelEntry::Group(group) => check_line("the group header", &group.header, "[]")?,
+            }
+        }
+        for group in file.groups_mut() {
+            for entry in group.content.iter_mut() {
+                let entry = match entry {
+                    Entry::Content(entry) => entry,
+                    Entry::Comment(comment) => {
+                        check_comment(comment)?;
+                        continue;
+                    }
+                    Entry::Invalid(line) => {
+                        check_line("the invalid line", &String::from_utf8_lossy(line), "")?;
+                        continue;
+                    }
+                };
+                if let Some(c) = entry.key.chars().find(|c| !is_key_character(*c)) {
+                    return Err(Error::InvalidValue(format!(
+                        "the key {:?} of [{}] contains the character {c:?}",
+                        entry.key, group.header
+                    )));
+                }
+                for comment in &entry.format.doc_comments {
+                    check_line("the comment", comment, "")?;
+                }
+                for value in &mut entry.values {
+                    check_control_characters(&group.header, &entry.key, value, options)?;
+                    if !entry.format.escapes_decoded {
+                        // Line breaks are always escaped when written
+                        *value = value.replace('\t', "\\t");
+                    }
+                }
+            }
+        }
+        Ok(file.to_string_with(options))
+    }
+}
+
+/// Fails if the value contains a control character which is rejected by the options or cannot be escaped.
+fn check_control_characters(
+    header: &str,
+    key: &str,
+    value: &str,
+    options: &FormatOptions,
+) -> Result<(), Error> {
+    let escaped = |c: char| {
+        matches!(c, '\n' | '\r' | '\t') && options.control_characters == ControlCharacters::Escape
+    };
+    match value.chars().find(|c| c.is_control() && !escaped(*c)) {
+        Some(character) => Err(Error::InvalidValue(format!(
+            "{key} of [{header}] contains the control character {character:?}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Fails if the comment would not be read back as a single comment or blank lines.
+fn check_comment(comment: &CommentEntry) -> Result<(), Error> {
+    match comment {
+        CommentEntry::Text(text) => check_line("the comment", text, ""),
+        CommentEntry::Blank(lines) => match lines.chars().find(|c| !" \t\r\n".contains(*c)) {
+            Some(c) => Err(Error::InvalidValue(format!(
+                "the blank lines contain the character {c:?}"
+            ))),
+            None => Ok(()),
+        },
+    }
+}
+
+/// Fails if the text, written as is on a line, contains a control character other than a tabulation,
+/// which could start another line, or one of the forbidden characters.
+fn check_line(name: &str, text: &str, forbidden: &str) -> Result<(), Error> {
+    match text
+        .chars()
+        .find(|c| (c.is_control() && *c != '\t') || forbidden.contains(*c))
+    {
+        Some(c) => Err(Error::InvalidValue(format!(
+            "{name} {text:?} contains the character {c:?}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Applies the style of the options to the entry.
+/// Adds a blank line before the groups which are not preceded by one, except at the start of the file.
+fn separate_groups(file: &mut DesktopFile) {
+    let mut content = Vec::with_capacity(file.content.len());
+    let mut previous_blank = true;
+    for entry in file.content.drain(..) {
+        if matches!(entry, TopLevelEntry::Group(_)) && !previous_blank {
+            content.push(TopLevelEntry::Comment(CommentEntry::Blank(String::from(
+                "\n",
+            ))));
+        }
+        previous_blank = match &entry {
+            TopLevelEntry::Group(group) => group.content.last().is_some_and(Entry::is_blank),
+            comment => comment.is_blank(),
+        };
+        content.push(entry);
+    }
+    file.content = content;
+}
+
+fn style_entry(entry: &mut ContentEntry, options: &FormatOptions) {
+    if let Some(assignment) = &options.assignment {
+        entry.format.assignment = (assignment != "=").then(|| assignment.clone());
+    }
+    if let Some(trailing) = options.trailing_separator {
+        let list = entry.values.len() > 1
+            || entry.format.trailing_separator
+            || ValueType::of_key(&entry.key).is_some_and(|value_type| value_type.is_list());
+        entry.format.trailing_separator = trailing && list;
+    }
+}
+
+/// The position of an entry in a sorted group.
+fn sort_key(entry: &ContentEntry) -> (usize, &str, Option<String>) {
+    let rank = match Key::ALL.iter().position(|key| key.as_str() == entry.key) {
+        Some(rank) => rank,
+        None if entry.key.starts_with("X-") => Key::ALL.len() + 1,
+        None => Key::ALL.len(),
+    };
+    // Standard keys are already ordered by their rank
+    let name = if rank < Key::ALL.len() {
+        ""
+    } else {
+        &entry.key
+    };
+    (rank, name, entry.locale.as_ref().map(ToString::to_string))
+}
+
+/// Sorts the entries of the group, moving the comments and invalid lines with the following entry
+/// and removing the blank lines before it.
+///
+/// The lines after the last entry stay at the end of the group.
+fn sort_keys(group: &mut Group) {
+    let mut chunks: Vec<(ContentEntry, Vec<Entry>)> = vec![];
+    let mut pending = vec![];
+    for entry in group.content.drain(..) {
+        match entry {
+            Entry::Content(content) => {
+                pending.retain(|entry: &Entry| !entry.is_blank());
+                chunks.push((content, core::mem::take(&mut pending)));
+            }
+            other => pending.push(other),
+        }
+    }
+
+    chunks.sort_by(|(a, _), (b, _)| sort_key(a).cmp(&sort_key(b)));
+    for (content, attached) in chunks {
+        group.content.extend(attached);
+        group.content.push(Entry::Content(content));
+    }
+    group.content.extend(pending);
+}
+
+/// Whether the comment is kept, collapsing the blank lines into a single one after any other line.
+///
+/// The trailing spaces of comments are removed, and so are the spaces indenting the following line.
+fn keep_blank(comment: &mut CommentEntry, previous_blank: &mut bool) -> bool {
+    let lines = match comment {
+        CommentEntry::Text(text) => {
+            text.truncate(text.trim_end().len());
+            *previous_blank = false;
+            return true;
+        }
+        CommentEntry::Blank(lines) => lines,
+    };
+    if !lines.contains('\n') {
+        return false;
+    }
+    if *previous_blank {
+        return false;
+    }
+    *previous_blank = true;
+    *lines = String::from("\n");
+    true
+}
+
+/// Normalizes every file of the directory and its subdirectories with the extensions of the options,
+/// and writes them in the style of the options, see [DesktopFile::normalize] and [DesktopFile::to_string_with].
+///
+/// Returns the files which were not formatted, in order. They are rewritten unless [FormatOptions::check] is set,
+/// so that a pre-commit hook can fail if the list is not empty:
+/// ```no_run
+/// use freedesktop_rs::parser::{FormatOptions, format_tree};
+///
+/// let unformatted = format_tree("data/applications", &FormatOptions::new().check()).unwrap();
+/// for path in &unformatted {
+///     eprintln!("{} is not formatted", path.display());
+/// }
+/// ```
+///
+/// The files are parsed strictly, see [DesktopFile::parse_strict], so that a line which cannot be parsed is never
+/// dropped from a rewritten file. The files are replaced atomically, as by [DesktopFile::save],
+/// and the symbolic links to directories are not followed, so that each file is visited once.
+///
+/// Fails with [Error::InvalidValue] on the first file which cannot be parsed, before it or any following file is written,
+/// or if [FormatOptions::check] is set with a [banner](FormatOptions::banner) which has a timestamp.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub fn format_tree<P: AsRef<Path>>(dir: P, options: &FormatOptions) -> Result<Vec<PathBuf>, Error> {
+    if options.check && options.banner.as_ref().is_some_and(Banner::has_timestamp) {
+        return Err(Error::InvalidValue(String::from(
+            "a banner with a timestamp cannot be checked, as it changes each time it is written",
+        )));
+    }
+    let mut unformatted = vec![];
+    let mut dirs = vec![dir.as_ref().to_owned()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .map_err(Error::Io)?
+            .map(|entry| entry.and_then(|e| Ok((e.path(), e.file_type()?))))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::Io)?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (path, file_type) in entries {
+            if file_type.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let formatted = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| options.extensions.iter().any(|e| e == extension));
+            if !formatted {
+                continue;
+            }
+
+            let content = fs::read(&path).map_err(Error::Io)?;
+            let mut file = DesktopFile::parse_strict(&content).map_err(|error| {
+                Error::InvalidValue(format!("{} could not be parsed, {error}", path.display()))
+            })?;
+            file.normalize(options);
+            let line_ending = options.line_ending.unwrap_or(file.format.line_ending);
+            let normalized = format!("{}{}", file.to_string_with(options), line_ending.as_str());
+            if normalized.as_bytes() != content {
+                if !options.check {
+                    replace_file(&path, false, |writer| {
+                        writer.write_all(normalized.as_bytes())
+                    })?;
+                }
+                unformatted.push(path);
+            }
+        }
+    }
+
+    unformatted.sort();
+    Ok(unformatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::parser::models::EntrySet;
+
+    #[test]
+    fn test_to_string_with() {
+        let input = "# Header\n[A]\nKeys = a;b;\nName=App\n\n\n[B]\nMimeType=text/plain\n";
+        let file = DesktopFile::try_from(input).unwrap();
+
+        assert_eq!(file.to_string_with(&FormatOptions::new()), file.to_string());
+        assert_eq!(
+            file.to_string_with(
+                &FormatOptions::new()
+                    .with_assignment("=")
+                    .with_trailing_separator(false)
+                    .with_blank_lines_between_groups(0)
+            ),
+            "# Header\n[A]\nKeys=a;b\nName=App\n[B]\nMimeType=text/plain"
+        );
+        assert_eq!(
+            file.to_string_with(&FormatOptions::new().with_blank_lines_between_groups(2)),
+            "# Header\n[A]\nKeys = a;b;\nName=App\n\n\n[B]\nMimeType=text/plain"
+        );
+        assert_eq!(
+            file.to_string_with(&FormatOptions::new().with_trailing_separator(true)),
+            "# Header\n[A]\nKeys = a;b;\nName=App\n\n\n[B]\nMimeType=text/plain;"
+        );
+        let options = FormatOptions::new().with_banner(Banner::new("tool 2.0").without_timestamp());
+        assert_eq!(
+            file.to_string_with(&options),
+            input
+                .trim_end()
+                .replace("# Header", "# @generated by tool 2.0\n# Header")
+        );
+        assert_eq!(
+            file.to_string_with(&FormatOptions::new().with_line_ending(LineEnding::CrLf)),
+            input.trim_end().replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a `=` surrounded by spaces")]
+    fn test_invalid_assignment() {
+        FormatOptions::new().with_assignment(" : ");
+    }
+
+    #[test]
+    fn test_try_to_string_with() {
+        let mut file = DesktopFile::try_from("[A]\nName=App\nComment=Text\n").unwrap();
+        let options = FormatOptions::new();
+        let group = file.get_mut("A").unwrap();
+        group.find_mut("Name").unwrap().values[0] = String::from("Two\nlines");
+        group.find_mut("Comment").unwrap().format.escapes_decoded = true;
+        group.find_mut("Comment").unwrap().values[0] = String::from("\tIndented");
+
+        assert_eq!(
+            file.try_to_string_with(&options).unwrap(),
+            "[A]\nName=Two\\nlines\nComment=\\tIndented"
+        );
+        assert!(matches!(
+            file.try_to_string_with(&options.clone().reject_control_characters()),
+            Err(Error::InvalidValue(message)) if message == "Name of [A] contains the control character '\\n'"
+        ));
+
+        file.get_mut("A").unwrap().find_mut("Name").unwrap().values[0] = String::from("Bell\u{7}");
+        assert!(file.try_to_string_with(&options).is_err());
+        let name = &mut file.get_mut("A").unwrap().find_mut("Name").unwrap().values[0];
+        *name = String::from("Tab\tthen bell\u{7}");
+        assert!(file.try_to_string_with(&options).is_err());
+        *file.get_mut("A").unwrap().find_mut("Name").unwrap() =
+            ContentEntry::new("Name", "App").unwrap();
+        assert!(file.try_to_string_with(&options).is_ok());
+
+        let injected = [
+            Entry::Comment(CommentEntry::Text(String::from("x\nExec=evil"))),
+            Entry::Comment(CommentEntry::Blank(String::from("\nExec=evil\n"))),
+            Entry::Invalid(b"x\rExec=evil".to_vec()),
+        ];
+        for entry in injected {
+            let mut injected = file.clone();
+            injected.get_mut("A").unwrap().content.push(entry);
+            assert!(injected.try_to_string_with(&options).is_err());
+        }
+        let mut injected = file.clone();
+        let name = injected.get_mut("A").unwrap().find_mut("Name").unwrap();
+        name.format.doc_comments.push(String::from("x\nExec=evil"));
+        assert!(injected.try_to_string_with(&options).is_err());
+        let mut injected = file.clone();
+        injected.get_mut("A").unwrap().find_mut("Name").unwrap().key =
+            String::from("Exec=evil\nName");
+        assert!(injected.try_to_string_with(&options).is_err());
+        let mut injected = file.clone();
+        injected.get_mut("A").unwrap().header = String::from("A]\nExec=evil\n[A");
+        assert!(injected.try_to_string_with(&options).is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut file = DesktopFile::try_from(
+            "\n\n# Header\n\n\n[Desktop Entry]\n\nName = App\n# Comment\n\n\nExec=  app\n[Desktop Action new]\nExec=app --new\n\n\n",
+        )
+        .unwrap();
+        file.normalize(&FormatOptions::new());
+
+        let normalized = "# Header\n\n[Desktop Entry]\nName=App\n# Comment\nExec=app\n\n[Desktop Action new]\nExec=app --new";
+        assert_eq!(file.to_string(), normalized);
+        file.normalize(&FormatOptions::new());
+        assert_eq!(file.to_string(), normalized);
+
+        let mut file =
+            DesktopFile::try_from("[Desktop Entry]\nName=App\n# Comment\n\n\nExec=app\n").unwrap();
+        file.normalize(&FormatOptions::new().unsorted_keys());
+        assert_eq!(
+            file.to_string(),
+            "[Desktop Entry]\nName=App\n# Comment\n\nExec=app"
+        );
+    }
+
+    #[test]
+    fn test_fmt() {
+        let file = DesktopFile::try_from(
+            "\t# Header\t\n\n[Desktop Entry]\n\tName=App\n \t\n\tExec = app\n    # Indented\n\tKeywords=a;b;\n",
+        )
+        .unwrap();
+        let formatted =
+            "# Header\n\n[Desktop Entry]\nName=App\n\nExec=app\n# Indented\nKeywords=a;b;\n";
+
+        assert_eq!(file.fmt(), formatted);
+        assert_eq!(DesktopFile::try_from(formatted).unwrap().fmt(), formatted);
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let mut file = DesktopFile::try_from(
+            "[Desktop Entry]\nX-Vendor=1\n\n# Run\nExec=app\n\nName[fr]=Appli\nKeywords=app;\nName=App\nCustom=2\nName[de]=Anw\n# End\n\n[Desktop Action new]\nExec=app --new\nName=New",
+        )
+        .unwrap();
+        let unsorted = file.clone();
+        file.normalize(&FormatOptions::new());
+
+        let sorted = "[Desktop Entry]\nName=App\nName[de]=Anw\nName[fr]=Appli\n# Run\nExec=app\nKeywords=app;\nCustom=2\nX-Vendor=1\n# End\n\n[Desktop Action new]\nName=New\nExec=app --new";
+        assert_eq!(file.to_string(), sorted);
+        file.normalize(&FormatOptions::new());
+        assert_eq!(file.to_string(), sorted);
+
+        let mut file = unsorted.clone();
+        file.normalize(&FormatOptions::new().unsorted_keys());
+        assert_eq!(file.to_string(), unsorted.to_string());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_format_tree() {
+        let dir =
+            std::env::temp_dir().join(format!("freedesktop-rs-format-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let files = [
+            ("formatted.desktop", "[Desktop Entry]\nName=App\n"),
+            ("sub/spaced.desktop", "[Desktop Entry]\nName = App\n"),
+            ("ignored.txt", "[Group]\nKey = value\n"),
+        ];
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+
+        let check = format_tree(&dir, &FormatOptions::new().check()).unwrap();
+        assert_eq!(check, [dir.join("sub/spaced.desktop")]);
+        assert_eq!(
+            fs::read_to_string(dir.join("sub/spaced.desktop")).unwrap(),
+            "[Desktop Entry]\nName = App\n"
+        );
+
+        assert_eq!(format_tree(&dir, &FormatOptions::new()).unwrap(), check);
+        assert!(format_tree(&dir, &FormatOptions::new()).unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.join("sub/spaced.desktop")).unwrap(),
+            "[Desktop Entry]\nName=App\n"
+        );
+        let all = FormatOptions::new().check().with_extensions(["txt"]);
+        assert_eq!(format_tree(&dir, &all).unwrap(), [dir.join("ignored.txt")]);
+        let stamped = FormatOptions::new()
+            .check()
+            .with_banner(Banner::new("tool"));
+        assert!(matches!(
+            format_tree(&dir, &stamped),
+            Err(Error::InvalidValue(_))
+        ));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("..", dir.join("sub/parent")).unwrap();
+            assert!(format_tree(&dir, &FormatOptions::new()).unwrap().is_empty());
+        }
+
+        let invalid = "[Desktop Entry]\nName = App\nnot an entry\n";
+        fs::write(dir.join("invalid.desktop"), invalid).unwrap();
+        assert!(matches!(
+            format_tree(&dir, &FormatOptions::new()),
+            Err(Error::InvalidValue(_))
+        ));
+        assert_eq!(
+            fs::read_to_string(dir.join("invalid.desktop")).unwrap(),
+            invalid
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
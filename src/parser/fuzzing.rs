@@ -0,0 +1,158 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::parser::models::{
+    CommentEntry, ContentEntry, DesktopFile, Entry, EntryFormat, FileFormat, Group, Locale,
+    TopLevelEntry, escape_kept_value,
+};
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const KEY_CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-";
+const TEXT_CHARACTERS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789 -_.,:/()";
+const ENCODINGS: &[&str] = &["UTF-8", "ISO-8859-1", "ISO-8859-15"];
+
+/// Returns a word of the characters, whose length is in the range.
+fn word(u: &mut Unstructured, characters: &[u8], min: usize, max: usize) -> Result<String> {
+    let len = u.int_in_range(min..=max)?;
+    (0..len)
+        .map(|_| u.choose(characters).map(|c| char::from(*c)))
+        .collect()
+}
+
+/// Returns a non-empty text without leading nor trailing spaces, which are not kept when parsed.
+fn text(u: &mut Unstructured, max: usize) -> Result<String> {
+    let text = word(u, TEXT_CHARACTERS, 1, max)?;
+    match text.trim() {
+        "" => Ok(String::from("text")),
+        trimmed => Ok(trimmed.to_owned()),
+    }
+}
+
+/// Returns a value without control characters, which are not allowed by the specification, and which is not trimmed when parsed.
+fn value(u: &mut Unstructured) -> Result<String> {
+    let value: String = u.arbitrary::<String>()?.replace(char::is_control, "");
+    match value.trim() {
+        "" => Ok(String::from("value")),
+        trimmed => Ok(trimmed.to_owned()),
+    }
+}
+
+/// Generates locales which are valid as per the specification, such as `sr_RS.UTF-8@latin`.
+impl<'a> Arbitrary<'a> for Locale {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let lang = word(u, LOWERCASE, 2, 3)?;
+        let country = match u.arbitrary()? {
+            true => Some(word(u, UPPERCASE, 2, 2)?),
+            false => None,
+        };
+        let encoding = match u.arbitrary()? {
+            true => Some(u.choose(ENCODINGS)?.to_string()),
+            false => None,
+        };
+        let modifiers = match u.arbitrary()? {
+            true => Some(word(u, LOWERCASE, 3, 6)?),
+            false => None,
+        };
+        Ok(Self {
+            lang,
+            country,
+            encoding,
+            modifiers,
+        })
+    }
+}
+
+/// Generates entries with a valid key and escaped values, which are parsed back as the same entry.
+impl<'a> Arbitrary<'a> for ContentEntry {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let key = format!(
+            "{}{}",
+            word(u, UPPERCASE, 1, 1)?,
+            word(u, KEY_CHARACTERS, 0, 15)?
+        );
+        let locale = u.arbitrary()?;
+        let list: bool = u.arbitrary()?;
+        let count = match list {
+            true => u.int_in_range(1..=4)?,
+            false => 1,
+        };
+        // Semicolons are escaped even in single values, as values are always split on them when parsed
+        let values = (0..count)
+            .map(|_| value(u).map(|v| escape_kept_value(&v, Some(';'))))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            key,
+            values,
+            locale,
+            format: EntryFormat {
+                trailing_separator: list,
+                ..EntryFormat::default()
+            },
+        })
+    }
+}
+
+/// Generates groups of entries and comments, which are parsed back as the same group.
+impl<'a> Arbitrary<'a> for Group {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let header = text(u, 24)?;
+        let mut content = vec![];
+        for _ in 0..u.int_in_range(0..=8)? {
+            let entry = match u.ratio(1, 5)? {
+                true => Entry::Comment(CommentEntry::Text(text(u, 40)?)),
+                false => Entry::Content(u.arbitrary()?),
+            };
+            content.push(entry);
+        }
+        Ok(Self { header, content })
+    }
+}
+
+/// Generates files of at least one group and leading comments, which are written and parsed back as the same file.
+impl<'a> Arbitrary<'a> for DesktopFile {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut content = vec![];
+        for _ in 0..u.int_in_range(0..=2)? {
+            content.push(TopLevelEntry::Comment(CommentEntry::Text(text(u, 40)?)));
+        }
+        for _ in 0..u.int_in_range(1..=4)? {
+            content.push(TopLevelEntry::Group(u.arbitrary()?));
+        }
+        Ok(Self {
+            content,
+            format: FileFormat::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        // A linear congruential generator, so that the test is deterministic
+        let mut state: u64 = 0x2545f4914f6cdd1d;
+        let mut bytes = vec![0; 4096];
+        for _ in 0..200 {
+            for byte in &mut bytes {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                *byte = (state >> 56) as u8;
+            }
+
+            let file: DesktopFile = Unstructured::new(&bytes).arbitrary().unwrap();
+            // Written with a final line break, as files are saved
+            let mut written = file.to_string();
+            if !written.ends_with('\n') {
+                written.push('\n');
+            }
+            let parsed = DesktopFile::try_from(written.as_str()).unwrap();
+            assert_eq!(parsed, file, "{written}");
+            assert_eq!(parsed.to_string(), file.to_string());
+        }
+    }
+}
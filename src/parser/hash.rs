@@ -0,0 +1,122 @@
+use crate::parser::models::{ContentEntry, DesktopFile, EntrySet, Group};
+use alloc::{string::ToString, vec::Vec};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A FNV-1a hasher, whose output does not depend on the platform or the Rust version.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Writes the string followed by a separator, so that `("ab", "c")` and `("a", "bc")` differ.
+    fn write_str(&mut self, s: &str) {
+        self.write(s.as_bytes());
+        self.write(&[0xff]);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+}
+
+fn hash_entry(hasher: &mut StableHasher, entry: &ContentEntry) {
+    hasher.write_str(&entry.key);
+    match &entry.locale {
+        Some(locale) => hasher.write_str(&locale.to_string()),
+        None => hasher.write(&[0xfe]),
+    }
+    hasher.write_u64(entry.values.len() as u64);
+    for value in &entry.values {
+        hasher.write_str(value);
+    }
+}
+
+impl Group {
+    /// Returns a hash of the header and the key-values entries of the group.
+    ///
+    /// The hash ignores comments, blank lines and formatting, and is stable across platforms and versions
+    /// of this crate, so it can be stored to detect changes later on.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        hasher.write_str(&self.header);
+        for entry in self.without_comments() {
+            hash_entry(&mut hasher, entry);
+        }
+        hasher.0
+    }
+}
+
+impl DesktopFile {
+    /// Returns a hash of all the groups of the file, see [Group::content_hash].
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        for (_, hash) in self.group_hashes() {
+            hasher.write_u64(hash);
+        }
+        hasher.0
+    }
+
+    /// Returns the header and the hash of each group, in order.
+    pub fn group_hashes(&self) -> Vec<(&str, u64)> {
+        self.without_comments()
+            .into_iter()
+            .map(|group| (group.header.as_str(), group.content_hash()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "[Desktop Entry]
+Name=App
+Name[fr]=Appli
+[Desktop Action new]
+Exec=app --new
+";
+
+    #[test]
+    fn test_comment_insensitive_hash() {
+        let commented = "# Generated file
+[Desktop Entry]
+Name = App
+
+# French translation
+Name[fr]=Appli
+[Desktop Action new]
+Exec=app --new
+";
+        let file = DesktopFile::try_from(FILE).unwrap();
+
+        assert_eq!(
+            file.content_hash(),
+            DesktopFile::try_from(commented).unwrap().content_hash()
+        );
+        assert_eq!(file.content_hash(), 0xb83a95b130539aaa);
+    }
+
+    #[test]
+    fn test_changed_hash() {
+        let file = DesktopFile::try_from(FILE).unwrap();
+        let changed =
+            DesktopFile::try_from(FILE.replace("--new", "--new-window").as_str()).unwrap();
+        let hashes = file.group_hashes();
+        let changed_hashes = changed.group_hashes();
+
+        assert_ne!(file.content_hash(), changed.content_hash());
+        assert_eq!(hashes[0], changed_hashes[0]);
+        assert_ne!(hashes[1], changed_hashes[1]);
+    }
+}
@@ -0,0 +1,218 @@
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use nom::error::Error;
+
+use crate::parser::models::{DesktopFile, TopLevelEntry};
+
+use super::{ParserOptions, detect_line_ending, normalize_lines, parse_entries};
+
+/// A replacement of a range of bytes of a source.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Edit<'a> {
+    /// The replaced bytes of the source.
+    pub range: Range<usize>,
+    /// The bytes replacing the range.
+    pub replacement: &'a [u8],
+}
+
+impl Edit<'_> {
+    /// Returns the source after the edit.
+    pub fn apply(&self, source: &[u8]) -> Vec<u8> {
+        let mut new_source = source.to_vec();
+        new_source.splice(self.range.clone(), self.replacement.iter().copied());
+        new_source
+    }
+}
+
+impl DesktopFile {
+    /// Parses the source after the edit, reusing this file which must be the result of parsing `old_source`.
+    ///
+    /// Only the groups touched by the edit are parsed again. If this file does not match `old_source`,
+    /// or if the edited groups cannot be parsed, the whole new source is parsed instead.
+    pub fn reparse(
+        &self,
+        old_source: &[u8],
+        edit: &Edit,
+        options: &ParserOptions,
+    ) -> Result<Self, nom::Err<Error<Vec<u8>>>> {
+        let new_source = edit.apply(old_source);
+
+        match self.reparse_regions(old_source, &new_source, edit, options) {
+            Some(file) => Ok(file),
+            None => Self::parse_with_options(&new_source, options),
+        }
+    }
+
+    fn reparse_regions(
+        &self,
+        old_source: &[u8],
+        new_source: &[u8],
+        edit: &Edit,
+        options: &ParserOptions,
+    ) -> Option<Self> {
+        if edit.range.start > edit.range.end || edit.range.end > old_source.len() {
+            return None;
+        }
+        // The byte order mark shifts the first line, and the first line ending is the one of the whole file
+        if self.format.byte_order_mark || detect_line_ending(new_source) != self.format.line_ending
+        {
+            return None;
+        }
+
+        // Regions are the entries before the first group, then each group
+        let group_starts = group_starts(old_source);
+        let leading_entries = self
+            .content
+            .iter()
+            .position(|tle| matches!(tle, TopLevelEntry::Group(_)))
+            .unwrap_or(self.content.len());
+        if self.content.len() - leading_entries != group_starts.len() {
+            return None;
+        }
+
+        let region_of = |position: usize| group_starts.iter().filter(|s| **s <= position).count();
+        let region_start = |region: usize| match region {
+            0 => 0,
+            _ => group_starts[region - 1],
+        };
+        let region_end = |region: usize| {
+            group_starts
+                .get(region)
+                .copied()
+                .unwrap_or(old_source.len())
+        };
+        let entry_start = |region: usize| match region {
+            0 => 0,
+            _ => leading_entries + region - 1,
+        };
+
+        // An edit touching the start of a group may change where the previous one ends
+        let first = region_of(edit.range.start.saturating_sub(1));
+        let last = region_of(edit.range.end);
+
+        let span_start = region_start(first);
+        let span_end =
+            region_end(last) + edit.replacement.len() + edit.range.start - edit.range.end;
+        let span = normalize_lines(&new_source[span_start..span_end], self.format.line_ending);
+        let (rest, entries) = parse_entries(&span, options).ok()?;
+        if !rest.is_empty() {
+            return None;
+        }
+
+        let mut reparsed = Self {
+            content: entries,
+            format: self.format.clone(),
+        };
+        reparsed.apply_options(options);
+
+        let mut file = self.clone();
+        file.content
+            .splice(entry_start(first)..entry_start(last + 1), reparsed.content);
+        Some(file)
+    }
+}
+
+/// Returns the positions of the lines starting a group.
+fn group_starts(source: &[u8]) -> Vec<usize> {
+    let mut starts = vec![];
+    let mut position = 0;
+
+    for line in source.split_inclusive(|b| *b == b'\n') {
+        if line.first() == Some(&b'[') {
+            starts.push(position);
+        }
+        position += line.len();
+    }
+
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# Leading comment
+[Desktop Entry]
+Name=App
+
+[Desktop Action new]
+Exec=app --new
+[Desktop Action quit]
+Exec=app --quit
+";
+
+    fn assert_reparse(edit: Edit) {
+        assert_reparse_source(SOURCE.as_bytes(), edit);
+    }
+
+    fn assert_reparse_source(source: &[u8], edit: Edit) {
+        let old = DesktopFile::try_from(source).unwrap();
+        let new_source = edit.apply(source);
+
+        assert!(
+            old.reparse_regions(source, &new_source, &edit, &ParserOptions::default())
+                .is_some()
+        );
+
+        assert_eq!(
+            old.reparse(source, &edit, &ParserOptions::default())
+                .unwrap(),
+            DesktopFile::try_from(new_source.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reparse_value() {
+        let start = SOURCE.find("--new").unwrap();
+        assert_reparse(Edit {
+            range: start..start + 5,
+            replacement: b"--new-window",
+        });
+    }
+
+    #[test]
+    fn test_reparse_new_group() {
+        let start = SOURCE.find("Exec=app --new").unwrap();
+        assert_reparse(Edit {
+            range: start..start,
+            replacement: b"Exec=app\n[Desktop Action other]\n",
+        });
+    }
+
+    #[test]
+    fn test_reparse_removed_header() {
+        let start = SOURCE.find("[Desktop Action quit]").unwrap();
+        assert_reparse(Edit {
+            range: start..start + 22,
+            replacement: b"",
+        });
+        assert_reparse(Edit {
+            range: 0..SOURCE.len(),
+            replacement: b"[Other]\n",
+        });
+    }
+
+    #[test]
+    fn test_reparse_crlf() {
+        assert_reparse_source(
+            b"[A]\r\nK=v\r\n[B]\r\nL=w\r\n",
+            Edit {
+                range: 7..8,
+                replacement: b"x",
+            },
+        );
+
+        let source = b"\xEF\xBB\xBF[A]\nK=v\n";
+        let old = DesktopFile::try_from(source.as_slice()).unwrap();
+        let edit = Edit {
+            range: 9..10,
+            replacement: b"x",
+        };
+        assert_eq!(
+            old.reparse(source, &edit, &ParserOptions::default())
+                .unwrap(),
+            DesktopFile::try_from(edit.apply(source).as_slice()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::Error,
+    parser::models::{ContentEntry, DesktopFile, Group},
+};
+
+/// A view of a [DesktopFile] with its groups and keys indexed, see [DesktopFile::indexed].
+///
+/// The view borrows the file, so it can never be outdated:
+/// the file can only be modified once the view is dropped, and indexed again afterwards.
+#[derive(Debug, Clone)]
+pub struct IndexedFile<'a> {
+    groups: HashMap<&'a str, IndexedGroup<'a>>,
+}
+
+/// A view of a [Group] with its keys indexed, see [IndexedFile::find].
+#[derive(Debug, Clone)]
+pub struct IndexedGroup<'a> {
+    group: &'a Group,
+    entries: HashMap<&'a str, Vec<&'a ContentEntry>>,
+}
+
+impl DesktopFile {
+    /// Indexes the groups and the keys of the file, so that they are found in constant time.
+    ///
+    /// Building the index is linear in the size of the file, so it pays off when many keys are looked up,
+    /// such as by launchers reading the `Name` and `Exec` of every entry.
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\nExec=app\n").unwrap();
+    /// let index = file.indexed();
+    /// let group = index.get("Desktop Entry").unwrap();
+    ///
+    /// assert_eq!(group.get("Exec").unwrap().values, ["app"]);
+    /// ```
+    pub fn indexed(&self) -> IndexedFile<'_> {
+        let mut groups = HashMap::new();
+        for group in self.groups() {
+            // Like [EntrySet::find], the first group with a header wins
+            groups
+                .entry(group.header.as_str())
+                .or_insert_with(|| IndexedGroup::new(group));
+        }
+        IndexedFile { groups }
+    }
+}
+
+impl<'a> IndexedFile<'a> {
+    /// Find the first group with this header, or `None` if there is no such group.
+    pub fn find(&self, header: &str) -> Option<&IndexedGroup<'a>> {
+        self.groups.get(header)
+    }
+
+    /// Similar to [Self::find], but throws if the group is not found.
+    pub fn get(&self, header: &str) -> Result<&IndexedGroup<'a>, Error> {
+        self.find(header)
+            .ok_or_else(|| Error::NotFound(header.to_owned()))
+    }
+}
+
+impl<'a> IndexedGroup<'a> {
+    fn new(group: &'a Group) -> Self {
+        let mut entries: HashMap<_, Vec<_>> = HashMap::new();
+        for entry in group.entries() {
+            entries.entry(entry.key.as_str()).or_default().push(entry);
+        }
+        Self { group, entries }
+    }
+
+    /// Returns the indexed group.
+    pub fn group(&self) -> &'a Group {
+        self.group
+    }
+
+    /// Find the first entry for this key, whatever its locale, or `None` if no entry with this key was found.
+    pub fn find(&self, key: &str) -> Option<&'a ContentEntry> {
+        self.entries
+            .get(key)
+            .and_then(|entries| entries.first())
+            .copied()
+    }
+
+    /// Similar to [Self::find], but throws if the key is not found.
+    pub fn get(&self, key: &str) -> Result<&'a ContentEntry, Error> {
+        self.find(key)
+            .ok_or_else(|| Error::NotFound(key.to_owned()))
+    }
+
+    /// Find all the entries for this key, in order, including its translations and duplicates.
+    pub fn find_all(&self, key: &str) -> &[&'a ContentEntry] {
+        self.entries.get(key).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::models::EntrySet;
+
+    use super::*;
+
+    #[test]
+    fn test_indexed() {
+        let file = DesktopFile::try_from(
+            "[A]\nName[fr]=Appli\nName=App\n# Comment\nName=Duplicate\n[B]\nKey=1\n[A]\nKey=2\n",
+        )
+        .unwrap();
+        let index = file.indexed();
+
+        let group = index.get("A").unwrap();
+        assert_eq!(group.find("Name"), file["A"].find("Name"));
+        assert_eq!(group.find_all("Name").len(), 3);
+        assert!(group.find("Key").is_none());
+        assert!(group.find_all("Key").is_empty());
+        assert_eq!(index.get("B").unwrap().get("Key").unwrap().values, ["1"]);
+        assert!(matches!(index.get("C"), Err(Error::NotFound(_))));
+        assert_eq!(group.group(), file.get("A").unwrap());
+    }
+}
@@ -0,0 +1,168 @@
+use alloc::vec;
+use core::{iter::FilterMap, slice};
+
+use crate::parser::models::{ContentEntry, DesktopFile, Entry, Group, TopLevelEntry};
+
+/// Iterator over the key-value entries of a [Group], see [Group::entries].
+pub type Entries<'a> = FilterMap<slice::Iter<'a, Entry>, fn(&'a Entry) -> Option<&'a ContentEntry>>;
+
+/// Mutable iterator over the key-value entries of a [Group], see [Group::entries_mut].
+pub type EntriesMut<'a> =
+    FilterMap<slice::IterMut<'a, Entry>, fn(&'a mut Entry) -> Option<&'a mut ContentEntry>>;
+
+/// Owning iterator over the key-value entries of a [Group].
+pub type IntoEntries = FilterMap<vec::IntoIter<Entry>, fn(Entry) -> Option<ContentEntry>>;
+
+/// Iterator over the groups of a [DesktopFile], see [DesktopFile::groups].
+pub type Groups<'a> =
+    FilterMap<slice::Iter<'a, TopLevelEntry>, fn(&'a TopLevelEntry) -> Option<&'a Group>>;
+
+/// Mutable iterator over the groups of a [DesktopFile], see [DesktopFile::groups_mut].
+pub type GroupsMut<'a> = FilterMap<
+    slice::IterMut<'a, TopLevelEntry>,
+    fn(&'a mut TopLevelEntry) -> Option<&'a mut Group>,
+>;
+
+/// Owning iterator over the groups of a [DesktopFile].
+pub type IntoGroups = FilterMap<vec::IntoIter<TopLevelEntry>, fn(TopLevelEntry) -> Option<Group>>;
+
+impl Group {
+    /// Returns an iterator over the key-value entries of the group, skipping comments and invalid lines.
+    pub fn entries(&self) -> Entries<'_> {
+        self.content.iter().filter_map(|entry| match entry {
+            Entry::Content(content) => Some(content),
+            Entry::Comment(_) | Entry::Invalid(_) => None,
+        })
+    }
+
+    /// Similar to [Self::entries], but the entries can be modified.
+    pub fn entries_mut(&mut self) -> EntriesMut<'_> {
+        self.content.iter_mut().filter_map(|entry| match entry {
+            Entry::Content(content) => Some(content),
+            Entry::Comment(_) | Entry::Invalid(_) => None,
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a Group {
+    type Item = &'a ContentEntry;
+    type IntoIter = Entries<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Group {
+    type Item = &'a mut ContentEntry;
+    type IntoIter = EntriesMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries_mut()
+    }
+}
+
+impl IntoIterator for Group {
+    type Item = ContentEntry;
+    type IntoIter = IntoEntries;
+
+    /// Consumes the group into its key-value entries, dropping comments and invalid lines.
+    fn into_iter(self) -> Self::IntoIter {
+        self.content.into_iter().filter_map(|entry| match entry {
+            Entry::Content(content) => Some(content),
+            Entry::Comment(_) | Entry::Invalid(_) => None,
+        })
+    }
+}
+
+impl DesktopFile {
+    /// Returns an iterator over the groups of the file, skipping the comments between them.
+    pub fn groups(&self) -> Groups<'_> {
+        self.content.iter().filter_map(|entry| match entry {
+            TopLevelEntry::Group(group) => Some(group),
+            TopLevelEntry::Comment(_) => None,
+        })
+    }
+
+    /// Similar to [Self::groups], but the groups can be modified.
+    pub fn groups_mut(&mut self) -> GroupsMut<'_> {
+        self.content.iter_mut().filter_map(|entry| match entry {
+            TopLevelEntry::Group(group) => Some(group),
+            TopLevelEntry::Comment(_) => None,
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a DesktopFile {
+    type Item = &'a Group;
+    type IntoIter = Groups<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.groups()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut DesktopFile {
+    type Item = &'a mut Group;
+    type IntoIter = GroupsMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.groups_mut()
+    }
+}
+
+impl IntoIterator for DesktopFile {
+    type Item = Group;
+    type IntoIter = IntoGroups;
+
+    /// Consumes the file into its groups, dropping the comments between them.
+    fn into_iter(self) -> Self::IntoIter {
+        self.content.into_iter().filter_map(|entry| match entry {
+            TopLevelEntry::Group(group) => Some(group),
+            TopLevelEntry::Comment(_) => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_iterators() {
+        let mut file =
+            DesktopFile::try_from("# Comment\n[A]\nKey=1\n# Comment\nOther=2\n[B]\nKey=3\n")
+                .unwrap();
+
+        let headers: Vec<_> = file.groups().map(|g| g.header.as_str()).collect();
+        assert_eq!(headers, ["A", "B"]);
+        let keys: Vec<_> = (&file)
+            .into_iter()
+            .flatten()
+            .map(|e| e.key.as_str())
+            .collect();
+        assert_eq!(keys, ["Key", "Other", "Key"]);
+
+        for group in &mut file {
+            for entry in group.entries_mut().filter(|e| e.key == "Key") {
+                entry.values[0].push('0');
+            }
+        }
+        assert_eq!(
+            file.to_string(),
+            "# Comment\n[A]\nKey=10\n# Comment\nOther=2\n[B]\nKey=30"
+        );
+
+        let values: Vec<String> = file
+            .into_iter()
+            .flat_map(Group::into_iter)
+            .flat_map(|e| e.values)
+            .collect();
+        assert_eq!(values, ["10", "2", "30"]);
+    }
+}
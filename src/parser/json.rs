@@ -0,0 +1,66 @@
+use serde_json::Value;
+
+use crate::{de::from_file, error::SerdeError, parser::models::DesktopFile, ser::to_file};
+
+impl DesktopFile {
+    /// Represents the file as a JSON object, whose groups are objects of their keys.
+    ///
+    /// Values are strings with their escape sequences decoded, and lists are arrays.
+    /// Localized keys are written with their locale, such as `Name[fr]`, and comments are dropped,
+    /// see [crate::from_str].
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    /// use serde_json::json;
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName[fr]=Appli\nCategories=Utility;\n").unwrap();
+    /// assert_eq!(
+    ///     file.to_json(),
+    ///     json!({"Desktop Entry": {"Name[fr]": "Appli", "Categories": ["Utility"]}})
+    /// );
+    /// ```
+    pub fn to_json(&self) -> Value {
+        from_file(self).expect("Any key-file can be represented in JSON")
+    }
+
+    /// Creates a file from a JSON object, whose groups are objects of their keys, see [Self::to_json].
+    ///
+    /// Numbers and booleans are written as values, and `null` keys or groups are skipped.
+    /// The groups and keys are written in the order of the objects, which is alphabetical unless
+    /// the `preserve_order` feature of `serde_json` is enabled, but `[Desktop Entry]` is always written first.
+    /// Fails if the value is not an object of objects, if a value is an object or a nested array,
+    /// or if a key is not valid.
+    pub fn from_json(value: &Value) -> Result<DesktopFile, SerdeError> {
+        to_file(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let file = DesktopFile::try_from(
+            "# Comment\n[Desktop Entry]\nName=App\\sA\nName[fr]=Appli\nKeywords=One;Two\\;Three;\n\n[Desktop Action new]\nExec=app --new\n",
+        )
+        .unwrap();
+        let json = json!({
+            "Desktop Entry": {"Name": "App A", "Name[fr]": "Appli", "Keywords": ["One", "Two;Three"]},
+            "Desktop Action new": {"Exec": "app --new"},
+        });
+        assert_eq!(file.to_json(), json);
+        assert_eq!(
+            DesktopFile::from_json(&json).unwrap().to_string(),
+            "[Desktop Entry]\nKeywords=One;Two\\;Three;\nName=App A\nName[fr]=Appli\n\n[Desktop Action new]\nExec=app --new"
+        );
+
+        let templated = json!({"Desktop Entry": {"Terminal": false, "Version": 1.5, "Icon": null}});
+        assert_eq!(
+            DesktopFile::from_json(&templated).unwrap().to_string(),
+            "[Desktop Entry]\nTerminal=false\nVersion=1.5"
+        );
+        assert!(DesktopFile::from_json(&json!({"Group": {"Key": {"Nested": 1}}})).is_err());
+    }
+}
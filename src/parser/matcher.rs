@@ -0,0 +1,205 @@
+use crate::parser::models::{ContentEntry, Group, Locale};
+
+/// Picks the best translations for a list of wanted locales, by order of preference.
+///
+/// For each wanted locale, the translations are matched following the precedence of the specification:
+/// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, then `lang`.
+/// The encodings are ignored, and so is the case of the languages and countries.
+/// A translation matching a preferred locale always wins over one matching a following locale.
+/// ```
+/// use freedesktop_rs::parser::{LocaleMatcher, models::Locale};
+///
+/// let wanted: Vec<Locale> = ["de_AT", "fr_FR"].map(|l| l.parse().unwrap()).into();
+/// let available: Vec<Locale> = ["fr_FR", "de", "en"].map(|l| l.parse().unwrap()).into();
+///
+/// let best = LocaleMatcher::new(&wanted).best(&available);
+/// assert_eq!(best.map(|l| l.to_string()).as_deref(), Some("de"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LocaleMatcher<'a> {
+    wanted: &'a [Locale],
+    gkeyfile: bool,
+}
+
+/// The number of candidates derived from each wanted locale.
+const CANDIDATES: usize = 4;
+
+/// The number of candidates derived from each wanted locale by GLib, which also matches the encodings.
+const GKEYFILE_CANDIDATES: usize = 8;
+
+impl<'a> LocaleMatcher<'a> {
+    /// Creates a matcher of the locales, by order of preference.
+    pub fn new(wanted: &'a [Locale]) -> Self {
+        Self {
+            wanted,
+            gkeyfile: false,
+        }
+    }
+
+    /// Matches the translations as GLib's `GKeyFile` does, see [ParserOptions::gkeyfile](crate::parser::ParserOptions::gkeyfile).
+    ///
+    /// The candidates are the ones of `g_get_locale_variants`, where the modifier takes precedence over the country:
+    /// `lang_COUNTRY.ENCODING@MODIFIER`, `lang_COUNTRY@MODIFIER`, `lang.ENCODING@MODIFIER`, `lang@MODIFIER`,
+    /// `lang_COUNTRY.ENCODING`, `lang_COUNTRY`, `lang.ENCODING`, then `lang`.
+    /// The encodings are matched, and so is the case of all the components.
+    /// ```
+    /// use freedesktop_rs::parser::{LocaleMatcher, models::Locale};
+    ///
+    /// let wanted: Vec<Locale> = ["sr_RS@latin"].map(|l| l.parse().unwrap()).into();
+    /// let available: Vec<Locale> = ["sr_RS", "sr@latin"].map(|l| l.parse().unwrap()).into();
+    ///
+    /// let best = LocaleMatcher::new(&wanted).gkeyfile().best(&available);
+    /// assert_eq!(best.map(|l| l.to_string()).as_deref(), Some("sr@latin"));
+    /// ```
+    pub fn gkeyfile(mut self) -> Self {
+        self.gkeyfile = true;
+        self
+    }
+
+    /// Returns the score of the available locale, the higher the better, or `None` if it matches no wanted locale.
+    pub fn score(&self, available: &Locale) -> Option<usize> {
+        if self.gkeyfile {
+            return self.gkeyfile_score(available);
+        }
+        self.wanted
+            .iter()
+            .enumerate()
+            .find_map(|(preference, wanted)| {
+                let candidates = [
+                    (wanted.country.as_ref(), wanted.modifiers.as_ref()),
+                    (wanted.country.as_ref(), None),
+                    (None, wanted.modifiers.as_ref()),
+                    (None, None),
+                ];
+                candidates
+                    .into_iter()
+                    .position(|(country, modifiers)| {
+                        available.lang.eq_ignore_ascii_case(&wanted.lang)
+                            && match (&available.country, country) {
+                                (Some(a), Some(c)) => a.eq_ignore_ascii_case(c),
+                                (a, c) => a.as_ref() == c,
+                            }
+                            && available.modifiers.as_ref() == modifiers
+                    })
+                    .map(|rank| (self.wanted.len() - preference) * CANDIDATES - rank)
+            })
+    }
+
+    fn gkeyfile_score(&self, available: &Locale) -> Option<usize> {
+        self.wanted
+            .iter()
+            .enumerate()
+            .find_map(|(preference, wanted)| {
+                // The components of each variant are in the order of the bits of its index, from the highest
+                (0..GKEYFILE_CANDIDATES)
+                    .rev()
+                    .position(|variant: usize| {
+                        let has = |bit: usize| variant & bit != 0;
+                        available.lang == wanted.lang
+                            && available.modifiers.as_ref()
+                                == wanted.modifiers.as_ref().filter(|_| has(4))
+                            && available.country.as_ref()
+                                == wanted.country.as_ref().filter(|_| has(2))
+                            && available.encoding.as_ref()
+                                == wanted.encoding.as_ref().filter(|_| has(1))
+                    })
+                    .map(|rank| (self.wanted.len() - preference) * GKEYFILE_CANDIDATES - rank)
+            })
+    }
+
+    /// Returns the available locale with the best score, the first one if several have the same score.
+    pub fn best<'b, I: IntoIterator<Item = &'b Locale>>(&self, available: I) -> Option<&'b Locale> {
+        self.best_by(available, |locale| Some(locale))
+    }
+
+    /// Returns the best translation of the key in the group, or its unlocalized entry if no translation matches.
+    pub fn find<'g>(&self, group: &'g Group, key: &str) -> Option<&'g ContentEntry> {
+        let entries = || group.entries().filter(|e| e.key == key);
+        self.best_by(entries(), |entry| entry.locale.as_ref())
+            .or_else(|| entries().find(|entry| entry.locale.is_none()))
+    }
+
+    /// Returns the item whose locale has the best score, the first one if several have the same score.
+    fn best_by<T, I: IntoIterator<Item = T>>(
+        &self,
+        items: I,
+        locale: impl Fn(&T) -> Option<&Locale>,
+    ) -> Option<T> {
+        let mut best: Option<(usize, T)> = None;
+        for item in items {
+            let Some(score) = locale(&item).and_then(|l| self.score(l)) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|(best, _)| score > *best) {
+                best = Some((score, item));
+            }
+        }
+        best.map(|(_, item)| item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn locales(locales: &[&str]) -> Vec<Locale> {
+        locales.iter().map(|l| l.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_score() {
+        let wanted = locales(&["sr_RS@latin", "de"]);
+        let matcher = LocaleMatcher::new(&wanted);
+        let score = |locale: &str| matcher.score(&locale.parse().unwrap());
+
+        assert!(score("sr_RS.UTF-8@latin") > score("sr_RS"));
+        assert!(score("sr_RS") > score("sr@latin"));
+        assert!(score("sr@latin") > score("sr"));
+        assert!(score("sr") > score("de"));
+        assert_eq!(score("de_DE"), None);
+        assert_eq!(score("fr"), None);
+    }
+
+    #[test]
+    fn test_gkeyfile_score() {
+        let wanted = locales(&["sr_RS.UTF-8@latin", "de"]);
+        let matcher = LocaleMatcher::new(&wanted).gkeyfile();
+        let score = |locale: &str| matcher.score(&locale.parse().unwrap());
+
+        assert!(score("sr_RS.UTF-8@latin") > score("sr_RS@latin"));
+        assert!(score("sr_RS@latin") > score("sr.UTF-8@latin"));
+        assert!(score("sr.UTF-8@latin") > score("sr@latin"));
+        assert!(score("sr@latin") > score("sr_RS.UTF-8"));
+        assert!(score("sr_RS.UTF-8") > score("sr_RS"));
+        assert!(score("sr_RS") > score("sr.UTF-8"));
+        assert!(score("sr.UTF-8") > score("sr"));
+        assert!(score("sr") > score("de"));
+        assert_eq!(score("de.UTF-8"), None);
+        assert_eq!(score("sr_rs"), None);
+    }
+
+    #[test]
+    fn test_find() {
+        let group: Group =
+            "[Desktop Entry]\nName=App\nName[de]=Anwendung\nName[sr@latin]=Aplikacija\n"
+                .parse()
+                .unwrap();
+        let name = |wanted: &[&str]| {
+            let wanted = locales(wanted);
+            LocaleMatcher::new(&wanted)
+                .find(&group, "Name")
+                .map(|e| e.values[0].clone())
+        };
+
+        assert_eq!(name(&["sr_RS@latin", "de"]).as_deref(), Some("Aplikacija"));
+        assert_eq!(name(&["fr", "de_CH"]).as_deref(), Some("Anwendung"));
+        assert_eq!(name(&["fr"]).as_deref(), Some("App"));
+        assert_eq!(name(&[]).as_deref(), Some("App"));
+        assert_eq!(
+            LocaleMatcher::new(&locales(&["de"])).find(&group, "Comment"),
+            None
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::parser::models::{CanBeComment, DesktopFile, Entry, EntrySet};
+
+/// How [DesktopFile::merge] resolves an entry found in both files, with the same key and locale.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum MergeStrategy {
+    /// The entry of the merged file replaces the existing one, as drop-in override files do.
+    OtherWins,
+    /// The existing entry is kept.
+    SelfWins,
+    /// The entry of the merged file is added after the existing entries of its group, keeping both.
+    AppendDuplicates,
+}
+
+impl DesktopFile {
+    /// Merges the groups of the other file into this one.
+    ///
+    /// The groups missing from this file are added at its end, with their comments.
+    /// In the existing groups, the missing entries are added at the end of the group,
+    /// and the existing ones are resolved with the strategy, keeping their position.
+    /// The comments of the other file outside of the added groups are ignored.
+    /// ```
+    /// use freedesktop_rs::parser::{MergeStrategy, models::DesktopFile};
+    ///
+    /// let mut system = DesktopFile::try_from("[Desktop Entry]\nName=App\nExec=app\n").unwrap();
+    /// let user = DesktopFile::try_from("[Desktop Entry]\nExec=app --user\nHidden=true\n").unwrap();
+    /// system.merge(&user, MergeStrategy::OtherWins);
+    ///
+    /// assert_eq!(system.to_string(), "[Desktop Entry]\nName=App\nExec=app --user\nHidden=true");
+    /// ```
+    pub fn merge(&mut self, other: &DesktopFile, strategy: MergeStrategy) {
+        for other_group in other.groups() {
+            let Some(group) = self.find_mut(&other_group.header) else {
+                self.content.push(other_group.clone().into());
+                continue;
+            };
+
+            for other_entry in other_group.entries() {
+                let existing = group.content.iter_mut().find(|e| {
+                    matches!(e, Entry::Content(c) if c.key == other_entry.key && c.locale == other_entry.locale)
+                });
+                match (existing, strategy) {
+                    (Some(existing), MergeStrategy::OtherWins) => {
+                        *existing = other_entry.clone().into()
+                    }
+                    (Some(_), MergeStrategy::SelfWins) => {}
+                    (Some(_), MergeStrategy::AppendDuplicates) | (None, _) => {
+                        // Before the blank lines separating the group from the next one
+                        let end = group
+                            .content
+                            .iter()
+                            .rposition(|e| !e.is_blank())
+                            .map_or(0, |last| last + 1);
+                        group.content.insert(end, other_entry.clone().into())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let base = DesktopFile::try_from(
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\n# Comment\nExec=app\n\n",
+        )
+        .unwrap();
+        let other = DesktopFile::try_from(
+            "# Ignored\n[Desktop Entry]\nName[fr]=Application\nIcon=app\n\n[Desktop Action new]\n# Kept\nExec=app --new\n",
+        )
+        .unwrap();
+        let merged = |strategy| {
+            let mut file = base.clone();
+            file.merge(&other, strategy);
+            file.to_string()
+        };
+
+        assert_eq!(
+            merged(MergeStrategy::OtherWins),
+            "[Desktop Entry]\nName=App\nName[fr]=Application\n# Comment\nExec=app\nIcon=app\n\n[Desktop Action new]\n# Kept\nExec=app --new"
+        );
+        assert_eq!(
+            merged(MergeStrategy::SelfWins),
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\n# Comment\nExec=app\nIcon=app\n\n[Desktop Action new]\n# Kept\nExec=app --new"
+        );
+        assert_eq!(
+            merged(MergeStrategy::AppendDuplicates),
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\n# Comment\nExec=app\nName[fr]=Application\nIcon=app\n\n[Desktop Action new]\n# Kept\nExec=app --new"
+        );
+    }
+}
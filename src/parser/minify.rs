@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+
+use crate::parser::models::{DesktopFile, Entry, TopLevelEntry};
+
+/// Which entry [DesktopFile::minified] keeps when a key is written several times with the same locale in a group.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DuplicatePolicy {
+    /// The first entry is kept, as [Group::find](crate::parser::models::EntrySet::find) returns it.
+    FirstWins,
+    /// The last entry is kept, as parsers which overwrite the previous values read it.
+    LastWins,
+}
+
+impl DesktopFile {
+    /// Returns a compact copy of the file, such as the one installed in system directories.
+    ///
+    /// Comments, blank lines and invalid lines are removed, entries are written with a bare `=` and their trimmed values,
+    /// and only one entry is kept for each key and locale of a group, following the policy, at its own position.
+    /// [DesktopFile::compact] does the same in place with [DuplicatePolicy::FirstWins].
+    /// ```
+    /// use freedesktop_rs::parser::{DuplicatePolicy, models::DesktopFile};
+    ///
+    /// let file = DesktopFile::try_from("# Generated\n[Desktop Entry]\nName = App\n\n# Run\nExec=app\nName=Other\n").unwrap();
+    ///
+    /// assert_eq!(
+    ///     file.minified(DuplicatePolicy::FirstWins).to_string(),
+    ///     "[Desktop Entry]\nName=App\nExec=app"
+    /// );
+    /// assert_eq!(
+    ///     file.minified(DuplicatePolicy::LastWins).to_string(),
+    ///     "[Desktop Entry]\nExec=app\nName=Other"
+    /// );
+    /// ```
+    pub fn minified(&self, policy: DuplicatePolicy) -> Self {
+        let mut file = self.clone();
+        file.minify(policy);
+        file
+    }
+
+    /// Minifies the file in place, see [Self::minified].
+    pub(super) fn minify(&mut self, policy: DuplicatePolicy) {
+        self.content
+            .retain(|entry| matches!(entry, TopLevelEntry::Group(_)));
+
+        for group in self.groups_mut() {
+            let mut content: Vec<Entry> = Vec::with_capacity(group.content.len());
+            for entry in group.content.drain(..) {
+                let Entry::Content(mut entry) = entry else {
+                    continue;
+                };
+                let duplicate = content.iter().position(|e| {
+                    matches!(e, Entry::Content(c) if c.key == entry.key && c.locale == entry.locale)
+                });
+                match (duplicate, policy) {
+                    (Some(_), DuplicatePolicy::FirstWins) => continue,
+                    (Some(index), DuplicatePolicy::LastWins) => {
+                        content.remove(index);
+                    }
+                    (None, _) => {}
+                }
+                entry.format.raw_values = None;
+                entry.format.assignment = None;
+                entry.format.doc_comments.clear();
+                content.push(Entry::Content(entry));
+            }
+            group.content = content;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_minified() {
+        let file = DesktopFile::try_from(
+            "# Header\n\n[Desktop Entry]\nName=App\nName[fr]=Appli\n# Comment\n\nName[fr] = Application\n\n[Desktop Action new]\nExec=  app --new\n\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            file.minified(DuplicatePolicy::FirstWins).to_string(),
+            "[Desktop Entry]\nName=App\nName[fr]=Appli\n[Desktop Action new]\nExec=app --new"
+        );
+        assert_eq!(
+            file.minified(DuplicatePolicy::LastWins).to_string(),
+            "[Desktop Entry]\nName=App\nName[fr]=Application\n[Desktop Action new]\nExec=app --new"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fs::File, path::Path};
+
+use memmap2::Mmap;
+
+use crate::{error::Error, parser::models::DesktopFile};
+
+use super::ParserOptions;
+
+impl DesktopFile {
+    /// Parses a file by mapping it in memory, which avoids reading it into a buffer first.
+    ///
+    /// This is not zero-copy: the keys, values and comments are copied out of the map into owned entries,
+    /// as by [Self::parse_with_options], and the map is released before returning.
+    /// Only the buffer holding the whole file is saved, which matters for large files.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is parsed, see [Mmap::map].
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub unsafe fn from_mmap<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        // SAFETY: upheld by the caller.
+        unsafe { Self::from_mmap_with_options(path, &ParserOptions::default()) }
+    }
+
+    /// Similar to [Self::from_mmap], but with the given options.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is parsed, see [Mmap::map].
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub unsafe fn from_mmap_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParserOptions,
+    ) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::Io)?;
+        // SAFETY: upheld by the caller.
+        let map = unsafe { Mmap::map(&file) }.map_err(Error::Io)?;
+
+        Self::parse_with_options(&map, options).map_err(Error::Parsing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_parsing() {
+        let content = "[Desktop Entry]
+Type=Application
+Name=Mapped
+";
+        let path = std::env::temp_dir().join(format!(
+            "freedesktop-rs-mmap-{}.desktop",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+
+        let parsed = unsafe { DesktopFile::from_mmap(&path) }.unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(parsed, DesktopFile::try_from(content).unwrap());
+    }
+}
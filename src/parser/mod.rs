@@ -1,227 +1,552 @@
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::str::FromStr;
+
+use crate::error::LocaleError;
 use models::{
-    CommentEntry, ContentEntry, DesktopFile, Entry, Group, GroupContent, Locale, TopLevelEntry,
+    ContentEntry, DesktopFile, Entry, FileFormat, Group, LineEnding, Locale, TopLevelEntry,
 };
-use nom::{
-    branch::alt,
-    bytes::complete::{escaped_transform, is_not, take_while},
-    character::complete::{alpha1, char, line_ending, multispace1, space0},
-    combinator::{eof, map, map_res, opt, value},
-    error::{Error, ErrorKind},
-    multi::{many0, many_till},
-    sequence::{delimited, pair, preceded, terminated},
-    AsChar, IResult, Parser,
+use nom::{Parser as _, combinator::all_consuming, error::Error};
+use raw::{
+    CommentParser, decode_backslashes, parse_content_entry, parse_group, parse_key, parse_locale,
+    parse_top_level_entries_with,
 };
+use recovery::parse_recovering;
 
 /// Low level models
 pub mod models;
+pub use models::escape_value;
 
-impl TryFrom<&[u8]> for DesktopFile {
-    type Error = nom::Err<Error<Vec<u8>>>;
+/// Individual parsers of the format, to be reused by parsers of similar key-file formats.
+///
+/// Every parser is a [nom] parser over bytes, so it can be embedded in other grammars,
+/// such as the ones of `.theme` or `.service` files:
+/// ```
+/// use freedesktop_rs::parser::raw::parse_group;
+/// use nom::{Parser, multi::many1};
+///
+/// let theme = b"[Icon Theme]\nName=Theme\n[16x16/apps]\nSize=16\n";
+/// let (rest, groups) = many1(parse_group).parse(theme).unwrap();
+///
+/// assert!(rest.is_empty());
+/// assert_eq!(groups[1].header, "16x16/apps");
+/// ```
+pub mod raw;
+pub use raw::unescape_value;
 
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let res = many0(parse_top_level_entry).parse(value);
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+mod async_reader;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+#[cfg(feature = "mmap")]
+mod mmap;
+
+mod documents;
+pub use documents::{DocumentBoundary, Documents};
+
+mod incremental;
+pub use incremental::Edit;
+
+mod push;
+pub use push::Parser;
+
+mod builder;
+pub use builder::{Banner, DesktopFileBuilder, GroupBuilder};
+
+mod diff;
+pub use diff::Change;
+
+mod filter;
+
+mod comments;
+
+mod format;
+#[cfg(feature = "std")]
+pub use format::format_tree;
+pub use format::{ControlCharacters, FormatOptions};
+
+mod hash;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+mod indexed;
+#[cfg(feature = "std")]
+pub use indexed::{IndexedFile, IndexedGroup};
+
+mod iter;
+
+mod matcher;
+pub use matcher::LocaleMatcher;
+
+mod merge;
+pub use merge::MergeStrategy;
+
+mod minify;
+pub use minify::DuplicatePolicy;
+
+mod stats;
+pub use iter::{Entries, EntriesMut, Groups, GroupsMut, IntoEntries, IntoGroups};
+pub use stats::FileStats;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "verify")))]
+#[cfg(feature = "verify")]
+mod verified;
+#[cfg(feature = "verify")]
+pub use verified::sha256_hex;
+
+mod validate;
+pub use validate::{Diagnostic, Severity};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "encoding")))]
+#[cfg(feature = "encoding")]
+mod encoding;
+
+mod recovery;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fuzzing")))]
+#[cfg(feature = "fuzzing")]
+mod fuzzing;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+#[cfg(feature = "schemars")]
+mod schema;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+mod environment;
 
-        match res {
-            Ok((_, content)) => Ok(Self { content }),
-            Err(e) => Err(e.to_owned()),
+mod report;
+pub use report::{ParseReport, Recovery, RecoveryAction};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+mod writer;
+
+/// Options changing how a [DesktopFile] is parsed.
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    /// Whether leading and trailing whitespaces are removed from values. Defaults to `true`.
+    ///
+    /// The values as written in the input are kept in [models::EntryFormat::raw_values] regardless of this option.
+    pub trim_values: bool,
+
+    /// The character separating the values of a list. Defaults to `;`.
+    pub list_separator: char,
+
+    /// Whether lines of a group that cannot be parsed are kept as [Entry::Invalid] instead of stopping the parsing.
+    /// Defaults to `false`.
+    ///
+    /// Lines before the first group cannot be recovered.
+    pub recover_invalid_lines: bool,
+
+    /// Whether escape sequences of values are decoded, e.g. `\n` into a line feed and `\s` into a space.
+    /// Defaults to `false`, which keeps them as written, except `\\` which is read as a backslash.
+    ///
+    /// Decoded values are escaped again when the entry is written, see [models::EntryFormat::escapes_decoded].
+    pub decode_escapes: bool,
+
+    /// Whether the comment lines right before an entry are attached to it, see [DesktopFile::attach_comments].
+    /// Defaults to `false`, which keeps them as [Entry::Comment].
+    pub attach_comments: bool,
+
+    /// Whether the spaces after the `#` of comments are kept in their text, see [raw::parse_verbatim_comment_entry].
+    /// Defaults to `false`, which removes them.
+    pub verbatim_comments: bool,
+}
+
+/// The list separator defined by the specification.
+const DEFAULT_LIST_SEPARATOR: char = ';';
+
+/// The UTF-8 encoding of U+FEFF, which some editors write at the start of files.
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            trim_values: true,
+            list_separator: DEFAULT_LIST_SEPARATOR,
+            recover_invalid_lines: false,
+            decode_escapes: false,
+            attach_comments: false,
+            verbatim_comments: false,
         }
     }
 }
 
-impl TryFrom<&str> for DesktopFile {
-    type Error = nom::Err<Error<Vec<u8>>>;
+impl ParserOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Self::try_from(value.as_bytes())
+    /// Creates options matching how GLib's `GKeyFile` reads files, so that they are written back as it would.
+    ///
+    /// `GKeyFile` only removes the spaces between the `=` and the value, keeps the comments as they are written,
+    /// and ties them to the key that follows. Files read with these options are written back as `GKeyFile` would
+    /// with [FormatOptions::gkeyfile], and [LocaleMatcher::gkeyfile] picks the translations that `GKeyFile` would.
+    /// The list separator is set with [Self::with_list_separator], as with `g_key_file_set_list_separator`.
+    /// ```
+    /// use freedesktop_rs::parser::{FormatOptions, ParserOptions, models::DesktopFile};
+    ///
+    /// let input = "#Settings\n[Settings]\n#  Sizes \nSizes=small ,large,\n[Other]\nKey=value\n";
+    /// let options = ParserOptions::gkeyfile().with_list_separator(',');
+    /// let file = DesktopFile::parse_with_options(input.as_bytes(), &options).unwrap();
+    ///
+    /// assert_eq!(file["Settings"]["Sizes"].values, ["small ", "large"]);
+    /// assert_eq!(
+    ///     file.to_string_with(&FormatOptions::gkeyfile()),
+    ///     "#Settings\n\n[Settings]\n#  Sizes \nSizes=small ,large,\n\n[Other]\nKey=value"
+    /// );
+    /// ```
+    pub fn gkeyfile() -> Self {
+        Self::default()
+            .untrimmed_values()
+            .attach_comments()
+            .verbatim_comments()
+    }
+
+    /// Keeps the leading and trailing whitespaces of values.
+    pub fn untrimmed_values(mut self) -> Self {
+        self.trim_values = false;
+        self
+    }
+
+    /// Keeps the lines that cannot be parsed as [Entry::Invalid].
+    pub fn recover_invalid_lines(mut self) -> Self {
+        self.recover_invalid_lines = true;
+        self
+    }
+
+    /// Decodes the escape sequences of values into the characters they stand for.
+    pub fn decode_escapes(mut self) -> Self {
+        self.decode_escapes = true;
+        self
+    }
+
+    /// Attaches the comment lines right before an entry to it.
+    pub fn attach_comments(mut self) -> Self {
+        self.attach_comments = true;
+        self
+    }
+
+    /// Keeps the spaces after the `#` of comments in their text.
+    pub fn verbatim_comments(mut self) -> Self {
+        self.verbatim_comments = true;
+        self
+    }
+
+    /// Returns the parser of the comments, according to [Self::verbatim_comments].
+    fn comment_parser(&self) -> CommentParser {
+        match self.verbatim_comments {
+            true => raw::parse_verbatim_comment_entry,
+            false => raw::parse_comment_entry,
+        }
     }
-}
 
-fn parse_top_level_entry(input: &[u8]) -> IResult<&[u8], TopLevelEntry> {
-    alt((
-        map_res(parse_group, TopLevelEntry::try_from),
-        map_res(parse_comment_entry, TopLevelEntry::try_from),
-    ))
-    .parse(input)
+    /// Changes the character separating the values of a list, as `GKeyFile` allows.
+    ///
+    /// The separator is kept in [models::EntryFormat::list_separator] so that the entry is written back with it.
+    pub fn with_list_separator(mut self, separator: char) -> Self {
+        self.list_separator = separator;
+        self
+    }
 }
 
-fn parse_group(input: &[u8]) -> IResult<&[u8], Group> {
-    let (input, (header, content)) = pair(parse_group_header, parse_group_content).parse(input)?;
+impl DesktopFile {
+    /// Parses raw data with the given options.
+    pub fn parse_with_options(
+        value: &[u8],
+        options: &ParserOptions,
+    ) -> Result<Self, nom::Err<Error<Vec<u8>>>> {
+        // Unlike parse_with_report, the lines which cannot be parsed are not located
+        let mut file = Self::parse_normalized(value, |input| parse_entries(input, options))?;
+        file.apply_options(options);
+        Ok(file)
+    }
+
+    /// Parses a `mimeapps.list` file, whose keys are MIME types such as `text/plain`.
+    #[cfg(feature = "std")]
+    pub(crate) fn parse_mimeapps(value: &[u8]) -> Result<Self, nom::Err<Error<Vec<u8>>>> {
+        Self::parse_normalized(value, raw::parse_mimeapps_entries)
+    }
+
+    /// Strips the byte order mark and normalizes the line endings before parsing the entries.
+    fn parse_normalized<F>(value: &[u8], parse: F) -> Result<Self, nom::Err<Error<Vec<u8>>>>
+    where
+        F: Fn(&[u8]) -> nom::IResult<&[u8], Vec<TopLevelEntry>>,
+    {
+        let (value, byte_order_mark) = strip_byte_order_mark(value);
+        let line_ending = detect_line_ending(value);
+        let normalized = normalize_lines(value, line_ending);
 
-    Ok((
-        input,
-        Group {
-            header: header.to_owned(),
+        let (_, content) = parse(&normalized).map_err(|e| e.to_owned())?;
+        Ok(Self {
             content,
-        },
-    ))
+            format: FileFormat {
+                byte_order_mark,
+                line_ending,
+            },
+        })
+    }
+
+    fn apply_options(&mut self, options: &ParserOptions) {
+        if options.attach_comments {
+            self.attach_comments();
+        }
+
+        let custom_separator = options.list_separator != DEFAULT_LIST_SEPARATOR;
+        if options.trim_values && !custom_separator && !options.decode_escapes {
+            return;
+        }
+
+        for entry in self
+            .content
+            .iter_mut()
+            .flat_map(|tle| match tle {
+                TopLevelEntry::Group(group) => group.content.iter_mut(),
+                TopLevelEntry::Comment(_) => [].iter_mut(),
+            })
+            .filter_map(|e| match e {
+                Entry::Content(content_entry) => Some(content_entry),
+                Entry::Comment(_) | Entry::Invalid(_) => None,
+            })
+        {
+            let Some(raw_values) = &mut entry.format.raw_values else {
+                continue;
+            };
+
+            if custom_separator {
+                let mut raw = raw_values.join(&DEFAULT_LIST_SEPARATOR.to_string());
+                if entry.format.trailing_separator {
+                    raw.push(DEFAULT_LIST_SEPARATOR);
+                }
+                let mut separator = [0; 4];
+                let separator = options.list_separator.encode_utf8(&mut separator);
+                entry.format.trailing_separator =
+                    ends_with_separator(raw.as_bytes(), separator.as_bytes());
+                *raw_values = split_values(&raw, options.list_separator);
+                entry.format.list_separator = Some(options.list_separator);
+            }
+
+            // Decoded after trimming, so that `\s` keeps its space
+            let decode = match options.decode_escapes {
+                true => unescape_value,
+                false => decode_backslashes,
+            };
+            entry.values = raw_values
+                .iter()
+                .map(|v| decode(if options.trim_values { v.trim() } else { v }))
+                .collect();
+            entry.format.values_trimmed = options.trim_values;
+            entry.format.escapes_decoded = options.decode_escapes;
+        }
+    }
 }
 
-fn parse_group_header(input: &[u8]) -> IResult<&[u8], &str> {
-    map_res(
-        terminated(
-            delimited(char('['), take_while(|c| c != b'[' && c != b']'), char(']')),
-            opt(char('\n')),
-        ),
-        str::from_utf8,
-    )
-    .parse(input)
+/// Removes the UTF-8 byte order mark at the start of the input, and returns whether there was one.
+fn strip_byte_order_mark(input: &[u8]) -> (&[u8], bool) {
+    match input.strip_prefix(BYTE_ORDER_MARK) {
+        Some(rest) => (rest, true),
+        None => (input, false),
+    }
 }
 
-fn parse_group_content(input: &[u8]) -> IResult<&[u8], GroupContent> {
-    many0(parse_entry).parse(input)
+/// Detects the line ending of the input from its first line, and converts the `\r\n` line endings to `\n` if it is [LineEnding::CrLf].
+fn normalize_line_endings(input: &[u8]) -> (Cow<'_, [u8]>, LineEnding) {
+    match detect_line_ending(input) {
+        LineEnding::Lf => (Cow::Borrowed(input), LineEnding::Lf),
+        LineEnding::CrLf => (Cow::Owned(crlf_to_lf(input)), LineEnding::CrLf),
+    }
 }
 
-fn parse_entry(input: &[u8]) -> IResult<&[u8], Entry> {
-    alt((
-        map_res(parse_comment_entry, Entry::try_from),
-        map_res(parse_content_entry, Entry::try_from),
-    ))
-    .parse(input)
+/// Transcodes the lines in a legacy encoding if the `encoding` feature is enabled,
+/// and converts the `\r\n` line endings to `\n` if the line ending of the file is [LineEnding::CrLf].
+///
+/// Lines are normalized independently, so the input may be any sequence of whole lines of the file.
+fn normalize_lines(input: &[u8], line_ending: LineEnding) -> Cow<'_, [u8]> {
+    #[cfg(feature = "encoding")]
+    let input = encoding::transcode_legacy(input);
+    #[cfg(not(feature = "encoding"))]
+    let input = Cow::Borrowed(input);
+
+    match line_ending {
+        LineEnding::Lf => input,
+        LineEnding::CrLf => Cow::Owned(crlf_to_lf(&input)),
+    }
 }
 
-fn parse_blank_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
-    let (input, space) = map_res(multispace1, str::from_utf8).parse(input)?;
-    Ok((input, CommentEntry::Blank(space.to_owned())))
+/// Detects the line ending of the input from its first line, which is [LineEnding::Lf] if there is none.
+fn detect_line_ending(input: &[u8]) -> LineEnding {
+    match memchr::memchr(b'\n', input) {
+        Some(end) if end > 0 && input[end - 1] == b'\r' => LineEnding::CrLf,
+        _ => LineEnding::Lf,
+    }
 }
 
-fn parse_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
-    alt((parse_blank_comment_entry, parse_text_comment_entry)).parse(input)
+/// Converts the `\r\n` line endings of the input to `\n`.
+fn crlf_to_lf(input: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(input.len());
+    for line in input.split_inclusive(|b| *b == b'\n') {
+        match line.strip_suffix(b"\r\n") {
+            Some(content) => {
+                normalized.extend_from_slice(content);
+                normalized.push(b'\n');
+            }
+            None => normalized.extend_from_slice(line),
+        }
+    }
+    normalized
 }
 
-fn parse_text_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
-    let (input, comment) = map_res(
-        preceded(
-            pair(char('#'), space0),
-            terminated(
-                take_while(|c: u8| {
-                    let item = c.as_char();
-                    item.is_ascii() && !item.is_newline()
-                }),
-                char('\n'),
-            ),
-        ),
-        str::from_utf8,
-    )
-    .parse(input)?;
-
-    Ok((input, CommentEntry::Text(comment.to_owned())))
+/// Parses the top-level entries of the input, recovering invalid lines if required by the options.
+fn parse_entries<'a>(
+    input: &'a [u8],
+    options: &ParserOptions,
+) -> nom::IResult<&'a [u8], Vec<TopLevelEntry>> {
+    if options.recover_invalid_lines {
+        parse_recovering(input, options.comment_parser())
+    } else {
+        parse_top_level_entries_with(input, parse_key, options.comment_parser())
+    }
 }
 
-fn parse_key(input: &[u8]) -> IResult<&[u8], &str> {
-    map_res(
-        take_while(|c: u8| {
-            let item = c.as_char();
-            item.is_alphanumeric() || item == '-'
-        }),
-        str::from_utf8,
-    )
-    .parse(input)
+/// Returns whether the text ends with a separator which is not escaped.
+fn ends_with_separator(text: &[u8], separator: &[u8]) -> bool {
+    let Some(rest) = text.strip_suffix(separator) else {
+        return false;
+    };
+    let escapes = rest.iter().rev().take_while(|b| **b == b'\\').count();
+    escapes % 2 == 0
 }
 
-fn parse_value(input: &[u8]) -> IResult<&[u8], Vec<String>> {
-    map(
-        many_till(parse_single_value, alt((line_ending, eof))),
-        |r| r.0,
-    )
-    .parse(input)
+/// Splits the raw values on the unescaped separators, ignoring the trailing one.
+pub(crate) fn split_values(raw: &str, separator: char) -> Vec<String> {
+    let mut values = vec![];
+    let mut current = String::new();
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            current.push(c);
+            current.extend(chars.next());
+        } else if c == separator {
+            values.push(core::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        values.push(current);
+    }
+
+    values
 }
 
-fn parse_single_value(input: &[u8]) -> IResult<&[u8], String> {
-    terminated(
-        map_res(
-            escaped_transform(
-                is_not("\\;\n"),
-                '\\',
-                map(
-                    alt((
-                        value("\\n", char('n')),
-                        value("\\r", char('r')),
-                        value("\\s", char('s')),
-                        value("\\t", char('t')),
-                        value("\\", char('\\')),
-                        value("\\;", char(';')),
-                    )),
-                    |s| s.as_bytes(),
-                ),
-            ),
-            |v| String::from_utf8(v).map(|s| s.trim().to_owned()),
-        ),
-        opt(char(';')),
-    )
-    .parse(input)
+impl TryFrom<&[u8]> for DesktopFile {
+    type Error = nom::Err<Error<Vec<u8>>>;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_with_options(value, &ParserOptions::default())
+    }
 }
 
-fn parse_entry_locale(input: &[u8]) -> IResult<&[u8], Option<Locale>> {
-    let (main_input, locale_result) = opt(delimited(
-        char('['),
-        take_while(|c| c != b'[' && c != b']'),
-        char(']'),
-    ))
-    .parse(input)?;
-
-    if let Some(raw) = locale_result {
-        let modifier_parser = map_res(preceded(char('@'), alpha1), |r| str::from_utf8(r));
-        let encoding_parser = map_res(
-            preceded(
-                char('.'),
-                take_while(|b: u8| {
-                    let c = b.as_char();
-                    c.is_alphanumeric() || c == '-'
-                }),
-            ),
-            |r| str::from_utf8(r),
-        );
-        let country_parser = map_res(preceded(char('_'), alpha1), |r| str::from_utf8(r));
-        let mut lang_parser = map_res(alpha1, |r| str::from_utf8(r));
-
-        let (input, lang) = lang_parser.parse(raw)?;
-        let (input, country) = opt(country_parser).parse(input)?;
-        println!("{}", str::from_utf8(input).unwrap());
-        let (input, encoding) = opt(encoding_parser).parse(input)?;
-        let (input, modifier) = opt(modifier_parser).parse(input)?;
-        if !input.is_empty() {
-            return Err(nom::Err::Failure(Error {
-                input,
-                code: ErrorKind::NonEmpty,
-            }));
-        }
-        Ok((
-            main_input,
-            Some(Locale {
-                lang: lang.to_owned(),
-                country: country.map(ToOwned::to_owned),
-                encoding: encoding.map(ToOwned::to_owned),
-                modifiers: modifier.map(ToOwned::to_owned),
-            }),
-        ))
-    } else {
-        Ok((main_input, None))
+impl TryFrom<&str> for DesktopFile {
+    type Error = nom::Err<Error<Vec<u8>>>;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_bytes())
+    }
+}
+
+impl FromStr for DesktopFile {
+    type Err = nom::Err<Error<Vec<u8>>>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl FromStr for Group {
+    type Err = nom::Err<Error<Vec<u8>>>;
+
+    /// Parses a group header and its entries, such as `[Desktop Entry]\nName=App`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_complete(s.as_bytes(), parse_group)
+    }
+}
+
+impl FromStr for ContentEntry {
+    type Err = nom::Err<Error<Vec<u8>>>;
+
+    /// Parses a single key-values entry, such as `Name[fr]=Bonjour`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_complete(s.as_bytes(), parse_content_entry)
+    }
+}
+
+impl FromStr for Locale {
+    type Err = LocaleError;
+
+    /// Parses a locale without brackets, such as `en_US.UTF-8@new`, as `lang_COUNTRY.ENCODING@MODIFIER`.
+    ///
+    /// Only the language is required. Fails with the first malformed component:
+    /// ```
+    /// use freedesktop_rs::{error::LocaleError, parser::models::Locale};
+    ///
+    /// let locale: Locale = "sr_RS@latin".parse().unwrap();
+    /// assert_eq!(locale.country.as_deref(), Some("RS"));
+    ///
+    /// assert_eq!("fr_".parse::<Locale>(), Err(LocaleError::EmptyComponent('_')));
+    /// assert_eq!("fr@euro_FR".parse::<Locale>(), Err(LocaleError::UnexpectedCharacter('_')));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_locale(s)
+    }
+}
+
+impl TryFrom<&str> for Locale {
+    type Error = LocaleError;
+
+    /// Parses a locale without brackets, see [Locale::from_str].
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
 
-fn parse_content_entry(input: &[u8]) -> IResult<&[u8], ContentEntry> {
-    let (input, key) = parse_key.parse(input)?;
-
-    let (input, locale) = parse_entry_locale.parse(input)?;
-
-    let (input, _) = (space0, char('='), space0).parse(input)?;
-    let (input, values) = parse_value.parse(input)?;
-    Ok((
-        input,
-        ContentEntry {
-            key: key.to_owned(),
-            values,
-            locale,
-        },
-    ))
+/// Parses the whole input, failing if anything is left after the parsed item.
+fn parse_complete<'a, O>(
+    input: &'a [u8],
+    parser: impl nom::Parser<&'a [u8], Output = O, Error = Error<&'a [u8]>>,
+) -> Result<O, nom::Err<Error<Vec<u8>>>> {
+    all_consuming(parser)
+        .parse(input)
+        .map(|(_, output)| output)
+        .map_err(|e| e.to_owned())
 }
 
 #[cfg(test)]
 mod tests {
 
+    use alloc::{borrow::ToOwned, format, string::ToString};
+
     use nom::{error::ErrorKind, error_position};
 
-    use super::{parse_entry, *};
+    use crate::parser::models::EntrySet;
+
+    use super::{models::*, raw::*, *};
 
     #[test]
     fn test_parse_entry() {
@@ -238,7 +563,8 @@ mod tests {
                 Entry::Content(ContentEntry {
                     key: "Hello".to_owned(),
                     values: vec!["World".to_owned()],
-                    locale: None
+                    locale: None,
+                    format: EntryFormat::default(),
                 })
             ))
         );
@@ -255,7 +581,8 @@ mod tests {
                         country: Some(String::from("US")),
                         encoding: Some(String::from("UTF-8")),
                         modifiers: None,
-                    })
+                    }),
+                    format: EntryFormat::default(),
                 })
             ))
         );
@@ -283,12 +610,243 @@ mod tests {
                 Entry::Content(ContentEntry {
                     key: String::from("Hello"),
                     values: vec![],
-                    locale: None
+                    locale: None,
+                    format: EntryFormat::default(),
                 })
             ))
         )
     }
 
+    #[test]
+    fn test_utf8_comment() {
+        let comment = "# Éditeur de texte — 文本编辑器\n";
+
+        assert_eq!(
+            parse_entry(comment.as_bytes()),
+            Ok((
+                "".as_bytes(),
+                Entry::Comment(CommentEntry::Text(
+                    "Éditeur de texte — 文本编辑器".to_owned()
+                ))
+            ))
+        );
+        assert_eq!(
+            DesktopFile::try_from(comment).unwrap().to_string(),
+            comment.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        let file = "\u{feff}[Desktop Entry]\nName=App";
+        let parsed = DesktopFile::try_from(file).unwrap();
+
+        assert!(parsed.format.byte_order_mark);
+        assert_eq!(parsed, DesktopFile::try_from(&file[3..]).unwrap());
+        assert_eq!(parsed.to_string(), file);
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let file = "# Comment\r\n[Desktop Entry]\r\nName=App\r\n\r\nKeywords=a;b;\r\n";
+        let parsed = DesktopFile::try_from(file).unwrap();
+
+        assert_eq!(parsed.format.line_ending, LineEnding::CrLf);
+        assert_eq!(
+            parsed,
+            DesktopFile::try_from(file.replace("\r\n", "\n").as_str()).unwrap()
+        );
+        assert_eq!(parsed["Desktop Entry"]["Keywords"].values, ["a", "b"]);
+        assert_eq!(parsed.to_string(), file.trim_end());
+        assert_eq!(
+            DesktopFile::try_from("[A]\nKey=a\r\n")
+                .unwrap()
+                .format
+                .line_ending,
+            LineEnding::Lf
+        );
+    }
+
+    #[test]
+    fn test_assignment_round_trip() {
+        let file = "[Desktop Entry]\nName = App\nExec\t=app\nType=Application";
+        let mut parsed = DesktopFile::try_from(file).unwrap();
+
+        assert_eq!(parsed.to_string(), file);
+        let group = parsed.get_mut("Desktop Entry").unwrap();
+        group.get_mut("Exec").unwrap().values = vec![String::from("other")];
+        assert_eq!(
+            parsed.to_string(),
+            "[Desktop Entry]\nName = App\nExec\t=other\nType=Application"
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let file: DesktopFile = "[Desktop Entry]\nName=App".parse().unwrap();
+        let group: Group = "[Desktop Entry]\nName=App".parse().unwrap();
+        let entry: ContentEntry = "Name[fr_FR]=Appli".parse().unwrap();
+        let locale: Locale = "sr_YU.UTF-8@Latn".parse().unwrap();
+
+        assert_eq!(file.get("Desktop Entry").unwrap(), &group);
+        assert_eq!(entry.locale.unwrap().to_string(), "fr_FR");
+        assert_eq!(locale.encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(locale.modifiers.as_deref(), Some("Latn"));
+        assert!("Name=App\nExec=app".parse::<ContentEntry>().is_err());
+        assert!("[Desktop Entry]\n[Other".parse::<Group>().is_err());
+        assert_eq!("".parse::<Locale>(), Err(LocaleError::Empty));
+        assert_eq!("_FR".parse::<Locale>(), Err(LocaleError::MissingLanguage));
+        assert_eq!(
+            Locale::try_from("fr_FR.@euro"),
+            Err(LocaleError::EmptyComponent('.'))
+        );
+        assert_eq!(
+            "fr[FR]".parse::<Locale>(),
+            Err(LocaleError::UnexpectedCharacter('['))
+        );
+    }
+
+    #[test]
+    fn test_decode_escapes() {
+        let file = "[Desktop Entry]\nComment=\\sLine\\nNext\\tTab\\\\\nKeywords=a\\;b;c;\n";
+        let options = ParserOptions::new().decode_escapes();
+        let parsed = DesktopFile::parse_with_options(file.as_bytes(), &options).unwrap();
+        let group = parsed.get("Desktop Entry").unwrap();
+
+        assert_eq!(
+            group.get("Comment").unwrap().values,
+            vec![" Line\nNext\tTab\\"]
+        );
+        assert_eq!(group.get("Keywords").unwrap().values, vec!["a;b", "c"]);
+        assert_eq!(parsed.to_string(), file.trim_end());
+
+        let kept = DesktopFile::try_from(file).unwrap();
+        assert_eq!(
+            kept.get("Desktop Entry")
+                .unwrap()
+                .get("Comment")
+                .unwrap()
+                .values,
+            vec!["\\sLine\\nNext\\tTab\\"]
+        );
+    }
+
+    #[test]
+    fn test_trailing_separator() {
+        let file = "[Desktop Entry]\nCategories=Game;Arcade;\nKeywords=a;b\nName=Semi\\;\n";
+        let parsed = DesktopFile::try_from(file).unwrap();
+        let group = parsed.get("Desktop Entry").unwrap();
+
+        assert!(group.get("Categories").unwrap().format.trailing_separator);
+        assert!(!group.get("Keywords").unwrap().format.trailing_separator);
+        assert!(!group.get("Name").unwrap().format.trailing_separator);
+        assert_eq!(parsed.to_string(), file.trim_end());
+
+        let commas = DesktopFile::parse_with_options(
+            b"[Group]\nList=a,b,\n",
+            &ParserOptions::new()
+                .untrimmed_values()
+                .with_list_separator(','),
+        )
+        .unwrap();
+        assert_eq!(commas.to_string(), "[Group]\nList=a,b,");
+    }
+
+    #[test]
+    fn test_untrimmed_values() {
+        let file = "[Desktop Entry]
+Name=  spaced value ;other  
+";
+        let trimmed = DesktopFile::try_from(file).unwrap();
+        let untrimmed = DesktopFile::parse_with_options(
+            file.as_bytes(),
+            &ParserOptions::new().untrimmed_values(),
+        )
+        .unwrap();
+        let raw_values = vec![String::from("spaced value "), String::from("other  ")];
+
+        let entry = trimmed.get("Desktop Entry").unwrap().get("Name").unwrap();
+        assert_eq!(
+            entry.values,
+            vec![String::from("spaced value"), String::from("other")]
+        );
+        assert_eq!(entry.format.raw_values, Some(raw_values.clone()));
+
+        let entry = untrimmed.get("Desktop Entry").unwrap().get("Name").unwrap();
+        assert_eq!(entry.values, raw_values);
+    }
+
+    #[test]
+    fn test_custom_list_separator() {
+        let file = "[Settings]
+Sizes=small ,medium,large,
+Name=Hello; World
+";
+        let parsed = DesktopFile::parse_with_options(
+            file.as_bytes(),
+            &ParserOptions::new()
+                .untrimmed_values()
+                .with_list_separator(','),
+        )
+        .unwrap();
+        let group = parsed.get("Settings").unwrap();
+
+        assert_eq!(
+            group.get("Sizes").unwrap().values,
+            vec![
+                String::from("small "),
+                String::from("medium"),
+                String::from("large")
+            ]
+        );
+        assert_eq!(
+            group.get("Name").unwrap().values,
+            vec![String::from("Hello; World")]
+        );
+        assert_eq!(
+            parsed.to_string(),
+            "[Settings]
+Sizes=small ,medium,large,
+Name=Hello; World"
+        );
+
+        let parsed = DesktopFile::parse_with_options(
+            "[Settings]\nSizes=a·ķ\nNames=a·b·\n".as_bytes(),
+            &ParserOptions::new().with_list_separator('·'),
+        )
+        .unwrap();
+        let group = parsed.get("Settings").unwrap();
+        assert!(!group.get("Sizes").unwrap().format.trailing_separator);
+        assert_eq!(group.get("Sizes").unwrap().values, ["a", "ķ"]);
+        assert!(group.get("Names").unwrap().format.trailing_separator);
+        assert_eq!(group.get("Names").unwrap().values, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_gkeyfile() {
+        let input = "#Header\n[Settings]\n#  Sizes \nSizes=small ,large,\nName = App \n[Other]\n#\nKey=value\n";
+        let written = "#Header\n\n[Settings]\n#  Sizes \nSizes=small ,large,\nName=App \n\n[Other]\n#\nKey=value";
+        let options = ParserOptions::gkeyfile().with_list_separator(',');
+
+        let parsed = DesktopFile::parse_with_options(input.as_bytes(), &options).unwrap();
+        let group = parsed.get("Settings").unwrap();
+        assert_eq!(group.get("Sizes").unwrap().doc_comments(), ["  Sizes "]);
+        assert_eq!(group.get("Name").unwrap().values, ["App "]);
+        assert_eq!(parsed.to_string_with(&FormatOptions::gkeyfile()), written);
+
+        let recovered = DesktopFile::parse_with_options(
+            written.as_bytes(),
+            &options.clone().recover_invalid_lines(),
+        )
+        .unwrap();
+        assert_eq!(
+            recovered.to_string_with(&FormatOptions::gkeyfile()),
+            written
+        );
+        let (reported, _) = DesktopFile::parse_with_report(written.as_bytes(), &options).unwrap();
+        assert_eq!(reported, recovered);
+    }
+
     #[test]
     fn test_group_parsing() {
         let group = "[Desktop]
@@ -308,22 +866,26 @@ Hidden=false
                         Entry::Content(ContentEntry {
                             key: String::from("Type"),
                             values: vec![String::from("Application")],
-                            locale: None
+                            locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Content(ContentEntry {
                             key: String::from("Exec"),
                             values: vec![String::from("sh-test")],
-                            locale: None
+                            locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Content(ContentEntry {
                             key: String::from("Id"),
                             values: vec![String::from("4")],
-                            locale: None
+                            locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Content(ContentEntry {
                             key: String::from("Hidden"),
                             values: vec![String::from("false")],
-                            locale: None
+                            locale: None,
+                            format: EntryFormat::default(),
                         }),
                     ],
                 }
@@ -353,6 +915,33 @@ Hidden=false
         );
     }
 
+    #[test]
+    fn test_malformed_locales() {
+        use crate::error::LocaleError;
+
+        let check = |locale: &str| check_entry_locale(locale.as_bytes());
+
+        assert_eq!(
+            check("[sr_RS.UTF-8@latin]").unwrap().unwrap().to_string(),
+            "sr_RS.UTF-8@latin"
+        );
+        assert_eq!(check("=value"), Ok(None));
+        assert_eq!(check("[]"), Err(LocaleError::Empty));
+        assert_eq!(check("[_FR]"), Err(LocaleError::MissingLanguage));
+        assert_eq!(check("[fr.]"), Err(LocaleError::EmptyComponent('.')));
+        assert_eq!(check("[fr@]"), Err(LocaleError::EmptyComponent('@')));
+        assert_eq!(
+            check("[fr@euro_FR]"),
+            Err(LocaleError::UnexpectedCharacter('_'))
+        );
+        assert_eq!(check("[fr[x]]"), Err(LocaleError::NestedBracket));
+        assert_eq!(check("[fr\n]"), Err(LocaleError::Unterminated));
+        assert!(matches!(
+            parse_entry("Name[]=App\n".as_bytes()),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
     #[test]
     fn test_multi_values_parsing() {
         let values_without_final_semi = "World;Universe;all others";
@@ -395,27 +984,67 @@ Hidden=false
                             Entry::Content(ContentEntry {
                                 key: String::from("Type"),
                                 values: vec![String::from("Application")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("Exec"),
                                 values: vec![String::from("sh test")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("Id"),
                                 values: vec![String::from("4")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             }),
                             Entry::Content(ContentEntry {
                                 key: String::from("Hidden"),
                                 values: vec![String::from("false")],
-                                locale: None
+                                locale: None,
+                                format: EntryFormat::default(),
                             })
                         ]
                     })
                 ],
+                format: FileFormat::default(),
             })
         );
     }
+
+    #[test]
+    fn test_multiple_groups_round_trip() {
+        let file = "[Desktop Entry]
+Type=Application
+
+[org.example.Foo]
+Key=Value
+[Empty]
+[Last]
+";
+
+        let parsed = DesktopFile::try_from(file).unwrap();
+        assert_eq!(parsed.without_comments().len(), 4);
+        assert_eq!(parsed.to_string(), file);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_mime_type_keys() {
+        let file = "[Default Applications]\nimage/svg+xml=org.example.Viewer.desktop;\n";
+
+        assert!(parse_content_entry(b"image/svg+xml=org.example.Viewer.desktop;\n").is_err());
+        let desktop_file = DesktopFile::try_from(file).unwrap();
+        let group = desktop_file.get("Default Applications").unwrap();
+        assert!(group.get("image/svg+xml").is_err());
+
+        let parsed = DesktopFile::parse_mimeapps(file.as_bytes()).unwrap();
+        let group = parsed.get("Default Applications").unwrap();
+        assert_eq!(
+            group.get("image/svg+xml").unwrap().values,
+            vec![String::from("org.example.Viewer.desktop")]
+        );
+        assert_eq!(format!("{parsed}\n"), file);
+    }
 }
@@ -1,6 +1,28 @@
-use std::fmt::Display;
-
-use crate::error::Error;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    fmt::{Display, Write as _},
+    hash::{Hash, Hasher},
+    ops::Index,
+};
+
+use crate::{
+    error::Error,
+    parser::{
+        LocaleMatcher,
+        raw::{check_entry_locale, decode_backslashes, is_key_character, unescape_value},
+    },
+};
+
+/// The group which must be the first of desktop entry files.
+pub(crate) const MAIN_GROUP: &str = "Desktop Entry";
 
 /// Trait implemented by entries to dynamically check whether the entry is blank or a comment.
 pub trait CanBeComment {
@@ -10,6 +32,12 @@ pub trait CanBeComment {
     fn is_comment(&self) -> bool;
 }
 
+/// Trait implemented by the entries of an [EntrySet] to get the key they are found by.
+pub trait Keyed {
+    /// Returns the key of the entry, such as the key of a [ContentEntry] or the header of a [Group].
+    fn key(&self) -> &str;
+}
+
 /// Trait implemented by entites that contain multiple entries (eg. [Group] and [TopLevelEntry]).
 pub trait EntrySet<E> {
     /// Returns a vector of the entries that are not comments or blanks.
@@ -33,18 +61,87 @@ pub trait EntrySet<E> {
     fn get_mut(&mut self, key: &str) -> Result<&mut E, Error> {
         self.find_mut(key).ok_or(Error::NotFound(key.to_owned()))
     }
+
+    /// Find all the entries for this key, in order, as files may contain duplicates.
+    fn find_all(&self, key: &str) -> Vec<&E>
+    where
+        E: Keyed,
+    {
+        self.without_comments()
+            .into_iter()
+            .filter(|e| e.key() == key)
+            .collect()
+    }
+
+    /// Similar to [Self::find_all], but throws if the key is not found.
+    fn get_all(&self, key: &str) -> Result<Vec<&E>, Error>
+    where
+        E: Keyed,
+    {
+        let entries = self.find_all(key);
+        match entries.is_empty() {
+            true => Err(Error::NotFound(key.to_owned())),
+            false => Ok(entries),
+        }
+    }
+
+    /// Find all the entries for this key and returns them as mutable references, in order.
+    ///
+    /// By default, only the first entry is returned, as by [Self::find_mut],
+    /// so sets which may contain duplicates should implement it.
+    fn find_all_mut(&mut self, key: &str) -> Vec<&mut E> {
+        self.find_mut(key).into_iter().collect()
+    }
+
+    /// Returns the distinct keys of the entries, in order of first appearance.
+    fn keys<'a>(&'a self) -> Vec<&'a str>
+    where
+        E: Keyed + 'a,
+    {
+        distinct(self.without_comments().into_iter().map(Keyed::key))
+    }
+
+    /// Returns whether an entry has this key.
+    fn contains_key(&self, key: &str) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Returns the number of entries that are not comments or blanks, duplicates included.
+    ///
+    /// By default, the entries are collected by [Self::without_comments] to be counted.
+    fn len(&self) -> usize {
+        self.without_comments().len()
+    }
+
+    /// Returns whether there is no entry besides comments and blanks.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Collects the distinct keys, in order of first appearance.
+fn distinct<'a>(keys: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen = BTreeSet::new();
+    keys.filter(|key| seen.insert(*key)).collect()
 }
 
 /// Defines what options of a [Locale] are significant when searching for an entry.
+///
+/// A significant component must be the same, an insignificant one is ignored,
+/// and a wildcard one may be anything but is preferred when it is the same, see [Self::score].
 pub struct LocaleOptions<'a> {
     /// A reference to the locale to be found.
     pub locale: &'a Locale,
     /// Whether the country is significant.
     pub country: bool,
-    /// Whether the country is significant.
+    /// Whether the encoding is significant.
     pub encoding: bool,
-    /// Whether the country is significant.
+    /// Whether the modifier is significant.
     pub modifier: bool,
+    /// Whether any country matches, the same one being preferred. Ignored if the country is significant.
+    pub any_country: bool,
+    /// Whether any modifier matches, the same one being preferred. Ignored if the modifier is significant.
+    pub any_modifier: bool,
 }
 
 impl<'a> LocaleOptions<'a> {
@@ -55,6 +152,8 @@ impl<'a> LocaleOptions<'a> {
             country: false,
             encoding: false,
             modifier: false,
+            any_country: false,
+            any_modifier: false,
         }
     }
     /// Creates a new instance in which all options are significant.
@@ -64,17 +163,14 @@ impl<'a> LocaleOptions<'a> {
             country: true,
             encoding: true,
             modifier: true,
+            any_country: false,
+            any_modifier: false,
         }
     }
 
     /// Makes all options insignificant but the language.
     pub fn language_only(self) -> Self {
-        Self {
-            locale: self.locale,
-            country: false,
-            encoding: false,
-            modifier: false,
-        }
+        Self::new(self.locale)
     }
 
     /// Makes all options significant.
@@ -102,6 +198,56 @@ impl<'a> LocaleOptions<'a> {
         self
     }
 
+    /// Makes any country match, preferring the same one, instead of requiring it.
+    pub fn any_country(mut self) -> Self {
+        self.country = false;
+        self.any_country = true;
+        self
+    }
+
+    /// Makes any modifier match, preferring the same one, instead of requiring it.
+    pub fn any_modifier(mut self) -> Self {
+        self.modifier = false;
+        self.any_modifier = true;
+        self
+    }
+
+    /// Returns how closely the locale matches the options, the higher the better, or `None` if it does not match.
+    ///
+    /// The language must be the same, as must the significant components. Each component which is the same
+    /// and is significant or a wildcard adds to the score, the country weighing more than the modifier, then the encoding.
+    /// The locales are compared once canonicalized, see [Locale::equals_options].
+    /// ```
+    /// use freedesktop_rs::parser::models::{Locale, LocaleOptions};
+    ///
+    /// let wanted: Locale = "sr_RS@latin".parse().unwrap();
+    /// let options = LocaleOptions::new(&wanted).any_country().any_modifier();
+    /// let score = |locale: &str| options.score(&locale.parse().unwrap());
+    ///
+    /// assert!(score("sr_RS@latin") > score("sr_RS"));
+    /// assert!(score("sr_RS") > score("sr_ME@latin"));
+    /// assert!(score("sr_ME@latin") > score("sr"));
+    /// assert_eq!(score("hr_HR"), None);
+    /// ```
+    pub fn score(&self, locale: &Locale) -> Option<usize> {
+        if !locale.equals_options(self) {
+            return None;
+        }
+        let lhs = locale.canonicalize();
+        let rhs = self.locale.canonicalize();
+        let mut score = 1;
+        if (self.country || self.any_country) && lhs.country == rhs.country {
+            score += 4;
+        }
+        if (self.modifier || self.any_modifier) && lhs.modifiers == rhs.modifiers {
+            score += 2;
+        }
+        if self.encoding && lhs.encoding == rhs.encoding {
+            score += 1;
+        }
+        Some(score)
+    }
+
     /// Changes the locale.
     pub fn with_locale(mut self, locale: &'a Locale) -> Self {
         self.locale = locale;
@@ -112,7 +258,10 @@ impl<'a> LocaleOptions<'a> {
 /// A group of entries.
 ///
 /// This represents a section in a freedesktop file.
-#[derive(Debug, Eq, PartialEq, Clone)]
+///
+/// Groups are ordered by header, then by content.
+#[derive(Debug, Eq, PartialEq, Clone, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {
     /// The section name.
     pub header: String,
@@ -122,7 +271,7 @@ pub struct Group {
 }
 
 impl Display for Group {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(f, "[{}]", self.header)?;
 
         write_content(f, &self.content)
@@ -161,7 +310,7 @@ impl EntrySet<ContentEntry> for Group {
             .iter()
             .filter_map(|i| match i {
                 Entry::Content(content_entry) => Some(content_entry),
-                Entry::Comment(_) => None,
+                Entry::Comment(_) | Entry::Invalid(_) => None,
             })
             .find(|e| e.key == key)
     }
@@ -171,26 +320,73 @@ impl EntrySet<ContentEntry> for Group {
             .iter_mut()
             .filter_map(|i| match i {
                 Entry::Content(content_entry) => Some(content_entry),
-                Entry::Comment(_) => None,
+                Entry::Comment(_) | Entry::Invalid(_) => None,
             })
             .find(|e| e.key == key)
     }
+
+    fn find_all_mut(&mut self, key: &str) -> Vec<&mut ContentEntry> {
+        self.content
+            .iter_mut()
+            .filter_map(|i| match i {
+                Entry::Content(content_entry) if content_entry.key == key => Some(content_entry),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the distinct keys of the entries, regardless of their locale.
+    fn keys<'a>(&'a self) -> Vec<&'a str>
+    where
+        ContentEntry: 'a,
+    {
+        distinct(self.entries().map(|e| e.key.as_str()))
+    }
+
+    fn len(&self) -> usize {
+        self.entries().count()
+    }
+}
+
+impl Index<&str> for Group {
+    type Output = ContentEntry;
+
+    /// Returns the first entry for this key, see [EntrySet::find].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not in the group.
+    fn index(&self, key: &str) -> &ContentEntry {
+        self.find(key)
+            .unwrap_or_else(|| panic!("{key} not found in [{}]", self.header))
+    }
 }
 
 impl Group {
+    /// Creates an empty group with this header.
+    pub fn new<S: Into<String>>(header: S) -> Self {
+        Self {
+            header: header.into(),
+            content: vec![],
+        }
+    }
+
     /// Find the first entry for this key and locale, or `None` if no entry with this key was found.
+    ///
+    /// The first entry matching the options is returned, even if it is not localized,
+    /// see [Self::find_localized] for the precedence of the specification.
     pub fn find_with_locale(&self, key: &str, options: &LocaleOptions) -> Option<&ContentEntry> {
         self.content
             .iter()
             .filter_map(|i| match i {
                 Entry::Content(content_entry) => Some(content_entry),
-                Entry::Comment(_) => None,
+                Entry::Comment(_) | Entry::Invalid(_) => None,
             })
             .find(|e| {
                 e.key == key
                     && e.locale
                         .as_ref()
-                        .map_or(true, |locale| locale.equals_options(options))
+                        .is_none_or(|locale| locale.equals_options(options))
             })
     }
     /// Find the first entry for this key and locale and returns it as a mutable reference, or `None` if no entry with this key was found.
@@ -203,15 +399,237 @@ impl Group {
             .iter_mut()
             .filter_map(|i| match i {
                 Entry::Content(content_entry) => Some(content_entry),
-                Entry::Comment(_) => None,
+                Entry::Comment(_) | Entry::Invalid(_) => None,
             })
             .find(|e| {
                 e.key == key
                     && e.locale
                         .as_ref()
-                        .map_or(true, |locale| locale.equals_options(options))
+                        .is_none_or(|locale| locale.equals_options(options))
             })
     }
+
+    /// Find the translation of the key closest to the options, or the unlocalized entry if no translation matches,
+    /// see [LocaleOptions::score].
+    ///
+    /// The first entry is returned among the ones with the same score.
+    pub fn find_closest(&self, key: &str, options: &LocaleOptions) -> Option<&ContentEntry> {
+        let entries = || self.entries().filter(|e| e.key == key);
+        let mut closest: Option<(usize, &ContentEntry)> = None;
+        for entry in entries() {
+            let Some(score) = entry.locale.as_ref().and_then(|l| options.score(l)) else {
+                continue;
+            };
+            if closest.is_none_or(|(closest, _)| score > closest) {
+                closest = Some((score, entry));
+            }
+        }
+        closest
+            .map(|(_, entry)| entry)
+            .or_else(|| entries().find(|e| e.locale.is_none()))
+    }
+
+    /// Find the translation of the key in the locale, following the precedence of the specification:
+    /// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, `lang`, then the unlocalized key, see [LocaleMatcher].
+    ///
+    /// The encoding of the locales is ignored. Returns `None` if the key has neither a matching translation nor a default value.
+    /// ```
+    /// use freedesktop_rs::parser::models::{DesktopFile, Locale};
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName[sr]=Aplikacija\nName=App\nName[sr@latin]=Apl\n").unwrap();
+    /// let group = &file["Desktop Entry"];
+    /// let name = |locale: &str| &group.find_localized("Name", &locale.parse().unwrap()).unwrap().values[0];
+    ///
+    /// assert_eq!(name("sr_RS.UTF-8@latin"), "Apl");
+    /// assert_eq!(name("sr_RS"), "Aplikacija");
+    /// assert_eq!(name("fr_FR"), "App");
+    /// ```
+    pub fn find_localized(&self, key: &str, locale: &Locale) -> Option<&ContentEntry> {
+        self.find_preferred(key, core::slice::from_ref(locale))
+    }
+
+    /// Find the translation of the key in the first of the locales which has one, by order of preference,
+    /// then the unlocalized key, see [Self::find_localized].
+    ///
+    /// The locales are usually the ones of the `LANGUAGE` variable, see [Locale::parse_list].
+    /// ```
+    /// use freedesktop_rs::parser::models::{DesktopFile, Locale};
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\nName[de]=Anwendung\nName[fr]=Appli\n").unwrap();
+    /// let name = file["Desktop Entry"].find_preferred("Name", &Locale::parse_list("es:fr_CA:de"));
+    /// assert_eq!(name.unwrap().values, ["Appli"]);
+    /// ```
+    pub fn find_preferred(&self, key: &str, locales: &[Locale]) -> Option<&ContentEntry> {
+        LocaleMatcher::new(locales).find(self, key)
+    }
+
+    /// Similar to [Self::find_preferred], but fails with [Error::NotFound] if the key is not found.
+    pub fn get_preferred(&self, key: &str, locales: &[Locale]) -> Result<&ContentEntry, Error> {
+        self.find_preferred(key, locales)
+            .ok_or(Error::NotFound(key.to_owned()))
+    }
+
+    /// Similar to [Self::find_localized], but fails with [Error::NotFound] if the key is not found.
+    pub fn get_localized(&self, key: &str, locale: &Locale) -> Result<&ContentEntry, Error> {
+        self.find_localized(key, locale)
+            .ok_or(Error::NotFound(key.to_owned()))
+    }
+
+    /// Returns every entry of the key along with its locale, `None` for the unlocalized one, in the order of the group.
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\nName[fr]=Appli\nName[de]=Anwendung\n").unwrap();
+    /// let translated: Vec<String> = file["Desktop Entry"]
+    ///     .localizations("Name")
+    ///     .filter_map(|(locale, _)| locale.map(|l| l.to_string()))
+    ///     .collect();
+    ///
+    /// assert_eq!(translated, ["fr", "de"]);
+    /// ```
+    pub fn localizations<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Iterator<Item = (Option<&'a Locale>, &'a ContentEntry)> {
+        self.entries()
+            .filter(move |e| e.key == key)
+            .map(|e| (e.locale.as_ref(), e))
+    }
+
+    /// Sets the value of the key, escaping it so that it is written as is.
+    ///
+    /// The first entry of the key is updated in place and its duplicates are removed,
+    /// otherwise a new entry is added at the end of the group.
+    pub fn set<T: Display>(&mut self, key: &str, value: T) {
+        self.set_values(key, None, vec![value.to_string()], false);
+    }
+
+    /// Sets the values of the key, escaping them so that they are written as is.
+    ///
+    /// Like [Self::set], the first entry of the key is updated in place and its duplicates are removed.
+    /// A new entry is followed by a separator, as recommended by the specification for lists.
+    pub fn set_list<T: Display>(&mut self, key: &str, values: impl IntoIterator<Item = T>) {
+        let values = values.into_iter().map(|v| v.to_string()).collect();
+        self.set_values(key, None, values, true);
+    }
+
+    /// Sets the value of the key for the locale, escaping it so that it is written as is.
+    ///
+    /// Like [Self::set], the first entry of the key with this exact locale is updated in place and its duplicates are removed.
+    /// A new translation is added right after the last entry of the key, so that the translations follow their unlocalized entry.
+    pub fn set_localized<T: Display>(&mut self, key: &str, locale: Locale, value: T) {
+        self.set_values(key, Some(locale), vec![value.to_string()], false);
+    }
+
+    /// Removes all the entries of the key, including its translations, and returns them.
+    pub fn remove(&mut self, key: &str) -> Vec<ContentEntry> {
+        let mut removed = vec![];
+        let mut kept = Vec::with_capacity(self.content.len());
+        for entry in self.content.drain(..) {
+            match entry {
+                Entry::Content(content) if content.key == key => removed.push(content),
+                entry => kept.push(entry),
+            }
+        }
+        self.content = kept;
+        removed
+    }
+
+    /// Inserts the entry at the position in the content of the group, comments included.
+    ///
+    /// The other entries with the same key and locale are removed, as keys must be unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the content.
+    pub fn insert_at(&mut self, index: usize, entry: ContentEntry) {
+        let duplicate = |e: &Entry| matches!(e, Entry::Content(c) if c.key == entry.key && c.locale == entry.locale);
+        let before = self.content[..index]
+            .iter()
+            .filter(|e| duplicate(e))
+            .count();
+        self.content.retain(|e| !duplicate(e));
+        self.content.insert(index - before, Entry::Content(entry));
+    }
+
+    /// Edits a copy of the group with the closure, and applies the changes only if it succeeds.
+    ///
+    /// This keeps related keys consistent, as the group is left untouched if any edit fails:
+    /// ```
+    /// use freedesktop_rs::{error::Error, parser::models::{EntrySet, Group}};
+    ///
+    /// let mut group: Group = "[Desktop Entry]\nActions=new;".parse().unwrap();
+    /// let result = group.transaction(|tx| {
+    ///     tx.set_list("Actions", ["open"]);
+    ///     tx.get("Exec")?;
+    ///     Ok::<_, Error>(())
+    /// });
+    ///
+    /// assert!(result.is_err());
+    /// assert_eq!(group.get("Actions").unwrap().values, ["new"]);
+    /// ```
+    pub fn transaction<T, E, F>(&mut self, edit: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Group) -> Result<T, E>,
+    {
+        let mut copy = self.clone();
+        let result = edit(&mut copy)?;
+        *self = copy;
+        Ok(result)
+    }
+
+    fn set_values(&mut self, key: &str, locale: Option<Locale>, values: Vec<String>, list: bool) {
+        let same_entry =
+            |e: &Entry| matches!(e, Entry::Content(c) if c.key == key && c.locale == locale);
+        let Some(position) = self.content.iter().position(same_entry) else {
+            let index = match locale {
+                Some(_) => self
+                    .content
+                    .iter()
+                    .rposition(|e| matches!(e, Entry::Content(c) if c.key == key))
+                    .map_or(self.content.len(), |last| last + 1),
+                None => self.content.len(),
+            };
+            self.content.insert(
+                index,
+                Entry::Content(ContentEntry {
+                    key: key.to_owned(),
+                    values: values
+                        .iter()
+                        .map(|v| escape_kept_value(v, list.then_some(';')))
+                        .collect(),
+                    locale,
+                    format: EntryFormat {
+                        trailing_separator: list,
+                        ..EntryFormat::default()
+                    },
+                }),
+            );
+            return;
+        };
+
+        // Remove the duplicates after the updated entry
+        let mut index = 0;
+        self.content.retain(|e| {
+            index += 1;
+            index <= position + 1 || !same_entry(e)
+        });
+        if let Entry::Content(entry) = &mut self.content[position] {
+            // Only the separators of lists are escaped, as GLib rejects `\;` in other values
+            let separator = list.then(|| entry.format.list_separator.unwrap_or(';'));
+            entry.values = match entry.format.escapes_decoded {
+                true => values,
+                false => values
+                    .iter()
+                    .map(|v| escape_kept_value(v, separator))
+                    .collect(),
+            };
+            entry.format.raw_values = None;
+            if !list {
+                entry.format.trailing_separator = false;
+            }
+        }
+    }
 }
 
 /// Content of a section.
@@ -220,12 +638,18 @@ impl Group {
 pub type GroupContent = Vec<Entry>;
 
 /// An entry in the file
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Entry {
     /// A key-values entry
     Content(ContentEntry),
     /// A comment or blank line
     Comment(CommentEntry),
+    /// A line that could not be parsed, kept byte for byte, as it may not even be valid UTF-8.
+    ///
+    /// It is written as is by [DesktopFile::to_bytes], but lossily by [Display].
+    /// Only produced when [ParserOptions::recover_invalid_lines](crate::parser::ParserOptions::recover_invalid_lines) is set.
+    Invalid(Vec<u8>),
 }
 
 impl From<CommentEntry> for Entry {
@@ -241,18 +665,31 @@ impl From<ContentEntry> for Entry {
 }
 
 impl Display for Entry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Entry::Content(content_entry) => content_entry.fmt(f),
             Entry::Comment(comment_entry) => comment_entry.fmt(f),
+            Entry::Invalid(line) => write!(f, "{}", String::from_utf8_lossy(line)),
         }
     }
 }
 
+impl Keyed for ContentEntry {
+    fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl Keyed for Group {
+    fn key(&self) -> &str {
+        &self.header
+    }
+}
+
 impl CanBeComment for Entry {
     fn is_comment(&self) -> bool {
         match self {
-            Entry::Content(_) => false,
+            Entry::Content(_) | Entry::Invalid(_) => false,
             Entry::Comment(_) => true,
         }
     }
@@ -268,6 +705,7 @@ impl CanBeComment for Entry {
 
 /// An entry at the root of the file.
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TopLevelEntry {
     /// A section as per the Freedesktop specification.
     Group(Group),
@@ -289,7 +727,7 @@ impl From<Group> for TopLevelEntry {
 }
 
 impl Display for TopLevelEntry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             TopLevelEntry::Group(group) => group.fmt(f),
             TopLevelEntry::Comment(comment_entry) => comment_entry.fmt(f),
@@ -315,7 +753,8 @@ impl CanBeComment for TopLevelEntry {
 }
 
 /// A comment or a blank line.
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CommentEntry {
     /// A textual comment. Contains the line content.
     Text(String),
@@ -324,7 +763,7 @@ pub enum CommentEntry {
 }
 
 impl Display for CommentEntry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             CommentEntry::Text(s) => write!(f, "# {s}"),
 
@@ -344,7 +783,11 @@ impl CommentEntry {
 }
 
 /// A key-values entry.
-#[derive(Debug, Eq, PartialEq, Clone)]
+///
+/// Two entries are equal if their key, values and locale are equal, regardless of their [EntryFormat].
+/// They are ordered by key, then by locale with the unlocalized entry first, then by values.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContentEntry {
     /// The key of the entry.
     pub key: String,
@@ -354,24 +797,377 @@ pub struct ContentEntry {
 
     /// The potential locale of the entry.
     pub locale: Option<Locale>,
+
+    /// How the entry was written in the parsed input.
+    pub format: EntryFormat,
+}
+
+impl PartialEq for ContentEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.values == other.values && self.locale == other.locale
+    }
+}
+
+impl Eq for ContentEntry {}
+
+impl Hash for ContentEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+        self.values.hash(state);
+        self.locale.hash(state);
+    }
+}
+
+impl PartialOrd for ContentEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ContentEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .cmp(&other.key)
+            .then_with(|| self.locale.cmp(&other.locale))
+            .then_with(|| self.values.cmp(&other.values))
+    }
+}
+
+impl ContentEntry {
+    /// Creates an entry with a single value, which is escaped. Its `;` are kept, as they only separate the values of lists.
+    ///
+    /// The key may have a locale between brackets, such as `Name[fr]`.
+    /// Fails with [Error::InvalidValue] if the key or its locale is not valid, so the entry is always written as a valid line.
+    /// ```
+    /// use freedesktop_rs::parser::models::ContentEntry;
+    ///
+    /// let entry = ContentEntry::new("Comment[fr]", "Première ligne\nSeconde ligne").unwrap();
+    /// assert_eq!(entry.to_string(), "Comment[fr]=Première ligne\\nSeconde ligne");
+    /// assert!(ContentEntry::new("Comment=", "value").is_err());
+    /// ```
+    pub fn new<T: Display>(key: &str, value: T) -> Result<Self, Error> {
+        let value = escape_kept_value(&value.to_string(), None);
+        Self::validated(key, vec![value], false)
+    }
+
+    /// Creates an entry with a list of values, which are escaped and followed by a separator.
+    ///
+    /// The key is validated as by [Self::new].
+    pub fn new_list<T: Display>(
+        key: &str,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<Self, Error> {
+        let values = values
+            .into_iter()
+            .map(|v| escape_kept_value(&v.to_string(), Some(';')))
+            .collect();
+        Self::validated(key, values, true)
+    }
+
+    fn validated(key: &str, values: Vec<String>, list: bool) -> Result<Self, Error> {
+        let invalid =
+            |reason: String| Error::InvalidValue(format!("{key} is not a valid key: {reason}"));
+        let (name, bracketed) = match key.find('[') {
+            Some(start) => (&key[..start], &key[start..]),
+            None => (key, ""),
+        };
+        if name.is_empty() {
+            return Err(invalid(String::from("the key is empty")));
+        }
+        if let Some(c) = name.chars().find(|c| !is_key_character(*c)) {
+            return Err(invalid(format!("unexpected character {c:?}")));
+        }
+        let locale =
+            check_entry_locale(bracketed.as_bytes()).map_err(|e| invalid(e.to_string()))?;
+        if locale.is_some() && bracketed.find(']') != Some(bracketed.len() - 1) {
+            return Err(invalid(String::from(
+                "unexpected characters after the locale",
+            )));
+        }
+
+        Ok(Self {
+            key: name.to_owned(),
+            values,
+            locale,
+            format: EntryFormat {
+                trailing_separator: list,
+                ..EntryFormat::default()
+            },
+        })
+    }
+
+    /// Returns the value of an entry of type `string` or `localestring`.
+    ///
+    /// The value of a parsed entry is read whole from its [raw values](EntryFormat::raw_values), with its separators,
+    /// as they only separate the values of lists.
+    /// Fails with [Error::InvalidValue] if the entry was built with several values.
+    /// ```
+    /// use freedesktop_rs::parser::models::ContentEntry;
+    ///
+    /// let exec: ContentEntry = "Exec=sh -c 'a; b'".parse().unwrap();
+    /// assert_eq!(exec.as_string().unwrap(), "sh -c 'a; b'");
+    /// ```
+    pub fn as_string(&self) -> Result<Cow<'_, str>, Error> {
+        match (self.values.as_slice(), &self.format.raw_values) {
+            ([value], _) if !self.format.trailing_separator => Ok(Cow::Borrowed(value)),
+            (_, Some(raw_values)) => {
+                let value = self.joined(raw_values);
+                Ok(Cow::Owned(match self.format.escapes_decoded {
+                    true => unescape_value(&value),
+                    false => decode_backslashes(&value),
+                }))
+            }
+            ([value], None) => Ok(Cow::Borrowed(value)),
+            (values, None) => Err(Error::InvalidValue(format!(
+                "{} has {} values instead of one",
+                self.key,
+                values.len()
+            ))),
+        }
+    }
+
+    /// Joins the raw values into the value as written, trimmed as the values.
+    fn joined(&self, raw_values: &[String]) -> String {
+        let separator = self.format.list_separator.unwrap_or(';');
+        let mut value = raw_values.join(&separator.to_string());
+        if self.format.trailing_separator {
+            value.push(separator);
+        }
+        if self.format.values_trimmed {
+            value = value.trim().to_owned();
+        }
+        value
+    }
+
+    /// Returns the comment lines attached to the entry, see [DesktopFile::attach_comments].
+    pub fn doc_comments(&self) -> &[String] {
+        &self.format.doc_comments
+    }
+
+    /// Returns the values of an entry of type `strings` or `localestrings`.
+    pub fn as_string_list(&self) -> &[String] {
+        &self.values
+    }
+
+    /// Returns the value of an entry of type `boolean`, which is either `true` or `false`.
+    pub fn as_bool(&self) -> Result<bool, Error> {
+        match &*self.as_string()? {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            value => Err(self.invalid_value(value, "a boolean")),
+        }
+    }
+
+    /// Returns the value of an entry of type `numeric`, if it is an integer.
+    pub fn as_i64(&self) -> Result<i64, Error> {
+        let value = self.as_string()?;
+        value
+            .parse()
+            .map_err(|_| self.invalid_value(&value, "an integer"))
+    }
+
+    /// Returns the value of an entry of type `numeric`.
+    ///
+    /// Numbers are parsed regardless of the locale, with a `.` as decimal separator, as in the C locale.
+    pub fn as_f64(&self) -> Result<f64, Error> {
+        let value = self.as_string()?;
+        value
+            .parse()
+            .map_err(|_| self.invalid_value(&value, "a number"))
+    }
+
+    fn invalid_value(&self, value: &str, expected: &str) -> Error {
+        Error::InvalidValue(format!("{}={value} is not {expected}", self.key))
+    }
+}
+
+/// Formatting details of a [ContentEntry] as found in the parsed input.
+///
+/// An entry created by hand has no formatting details.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryFormat {
+    /// The values as written in the input, before trimming, with all their escape sequences.
+    ///
+    /// The values which were not changed are written back from them, so that `\\n` is not written as `\n`.
+    pub raw_values: Option<Vec<String>>,
+
+    /// Whether the values were trimmed from the [raw values](Self::raw_values).
+    pub values_trimmed: bool,
+
+    /// The character separating the values, if it is not `;`.
+    pub list_separator: Option<char>,
+
+    /// Whether the values were followed by a separator, as recommended by the specification for lists.
+    pub trailing_separator: bool,
+
+    /// The `=` between the key and the values with its surrounding whitespaces, such as ` = `, if it is not a bare `=`.
+    pub assignment: Option<String>,
+
+    /// Whether the escape sequences of the values were decoded, in which case they are escaped again when written.
+    pub escapes_decoded: bool,
+
+    /// The comment lines written right before the entry, without their `#`, when they are attached to it.
+    ///
+    /// They move with the entry and are written before it, see [DesktopFile::attach_comments].
+    pub doc_comments: Vec<String>,
 }
 
 impl Display for ContentEntry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for comment in &self.format.doc_comments {
+            writeln!(f, "# {comment}")?;
+        }
         write!(f, "{}", self.key)?;
         if let Some(locale) = &self.locale {
             write!(f, "[")?;
             locale.fmt(f)?;
             write!(f, "]")?;
         }
-        write!(f, "={}", self.values.join(";"))
+        let assignment = self.format.assignment.as_deref().unwrap_or("=");
+        let separator = self.format.list_separator.unwrap_or(';');
+        // A single value is not a list, so its separators are written as is
+        let list = self.values.len() > 1 || self.format.trailing_separator;
+        if self.format.escapes_decoded {
+            let values: Vec<_> = self
+                .values
+                .iter()
+                .map(|v| escape_value(v, list.then_some(separator)))
+                .collect();
+            write!(f, "{assignment}{}", values.join(&separator.to_string()))?;
+        } else {
+            let values = self.written_values(list.then_some(separator));
+            write!(f, "{assignment}{}", values.join(&separator.to_string()))?;
+        }
+        if self.format.trailing_separator && !self.values.is_empty() {
+            write!(f, "{separator}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ContentEntry {
+    /// Returns the value as written in the file, if the value at this index was not changed since it was parsed,
+    /// so that its escape sequences are written back as they were.
+    fn written_value(&self, index: usize, value: &str) -> Option<&str> {
+        let raw = self.format.raw_values.as_ref()?.get(index)?;
+        let raw = match self.format.values_trimmed {
+            true => raw.trim(),
+            false => raw,
+        };
+        (decode_backslashes(raw) == value).then_some(raw)
+    }
+
+    /// Returns the values whose escape sequences are kept with these sequences as written in the file.
+    fn written_values(&self, separator: Option<char>) -> Vec<Cow<'_, str>> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match self.written_value(i, v) {
+                Some(written) => Cow::Borrowed(written),
+                None => Cow::Owned(escape_raw_value(v, separator)),
+            })
+            .collect()
+    }
+
+    /// Returns the values with their escape sequences decoded, as by [ParserOptions::decode_escapes](crate::parser::ParserOptions::decode_escapes).
+    pub(crate) fn decoded_values(&self) -> Vec<String> {
+        match self.format.escapes_decoded {
+            true => self.values.clone(),
+            false => self
+                .written_values(self.format.list_separator.or(Some(';')))
+                .iter()
+                .map(|v| unescape_value(v))
+                .collect(),
+        }
+    }
+
+    /// Returns the value of an entry of type `string` or `localestring` with its escape sequences decoded, see [Self::as_string].
+    pub(crate) fn decoded_string(&self) -> Result<Cow<'_, str>, Error> {
+        let written = match (self.values.as_slice(), &self.format.raw_values) {
+            _ if self.format.escapes_decoded => return self.as_string(),
+            ([_], _) if !self.format.trailing_separator => self.written_values(None).remove(0),
+            (_, Some(raw_values)) => Cow::Owned(self.joined(raw_values)),
+            _ => return self.as_string(),
+        };
+        Ok(match written.contains('\\') {
+            true => Cow::Owned(unescape_value(&written)),
+            false => written,
+        })
+    }
+}
+
+/// Escapes a value as the values parsed by default, whose escape sequences are kept as written but for `\\`,
+/// which is read as a backslash, see [escape_value].
+pub(crate) fn escape_kept_value(value: &str, separator: Option<char>) -> String {
+    decode_backslashes(&escape_value(value, separator))
+}
+
+/// Escapes a value so that it can be written in a file, reversing [crate::parser::raw::unescape_value].
+///
+/// Leading and trailing spaces are written as `\s` so that they are not trimmed when the file is read again,
+/// and the separator of lists, if any, is escaped.
+pub fn escape_value(value: &str, separator: Option<char>) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let leading_spaces = value.len() - value.trim_start_matches(' ').len();
+    let trailing_spaces = value.trim_end_matches(' ').len().max(leading_spaces);
+
+    for (i, c) in value.char_indices() {
+        match c {
+            ' ' if i < leading_spaces || i >= trailing_spaces => escaped.push_str("\\s"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c if Some(c) == separator => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes what cannot be written as is in a value whose escape sequences are kept as written.
+///
+/// The existing escape sequences are kept, while line breaks, unescaped separators and backslashes
+/// which do not start an escape sequence are escaped, so that the value is read back as a single value on a single line.
+fn escape_raw_value(value: &str, separator: Option<char>) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next_if(|&next| {
+                matches!(next, 's' | 'n' | 't' | 'r' | ';') || Some(next) == separator
+            }) {
+                Some(next) => {
+                    escaped.push('\\');
+                    escaped.push(next);
+                }
+                None => escaped.push_str("\\\\"),
+            },
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c if Some(c) == separator => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
     }
+
+    escaped
 }
 
 /// A locale of an entry.
 ///
 /// If given to an entry, the only required argument is the language. Everything else is optional.
-#[derive(Debug, Eq, PartialEq, Clone)]
+///
+/// Locales are ordered by language, then by country, modifier and encoding, the absent ones first.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct Locale {
     /// The language of the bound value.
     pub lang: String,
@@ -385,24 +1181,74 @@ pub struct Locale {
 
 impl Locale {
     /// Check whether this locale respects the options
+    ///
+    /// The locales are compared once canonicalized, so the case of the country does not matter,
+    /// and a missing encoding is the same as UTF-8 since the values are always in UTF-8, see [Self::canonicalize].
     pub fn equals_options(&self, options: &LocaleOptions) -> bool {
-        let rhs = options.locale;
-        let mut res = self.lang == rhs.lang;
+        let lhs = self.canonicalize();
+        let rhs = options.locale.canonicalize();
+        let mut res = lhs.lang == rhs.lang;
         if options.country {
-            res &= self.country == rhs.country;
+            res &= lhs.country == rhs.country;
         }
         if options.encoding {
-            res &= self.encoding == rhs.encoding;
+            res &= lhs.encoding == rhs.encoding;
         }
         if options.modifier {
-            res &= self.modifiers == rhs.modifiers;
+            res &= lhs.modifiers == rhs.modifiers;
         }
         res
     }
+
+    /// Returns the locale written the canonical way: the language in lowercase, the country in uppercase,
+    /// and without its encoding if it is UTF-8, in any spelling.
+    /// ```
+    /// use freedesktop_rs::parser::models::Locale;
+    ///
+    /// let locale: Locale = "EN_us.utf8@Latin".parse().unwrap();
+    /// assert_eq!(locale.canonicalize().to_string(), "en_US@Latin");
+    /// ```
+    pub fn canonicalize(&self) -> Locale {
+        let utf8 = |encoding: &String| {
+            let encoding = encoding.to_ascii_lowercase();
+            encoding == "utf-8" || encoding == "utf8"
+        };
+        Locale {
+            lang: self.lang.to_ascii_lowercase(),
+            country: self.country.as_ref().map(|c| c.to_ascii_uppercase()),
+            encoding: self.encoding.clone().filter(|e| !utf8(e)),
+            modifiers: self.modifiers.clone(),
+        }
+    }
+
+    /// Parses a colon-separated list of locales, such as the `LANGUAGE` variable `sr@latin:de_DE:de`.
+    ///
+    /// The empty and malformed locales are left out.
+    pub fn parse_list(list: &str) -> Vec<Locale> {
+        list.split(':')
+            .filter_map(|locale| locale.parse().ok())
+            .collect()
+    }
+}
+
+impl PartialOrd for Locale {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Locale {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.lang
+            .cmp(&other.lang)
+            .then_with(|| self.country.cmp(&other.country))
+            .then_with(|| self.modifiers.cmp(&other.modifiers))
+            .then_with(|| self.encoding.cmp(&other.encoding))
+    }
 }
 
 impl Display for Locale {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.lang)?;
         if let Some(country) = &self.country {
             write!(f, "_{}", country.to_uppercase())?;
@@ -423,10 +1269,53 @@ impl Display for Locale {
 /// The representation of a Freedesktop file, which contains [TopLevelEntry].
 ///
 /// This struct is used to parse raw data, see its implementations of [From<...>] for more information.
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DesktopFile {
     /// The top-level entries of the file.
     pub content: Vec<TopLevelEntry>,
+    /// Formatting details of the parsed input, which are ignored when comparing files.
+    pub format: FileFormat,
+}
+
+impl PartialEq for DesktopFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
+}
+
+impl Eq for DesktopFile {}
+
+/// Formatting details of a [DesktopFile] as found in the parsed input.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileFormat {
+    /// Whether the input started with a UTF-8 byte order mark, which is written back.
+    pub byte_order_mark: bool,
+
+    /// The line ending of the input, detected from its first line, with which the file is written back.
+    pub line_ending: LineEnding,
+}
+
+/// The characters ending the lines of a file.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEnding {
+    /// A line feed, `\n`, as used on Unix systems.
+    #[default]
+    Lf,
+    /// A carriage return followed by a line feed, `\r\n`, as used on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the characters of the line ending.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
 }
 
 impl EntrySet<Group> for DesktopFile {
@@ -466,24 +1355,267 @@ impl EntrySet<Group> for DesktopFile {
             })
             .find(|g| g.header == header)
     }
+
+    fn find_all_mut(&mut self, header: &str) -> Vec<&mut Group> {
+        self.content
+            .iter_mut()
+            .filter_map(|i| match i {
+                TopLevelEntry::Group(group) if group.header == header => Some(group),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the distinct headers of the groups.
+    fn keys<'a>(&'a self) -> Vec<&'a str>
+    where
+        Group: 'a,
+    {
+        distinct(self.groups().map(|g| g.header.as_str()))
+    }
+
+    fn len(&self) -> usize {
+        self.groups().count()
+    }
+}
+
+impl Index<&str> for DesktopFile {
+    type Output = Group;
+
+    /// Returns the first group with this header, see [EntrySet::find]:
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\n").unwrap();
+    /// assert_eq!(file["Desktop Entry"]["Name"].values, ["App"]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file has no such group.
+    fn index(&self, header: &str) -> &Group {
+        self.find(header)
+            .unwrap_or_else(|| panic!("[{header}] not found"))
+    }
+}
+
+impl DesktopFile {
+    /// Creates an empty file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the group at the end of the file, and returns it.
+    ///
+    /// Fails with [Error::InvalidValue] if a group with the same header exists, as headers must be unique.
+    pub fn add_group(&mut self, group: Group) -> Result<&mut Group, Error> {
+        if self.find(&group.header).is_some() {
+            return Err(Error::InvalidValue(format!(
+                "[{}] already exists",
+                group.header
+            )));
+        }
+        self.content.push(TopLevelEntry::Group(group));
+        match self.content.last_mut() {
+            Some(TopLevelEntry::Group(group)) => Ok(group),
+            _ => unreachable!("The group was just added"),
+        }
+    }
+
+    /// Removes the group with this header, and returns it.
+    pub fn remove_group(&mut self, header: &str) -> Option<Group> {
+        let position = self
+            .content
+            .iter()
+            .position(|tle| matches!(tle, TopLevelEntry::Group(group) if group.header == header))?;
+        match self.content.remove(position) {
+            TopLevelEntry::Group(group) => Some(group),
+            TopLevelEntry::Comment(_) => unreachable!("The entry is a group"),
+        }
+    }
+
+    /// Changes the header of a group, keeping its position.
+    ///
+    /// Fails with [Error::NotFound] if there is no such group,
+    /// or with [Error::InvalidValue] if a group with the new header exists.
+    pub fn rename_group(&mut self, header: &str, new_header: &str) -> Result<(), Error> {
+        if header != new_header && self.find(new_header).is_some() {
+            return Err(Error::InvalidValue(format!(
+                "[{new_header}] already exists"
+            )));
+        }
+        self.get_mut(header)?.header = new_header.to_owned();
+        Ok(())
+    }
+
+    /// Returns the group with this header, adding an empty one at the end of the file if there is none.
+    pub fn ensure_group(&mut self, header: &str) -> &mut Group {
+        if self.find(header).is_some() {
+            return self.find_mut(header).expect("The group exists");
+        }
+        self.add_group(Group {
+            header: header.to_owned(),
+            content: vec![],
+        })
+        .expect("The group does not exist")
+    }
+
+    /// Returns the primary group of the file: `[Desktop Entry]` if there is one, or else the first group.
+    ///
+    /// Files such as `index.theme` or `.trashinfo` files have another primary group, which is the first one.
+    pub fn main_group(&self) -> Option<&Group> {
+        self.find(MAIN_GROUP).or_else(|| self.groups().next())
+    }
+
+    /// Similar to [Self::main_group], but the group can be modified.
+    pub fn main_group_mut(&mut self) -> Option<&mut Group> {
+        match self.find(MAIN_GROUP) {
+            Some(_) => self.find_mut(MAIN_GROUP),
+            None => self.groups_mut().next(),
+        }
+    }
+
+    /// Returns the groups whose header starts with the prefix, in order,
+    /// such as the `[Desktop Action new]` groups for the `Desktop Action ` prefix.
+    pub fn groups_with_prefix(&self, prefix: &str) -> Vec<&Group> {
+        self.groups()
+            .filter(|group| group.header.starts_with(prefix))
+            .collect()
+    }
+
+    /// Similar to [Self::groups_with_prefix], but the groups can be modified.
+    pub fn groups_with_prefix_mut(&mut self, prefix: &str) -> Vec<&mut Group> {
+        self.groups_mut()
+            .filter(|group| group.header.starts_with(prefix))
+            .collect()
+    }
 }
 
 impl Display for DesktopFile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write_content(f, &self.content)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.format.byte_order_mark {
+            write!(f, "\u{feff}")?;
+        }
+        match self.format.line_ending {
+            LineEnding::Lf => write_content(f, &self.content),
+            LineEnding::CrLf => write!(CrLfWriter(f), "{}", Content(&self.content)),
+        }
     }
 }
 
-fn write_content<T: CanBeComment + Display>(
-    f: &mut std::fmt::Formatter<'_>,
+/// The top-level entries of a file, written with line feeds.
+struct Content<'a>(&'a [TopLevelEntry]);
+
+impl Display for Content<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_content(f, self.0)
+    }
+}
+
+/// Writes the line feeds as `\r\n`. Values never contain line feeds, as they are escaped.
+struct CrLfWriter<'a, 'b>(&'a mut core::fmt::Formatter<'b>);
+
+impl core::fmt::Write for CrLfWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.0.write_str(first)?;
+        }
+        for line in lines {
+            self.0.write_str("\r\n")?;
+            self.0.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the written representation of an item already ends with a line break.
+pub(crate) trait EndsWithLineBreak {
+    fn ends_with_line_break(&self) -> bool;
+}
+
+impl EndsWithLineBreak for Entry {
+    fn ends_with_line_break(&self) -> bool {
+        self.is_blank()
+    }
+}
+
+impl EndsWithLineBreak for TopLevelEntry {
+    fn ends_with_line_break(&self) -> bool {
+        match self {
+            // The header is always followed by a line break
+            TopLevelEntry::Group(group) => group.content.last().is_none_or(Entry::is_blank),
+            TopLevelEntry::Comment(comment_entry) => comment_entry.is_blank(),
+        }
+    }
+}
+
+impl DesktopFile {
+    /// Returns the file as written by [Display], but with its [invalid lines](Entry::Invalid) as they were read,
+    /// even if they are not valid UTF-8.
+    /// ```
+    /// use freedesktop_rs::parser::{ParserOptions, models::DesktopFile};
+    ///
+    /// let input = b"[Desktop Entry]\r\nName=App\r\nName[fr_]=Appli\r\n";
+    /// let file = DesktopFile::parse_with_options(input, &ParserOptions::new().recover_invalid_lines()).unwrap();
+    ///
+    /// assert_eq!(file.to_bytes(), input.trim_ascii_end());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        if self.format.byte_order_mark {
+            bytes.extend_from_slice("\u{feff}".as_bytes());
+        }
+        let mut peekable = self.content.iter().peekable();
+        while let Some(item) = peekable.next() {
+            bytes.extend(item.to_bytes(self.format.line_ending));
+            if peekable.peek().is_some() && !item.ends_with_line_break() {
+                bytes.extend_from_slice(self.format.line_ending.as_str().as_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+impl TopLevelEntry {
+    /// Returns the entry as written by [Display], with the invalid lines of groups as they were read.
+    pub(crate) fn to_bytes(&self, line_ending: LineEnding) -> Vec<u8> {
+        let TopLevelEntry::Group(group) = self else {
+            return with_line_ending(self.to_string(), line_ending);
+        };
+        let mut bytes = with_line_ending(format!("[{}]\n", group.header), line_ending);
+        let mut peekable = group.content.iter().peekable();
+        while let Some(entry) = peekable.next() {
+            match entry {
+                Entry::Invalid(line) => bytes.extend_from_slice(line),
+                entry => bytes.extend(with_line_ending(entry.to_string(), line_ending)),
+            }
+            if peekable.peek().is_some() && !entry.ends_with_line_break() {
+                bytes.extend_from_slice(line_ending.as_str().as_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+/// Returns the bytes of the text, with its line feeds replaced by the line ending.
+fn with_line_ending(text: String, line_ending: LineEnding) -> Vec<u8> {
+    match line_ending {
+        LineEnding::Lf => text.into_bytes(),
+        LineEnding::CrLf => text.replace('\n', "\r\n").into_bytes(),
+    }
+}
+
+fn write_content<T: EndsWithLineBreak + Display>(
+    f: &mut core::fmt::Formatter<'_>,
     content: &[T],
-) -> std::fmt::Result {
+) -> core::fmt::Result {
     let mut peekable = content.iter().peekable();
     while let Some(item) = peekable.next() {
         item.fmt(f)?;
 
         // Add new line if it is a written entry before the end of iteration
-        if peekable.peek().is_some() && !item.is_blank() {
+        if peekable.peek().is_some() && !item.ends_with_line_break() {
             writeln!(f)?;
         }
     }
@@ -493,6 +1625,293 @@ fn write_content<T: CanBeComment + Display>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::unescape_value;
+
+    #[test]
+    fn test_group_management() {
+        let mut file = DesktopFile::try_from("# Comment\n[A]\nKey=1\n[B]\n").unwrap();
+        let group = Group::new;
+
+        assert!(file.add_group(group("A")).is_err());
+        file.add_group(group("C")).unwrap();
+        assert!(matches!(
+            file.rename_group("A", "B"),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            file.rename_group("D", "E"),
+            Err(Error::NotFound(_))
+        ));
+        file.rename_group("A", "First").unwrap();
+        assert_eq!(file.remove_group("B"), Some(group("B")));
+        assert_eq!(file.remove_group("B"), None);
+        file.ensure_group("First").content.clear();
+        file.ensure_group("D");
+
+        assert_eq!(file.to_string(), "# Comment\n[First]\n[C]\n[D]\n");
+    }
+
+    #[test]
+    fn test_group_mutation() {
+        let mut file = DesktopFile::try_from(
+            "[Group]\nName=App\nName[fr]=Appli\n# Comment\nKeys = a;b;\nName=Duplicate\n",
+        )
+        .unwrap();
+        let group = file.get_mut("Group").unwrap();
+        let french = Locale {
+            lang: String::from("fr"),
+            encoding: None,
+            country: None,
+            modifiers: None,
+        };
+
+        group.set("Name", " New;Name");
+        group.set_localized("Name", french.clone(), "Nouvelle");
+        group.set_localized("Comment", french, "Commentaire");
+        group.set_localized("Name", "de".parse().unwrap(), "Neu");
+        group.set_list("Keys", ["c;d", "e"]);
+        group.set_list("Categories", ["Game"]);
+        assert_eq!(
+            group.to_string(),
+            "[Group]
+Name=\\sNew;Name
+Name[fr]=Nouvelle
+Name[de]=Neu
+# Comment
+Keys = c\\;d;e;
+Comment[fr]=Commentaire
+Categories=Game;"
+        );
+
+        let removed = group.remove("Name");
+        assert_eq!(removed.len(), 3);
+        assert!(group.remove("Name").is_empty());
+        group.insert_at(3, removed[0].clone());
+        group.insert_at(0, removed[0].clone());
+        assert_eq!(
+            group.to_string(),
+            "[Group]
+Name=\\sNew;Name
+# Comment
+Keys = c\\;d;e;
+Comment[fr]=Commentaire
+Categories=Game;"
+        );
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut group: Group = "[Desktop Entry]\nName=App\nTerminal=false\n"
+            .parse()
+            .unwrap();
+        let toggle = |group: &mut Group, key: &str| {
+            group.transaction(|tx| {
+                tx.remove("Name");
+                let terminal = tx.get(key)?.as_bool()?;
+                tx.set(key, !terminal);
+                Ok::<_, Error>(terminal)
+            })
+        };
+
+        assert!(toggle(&mut group, "Hidden").is_err());
+        assert_eq!(
+            group.to_string(),
+            "[Desktop Entry]\nName=App\nTerminal=false"
+        );
+        assert!(!toggle(&mut group, "Terminal").unwrap());
+        assert_eq!(group.to_string(), "[Desktop Entry]\nTerminal=true");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_index() {
+        let file = DesktopFile::try_from("[A]\nKey=1\nKey=2\n[A]\nOther=3\n").unwrap();
+
+        assert_eq!(file["A"]["Key"].values, ["1"]);
+        assert!(std::panic::catch_unwind(|| &file["A"]["Other"]).is_err());
+        assert!(std::panic::catch_unwind(|| &file["B"]).is_err());
+    }
+
+    #[test]
+    fn test_find_all() {
+        let mut file =
+            DesktopFile::try_from("[A]\nKey=1\nOther=2\nKey=3\n[B]\n[A]\nKey=4\n").unwrap();
+
+        assert_eq!(file.find_all("A").len(), 2);
+        assert!(file.find_all("C").is_empty());
+        assert!(matches!(file.get_all("C"), Err(Error::NotFound(_))));
+
+        let group = file.get_mut("A").unwrap();
+        let values: Vec<_> = group
+            .get_all("Key")
+            .unwrap()
+            .iter()
+            .map(|e| e.values[0].as_str())
+            .collect();
+        assert_eq!(values, ["1", "3"]);
+        for entry in group.find_all_mut("Key") {
+            entry.values[0].push('0');
+        }
+        for group in file.find_all_mut("A").into_iter().skip(1) {
+            group.header = String::from("C");
+        }
+
+        assert_eq!(
+            file.to_string(),
+            "[A]\nKey=10\nOther=2\nKey=30\n[B]\n[C]\nKey=4"
+        );
+    }
+
+    #[test]
+    fn test_default_entry_set() {
+        struct Groups(Vec<Group>);
+
+        impl EntrySet<Group> for Groups {
+            fn without_comments(&self) -> Vec<&Group> {
+                self.0.iter().collect()
+            }
+
+            fn only_comments(&self) -> Vec<&CommentEntry> {
+                vec![]
+            }
+
+            fn find(&self, key: &str) -> Option<&Group> {
+                self.0.iter().find(|g| g.header == key)
+            }
+
+            fn find_mut(&mut self, key: &str) -> Option<&mut Group> {
+                self.0.iter_mut().find(|g| g.header == key)
+            }
+        }
+
+        let mut groups = Groups(vec![Group::new("A"), Group::new("B"), Group::new("A")]);
+        assert_eq!(groups.keys(), ["A", "B"]);
+        assert_eq!(groups.find_all("A").len(), 2);
+        assert_eq!(groups.find_all_mut("A").len(), 1);
+        assert_eq!(groups.len(), 3);
+    }
+
+    #[test]
+    fn test_main_group() {
+        let mut file =
+            DesktopFile::try_from("# Comment\n[Icon Theme]\nName=Theme\n[16x16/apps]\n").unwrap();
+        assert_eq!(file.main_group().unwrap().header, "Icon Theme");
+
+        file.main_group_mut().unwrap().header = String::from("X-Vendor");
+        file.ensure_group(MAIN_GROUP);
+        assert_eq!(file.main_group().unwrap().header, MAIN_GROUP);
+        file.main_group_mut().unwrap().set("Name", "App");
+        assert_eq!(file["Desktop Entry"]["Name"].values, ["App"]);
+
+        assert!(
+            DesktopFile::try_from("# Comment\n")
+                .unwrap()
+                .main_group()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_groups_with_prefix() {
+        let mut file = DesktopFile::try_from(
+            "[Desktop Entry]\nActions=new;\n[Desktop Action new]\nExec=app --new\n[X-Desktop Action]\n[Desktop Action open]\n",
+        )
+        .unwrap();
+
+        let headers = |groups: Vec<&Group>| -> Vec<String> {
+            groups.into_iter().map(|g| g.header.clone()).collect()
+        };
+        assert_eq!(
+            headers(file.groups_with_prefix("Desktop Action ")),
+            ["Desktop Action new", "Desktop Action open"]
+        );
+        assert!(file.groups_with_prefix("Icon").is_empty());
+
+        for group in file.groups_with_prefix_mut("Desktop Action ") {
+            group.set("Name", "Action");
+        }
+        assert_eq!(file["Desktop Action open"]["Name"].values, ["Action"]);
+    }
+
+    #[test]
+    fn test_entry_constructors() {
+        let entry = ContentEntry::new("Name[sr@latin]", " App;").unwrap();
+        assert_eq!(entry.key, "Name");
+        assert_eq!(entry.locale, Some("sr@latin".parse().unwrap()));
+        assert_eq!(entry.to_string(), "Name[sr@latin]=\\sApp;");
+        let parsed: ContentEntry = entry.to_string().parse().unwrap();
+        assert_eq!(unescape_value(&parsed.as_string().unwrap()), " App;");
+
+        let entry = ContentEntry::new_list("Categories", ["Utility", "A;B"]).unwrap();
+        assert_eq!(entry.to_string(), "Categories=Utility;A\\;B;");
+        assert_eq!(
+            ContentEntry::new("Key", 1).unwrap(),
+            "Key=1".parse::<ContentEntry>().unwrap()
+        );
+
+        for key in [
+            "",
+            "[fr]",
+            "Name ",
+            "Name=",
+            "Name[]",
+            "Name[fr",
+            "Name[fr]x",
+            "Name[fr]]",
+        ] {
+            assert!(
+                matches!(ContentEntry::new(key, "value"), Err(Error::InvalidValue(_))),
+                "{key}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_collection_basics() {
+        let file = DesktopFile::try_from(
+            "# Comment\n[A]\nKey=1\nKey[fr]=2\n# Comment\nOther=3\n[B]\n[A]\n",
+        )
+        .unwrap();
+
+        assert_eq!(file.keys(), ["A", "B"]);
+        assert_eq!(file.len(), 3);
+        assert!(file.contains_key("B") && !file.contains_key("C"));
+
+        let group = &file["A"];
+        assert_eq!(group.keys(), ["Key", "Other"]);
+        assert_eq!(group.len(), 3);
+        assert!(group.contains_key("Other") && !group.contains_key("Key[fr]"));
+        assert!(!group.is_empty() && file["B"].is_empty());
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let entry = |line: &str| line.parse::<ContentEntry>().unwrap();
+
+        assert!(entry("Terminal=true").as_bool().unwrap());
+        assert!(!entry("Hidden=false").as_bool().unwrap());
+        assert!(entry("Hidden=True").as_bool().is_err());
+        assert_eq!(entry("X-Count=-42").as_i64().unwrap(), -42);
+        assert!(entry("X-Count=4.2").as_i64().is_err());
+        assert_eq!(entry("X-Ratio=4.5").as_f64().unwrap(), 4.5);
+        assert!(entry("X-Ratio=4,5").as_f64().is_err());
+        assert_eq!(entry("Name=App").as_string().unwrap(), "App");
+        assert_eq!(
+            entry("Exec=sh -c 'a; b;' ").as_string().unwrap(),
+            "sh -c 'a; b;'"
+        );
+        assert_eq!(entry("Comment=").as_string().unwrap(), "");
+        assert!(matches!(
+            ContentEntry::new_list("Categories", ["Game", "Arcade"])
+                .unwrap()
+                .as_string(),
+            Err(Error::InvalidValue(_))
+        ));
+        assert_eq!(
+            entry("Categories=Game;Arcade;").as_string_list(),
+            ["Game", "Arcade"]
+        );
+    }
 
     #[test]
     fn test_single_value_entry_format() {
@@ -500,6 +1919,7 @@ mod tests {
             key: String::from("Hello"),
             values: vec![String::from("World")],
             locale: None,
+            format: EntryFormat::default(),
         });
         let single_value_locale_entry = Entry::Content(ContentEntry {
             key: String::from("Hello"),
@@ -510,6 +1930,7 @@ mod tests {
                 country: Some(String::from("US")),
                 modifiers: Some(String::from("new")),
             }),
+            format: EntryFormat::default(),
         });
 
         assert_eq!(&single_value_entry.to_string(), "Hello=World");
@@ -529,6 +1950,7 @@ mod tests {
                 String::from("all others"),
             ],
             locale: None,
+            format: EntryFormat::default(),
         });
         assert_eq!(
             &multi_values.to_string(),
@@ -536,6 +1958,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_escaped_entry_format() {
+        let entry = ContentEntry {
+            key: String::from("Hello"),
+            values: vec![String::from("a;b"), String::from("line\nbreak\\;c\\")],
+            locale: None,
+            format: EntryFormat::default(),
+        };
+        let written = entry.to_string();
+        assert_eq!(written, "Hello=a\\;b;line\\nbreak\\;c\\\\");
+
+        let parsed: Group = format!("[A]\n{written}\n").parse().unwrap();
+        let values = parsed.find("Hello").unwrap().decoded_values();
+        assert_eq!(values, ["a;b", "line\nbreak;c\\"]);
+
+        let parsed = DesktopFile::try_from("[A]\nKey=a\\;b\\s;c\\\\;\n").unwrap();
+        assert_eq!(parsed.to_string(), "[A]\nKey=a\\;b\\s;c\\\\;");
+        assert_eq!(
+            escape_value(&unescape_value("\\sa\\;b"), Some(';')),
+            "\\sa\\;b"
+        );
+        assert_eq!(escape_value(" a b  ", None), "\\sa b\\s\\s");
+        assert_eq!(escape_value("  ", None), "\\s\\s");
+
+        // `\\` is read as a backslash, and the value as written is kept to tell it from an escape sequence
+        let parsed: ContentEntry = "Comment=a\\\\n\\nb".parse().unwrap();
+        assert_eq!(parsed.values, ["a\\n\\nb"]);
+        assert_eq!(parsed.decoded_string().unwrap(), "a\\n\nb");
+        assert_eq!(parsed.to_string(), "Comment=a\\\\n\\nb");
+    }
+
     #[test]
     fn test_comments_format() {
         let text_comment = Entry::Comment(CommentEntry::Text(String::from("Test with spaces")));
@@ -557,6 +2010,7 @@ mod tests {
                             key: String::from("Title"),
                             values: vec![String::from("First group")],
                             locale: None,
+                            format: EntryFormat::default(),
                         }),
                         Entry::Comment(CommentEntry::Blank(String::from("\n"))),
                         Entry::Comment(CommentEntry::Text(String::from("End of group"))),
@@ -567,6 +2021,7 @@ mod tests {
                     content: vec![],
                 }),
             ],
+            format: FileFormat::default(),
         };
 
         assert_eq!(
@@ -580,4 +2035,146 @@ Title=First group
 "
         )
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ordering() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let entry = |line: &str| line.parse::<ContentEntry>().unwrap();
+        let entries = BTreeSet::from([
+            entry("Name[fr]=Appli"),
+            entry("Name=App"),
+            entry("Name[de_DE]=App"),
+            entry("Name[de]=App"),
+            entry("Exec=app"),
+        ]);
+        assert_eq!(
+            entries.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "Exec=app",
+                "Name=App",
+                "Name[de]=App",
+                "Name[de_DE]=App",
+                "Name[fr]=Appli"
+            ]
+        );
+
+        let mut spaced = entry("Exec=app");
+        spaced.format.assignment = Some(String::from(" = "));
+        let set = HashSet::from([entry("Exec=app"), spaced]);
+        assert_eq!(set.len(), 1);
+
+        let groups = BTreeSet::from([Group::new("B"), Group::new("A"), Group::default()]);
+        assert_eq!(
+            groups.iter().map(|g| g.header.as_str()).collect::<Vec<_>>(),
+            ["", "A", "B"]
+        );
+        assert!(DesktopFile::new().content.is_empty());
+    }
+
+    #[test]
+    fn test_find_localized() {
+        let group = DesktopFile::try_from(
+            "[A]\nName[de]=German\nName=Default\nName[de_AT]=Austrian\nName[de@old]=Old\nName[de_AT@old]=Old Austrian\n",
+        )
+        .unwrap()
+        .remove_group("A")
+        .unwrap();
+        let name = |locale: &str| {
+            group
+                .find_localized("Name", &locale.parse().unwrap())
+                .map(|e| e.values[0].as_str())
+        };
+
+        assert_eq!(name("de_AT.UTF-8@old"), Some("Old Austrian"));
+        assert_eq!(name("de_AT"), Some("Austrian"));
+        assert_eq!(name("de_CH@old"), Some("Old"));
+        assert_eq!(name("de_CH"), Some("German"));
+        assert_eq!(name("fr"), Some("Default"));
+        assert!(matches!(
+            group.get_localized("Comment", &"fr".parse().unwrap()),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_localizations() {
+        let group: Group = "[A]\nName=App\n# Comment\nComment=Text\nName[fr]=Appli\n"
+            .parse()
+            .unwrap();
+        let localizations: Vec<_> = group
+            .localizations("Name")
+            .map(|(locale, entry)| (locale.map(ToString::to_string), entry.values[0].as_str()))
+            .collect();
+
+        assert_eq!(
+            localizations,
+            [(None, "App"), (Some(String::from("fr")), "Appli")]
+        );
+        assert_eq!(group.localizations("Icon").count(), 0);
+    }
+
+    #[test]
+    fn test_find_preferred() {
+        let group: Group = "[A]\nName=App\nName[de]=Anwendung\nName[sr@latin]=Aplikacija\n"
+            .parse()
+            .unwrap();
+        let name = |list: &str| {
+            group
+                .find_preferred("Name", &Locale::parse_list(list))
+                .map(|e| e.values[0].as_str())
+        };
+
+        assert_eq!(name("fr:de_AT:sr_RS@latin"), Some("Anwendung"));
+        assert_eq!(name("sr_RS@latin:de"), Some("Aplikacija"));
+        assert_eq!(name("fr::fr_"), Some("App"));
+        assert!(group.get_preferred("Comment", &[]).is_err());
+        assert_eq!(Locale::parse_list("de:_FR:fr").len(), 2);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let locale = |locale: &str| locale.parse::<Locale>().unwrap();
+
+        assert_eq!(locale("en_US.UTF-8").canonicalize(), locale("en_US"));
+        assert_eq!(
+            locale("de_de.ISO-8859-1").canonicalize(),
+            locale("de_DE.ISO-8859-1")
+        );
+        assert!(locale("en_US.UTF-8").equals_options(&LocaleOptions::all(&locale("en_us"))));
+        assert!(!locale("en_US.ISO-8859-1").equals_options(&LocaleOptions::all(&locale("en_US"))));
+        assert!(!locale("en_GB").equals_options(&LocaleOptions::all(&locale("en_US"))));
+
+        let group: Group = "[A]\nName[en_GB]=Colour\nName[en_US]=Color\n"
+            .parse()
+            .unwrap();
+        let us = locale("en_US.UTF-8");
+        let options = LocaleOptions::new(&us)
+            .significant_country()
+            .significant_encoding();
+        assert_eq!(
+            group.find_with_locale("Name", &options).unwrap().values,
+            ["Color"]
+        );
+    }
+
+    #[test]
+    fn test_find_closest() {
+        let group: Group = "[A]\nName=App\nName[pt]=Aplicação\nName[pt_BR]=Aplicativo\n"
+            .parse()
+            .unwrap();
+        let name = |locale: &str, options: fn(LocaleOptions) -> LocaleOptions| {
+            let locale: Locale = locale.parse().unwrap();
+            group
+                .find_closest("Name", &options(LocaleOptions::new(&locale)))
+                .map(|e| e.values[0].as_str())
+        };
+
+        assert_eq!(name("pt_BR", |o| o.any_country()), Some("Aplicativo"));
+        assert_eq!(name("pt_PT", |o| o.any_country()), Some("Aplicação"));
+        assert_eq!(name("pt_PT", |o| o.significant_country()), Some("App"));
+        assert_eq!(name("pt_BR", |o| o), Some("Aplicação"));
+        assert_eq!(name("fr", |o| o.any_country().any_modifier()), Some("App"));
+    }
 }
@@ -0,0 +1,218 @@
+use alloc::{borrow::Cow, collections::VecDeque, vec::Vec};
+
+use memchr::{memchr, memmem};
+
+use crate::{
+    error::Error,
+    parser::models::{DesktopFile, FileFormat, LineEnding, TopLevelEntry},
+};
+
+use super::{ParserOptions, crlf_to_lf, detect_line_ending, parse_entries, strip_byte_order_mark};
+
+#[cfg(feature = "encoding")]
+use super::encoding::transcode_legacy;
+
+/// A parser fed with chunks of bytes, from which the parsed entries are pulled.
+///
+/// Chunks can be cut anywhere, even in the middle of a UTF-8 character. A group is parsed once the header
+/// of the next one is fed, or when the parser is finished, so that only the current group is buffered.
+/// As for a whole file, the line ending is detected from the first line, and `\r\n` are converted to `\n`:
+/// ```
+/// use freedesktop_rs::parser::Parser;
+///
+/// let mut parser = Parser::new();
+/// parser.feed(b"[Desktop Entry]\nName=App\n[Desktop Act").unwrap();
+/// assert_eq!(parser.next().unwrap().to_string(), "[Desktop Entry]\nName=App");
+/// assert!(parser.next().is_none());
+///
+/// parser.feed(b"ion new]\nName=New\n").unwrap();
+/// parser.finish().unwrap();
+/// assert_eq!(parser.next().unwrap().to_string(), "[Desktop Action new]\nName=New");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    options: ParserOptions,
+    buffer: Vec<u8>,
+    /// The length of the start of the buffer already searched for a group header.
+    scanned: usize,
+    parsed: VecDeque<TopLevelEntry>,
+    format: Option<FileFormat>,
+}
+
+impl Parser {
+    /// Creates a parser with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a parser with the given options.
+    pub fn with_options(options: ParserOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a chunk of the input, and parses the groups it completes.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        self.buffer.extend_from_slice(chunk);
+        if self.format.is_none() {
+            // Wait until the first line is complete, which gives the line ending
+            if memchr(b'\n', &self.buffer).is_none() {
+                return Ok(());
+            }
+            self.detect_format();
+        }
+
+        // A group ends where the next one starts, and the line feed may end the previous chunk
+        let start = self.scanned.saturating_sub(1);
+        let Some(end) =
+            memmem::rfind(&self.buffer[start..], b"\n[").map(|position| start + position + 1)
+        else {
+            self.scanned = self.buffer.len();
+            return Ok(());
+        };
+        let complete: Vec<u8> = self.buffer.drain(..end).collect();
+        self.scanned = self.buffer.len();
+        self.parse(&complete)
+    }
+
+    /// Parses the rest of the input, once every chunk was fed.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        if self.format.is_none() {
+            self.detect_format();
+        }
+        let rest = core::mem::take(&mut self.buffer);
+        self.scanned = 0;
+        self.parse(&rest)
+    }
+
+    /// Returns the formatting details of the file, once the start of the input was fed.
+    pub fn format(&self) -> Option<&FileFormat> {
+        self.format.as_ref()
+    }
+
+    fn detect_format(&mut self) {
+        let (input, byte_order_mark) = strip_byte_order_mark(&self.buffer);
+        let line_ending = detect_line_ending(input);
+        let stripped = self.buffer.len() - input.len();
+        self.buffer.drain(..stripped);
+        self.format = Some(FileFormat {
+            byte_order_mark,
+            line_ending,
+        });
+    }
+
+    fn parse(&mut self, input: &[u8]) -> Result<(), Error> {
+        if input.is_empty() {
+            return Ok(());
+        }
+        let input = match self.format.as_ref().map(|format| format.line_ending) {
+            Some(LineEnding::CrLf) => Cow::Owned(crlf_to_lf(input)),
+            _ => Cow::Borrowed(input),
+        };
+        // The input is made of whole lines, which are transcoded one by one
+        #[cfg(feature = "encoding")]
+        let input = match transcode_legacy(&input) {
+            Cow::Borrowed(_) => input,
+            Cow::Owned(transcoded) => Cow::Owned(transcoded),
+        };
+        let (rest, content) =
+            parse_entries(&input, &self.options).map_err(|e| Error::Parsing(e.to_owned()))?;
+        if !rest.is_empty() {
+            return Err(Error::Parsing(nom::Err::Error(nom::error::Error::new(
+                rest.to_vec(),
+                nom::error::ErrorKind::Complete,
+            ))));
+        }
+
+        let mut file = DesktopFile {
+            content,
+            format: FileFormat::default(),
+        };
+        file.apply_options(&self.options);
+        self.parsed.extend(file.content);
+        Ok(())
+    }
+}
+
+impl Iterator for Parser {
+    type Item = TopLevelEntry;
+
+    /// Pulls the next parsed entry, if any.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parsed.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    const FILE: &str = "\u{feff}# Outside comment
+[Desktop Entry]
+Name=Écran
+Keywords=a;b;
+
+[Desktop Action new]
+Name=  New window
+";
+
+    #[test]
+    fn test_push_parsing() {
+        for chunk_size in [1, 2, 7, FILE.len()] {
+            let mut parser = Parser::with_options(ParserOptions::new().untrimmed_values());
+            let mut content = vec![];
+            for chunk in FILE.as_bytes().chunks(chunk_size) {
+                parser.feed(chunk).unwrap();
+                content.extend(&mut parser);
+            }
+            parser.finish().unwrap();
+            content.extend(&mut parser);
+
+            let expected = DesktopFile::parse_with_options(
+                FILE.as_bytes(),
+                &ParserOptions::new().untrimmed_values(),
+            )
+            .unwrap();
+            assert_eq!(content, expected.content);
+            assert!(parser.format().unwrap().byte_order_mark);
+            assert!(expected.format.byte_order_mark);
+        }
+    }
+
+    #[test]
+    fn test_push_parsing_crlf() {
+        let crlf = FILE.replace('\n', "\r\n");
+        let mut parser = Parser::new();
+        for chunk in crlf.as_bytes().chunks(3) {
+            parser.feed(chunk).unwrap();
+        }
+        parser.finish().unwrap();
+
+        let expected = DesktopFile::try_from(crlf.as_str()).unwrap();
+        assert_eq!(parser.format().unwrap().line_ending, LineEnding::CrLf);
+        assert_eq!(parser.collect::<Vec<_>>(), expected.content);
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn test_push_parsing_legacy_encoding() {
+        let mut parser = Parser::new();
+        parser.feed(b"[Desktop Entry]\nName[fr]=Caf\xE9\n").unwrap();
+        parser.feed(b"[Other]\n").unwrap();
+        parser.finish().unwrap();
+
+        let entries: Vec<_> = parser.collect();
+        assert_eq!(entries[0].to_string(), "[Desktop Entry]\nName[fr]=Café");
+    }
+
+    #[test]
+    fn test_push_parsing_error() {
+        let mut parser = Parser::new();
+        parser.feed(b"[Desktop Entry]\nInvalid line\n").unwrap();
+        assert!(parser.feed(b"[Other]\n").is_err());
+    }
+}
@@ -0,0 +1,449 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use nom::{
+    AsChar, IResult, Parser,
+    branch::alt,
+    bytes::complete::{escaped_transform, take_while1},
+    character::complete::{char, line_ending, multispace1, space0},
+    combinator::{consumed, eof, map, map_res, opt, recognize, value},
+    error::{Error, ErrorKind, ParseError},
+    multi::{many_till, many0},
+    sequence::{delimited, pair, preceded, terminated},
+};
+
+use memchr::{memchr, memchr2, memchr3};
+
+use super::{ends_with_separator, split_values};
+use crate::{
+    error::LocaleError,
+    parser::models::{
+        CommentEntry, ContentEntry, Entry, EntryFormat, Group, GroupContent, Locale, TopLevelEntry,
+    },
+};
+
+/// Parses all the top-level entries of the input, until one cannot be parsed.
+pub fn parse_top_level_entries(input: &[u8]) -> IResult<&[u8], Vec<TopLevelEntry>> {
+    many0(parse_top_level_entry).parse(input)
+}
+
+/// Parses a group or a comment outside of any group.
+pub fn parse_top_level_entry(input: &[u8]) -> IResult<&[u8], TopLevelEntry> {
+    alt((
+        map_res(parse_group, TopLevelEntry::try_from),
+        map_res(parse_comment_entry, TopLevelEntry::try_from),
+    ))
+    .parse(input)
+}
+
+/// Parses a group header and the entries that follow it.
+pub fn parse_group(input: &[u8]) -> IResult<&[u8], Group> {
+    let (input, (header, content)) = pair(parse_group_header, parse_group_content).parse(input)?;
+
+    Ok((
+        input,
+        Group {
+            header: header.to_owned(),
+            content,
+        },
+    ))
+}
+
+/// Parses a group header such as `[Desktop Entry]`, and returns its name.
+///
+/// The line feed following the header is consumed.
+pub fn parse_group_header(input: &[u8]) -> IResult<&[u8], &str> {
+    map_res(
+        terminated(
+            delimited(char('['), take_till_brackets, char(']')),
+            opt(char('\n')),
+        ),
+        str::from_utf8,
+    )
+    .parse(input)
+}
+
+/// Parses the entries of a group, until one cannot be parsed (e.g. the next group header).
+pub fn parse_group_content(input: &[u8]) -> IResult<&[u8], GroupContent> {
+    many0(parse_entry).parse(input)
+}
+
+/// Parses a comment, a blank line, or a key-values entry.
+pub fn parse_entry(input: &[u8]) -> IResult<&[u8], Entry> {
+    alt((
+        map_res(parse_comment_entry, Entry::try_from),
+        map_res(parse_content_entry, Entry::try_from),
+    ))
+    .parse(input)
+}
+
+/// Parses consecutive blank lines.
+pub fn parse_blank_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
+    let (input, space) = map_res(multispace1, str::from_utf8).parse(input)?;
+    Ok((input, CommentEntry::Blank(space.to_owned())))
+}
+
+/// Parses blank lines or a comment.
+pub fn parse_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
+    alt((parse_blank_comment_entry, parse_text_comment_entry)).parse(input)
+}
+
+/// Parses a comment line starting with `#`.
+pub fn parse_text_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
+    let (input, comment) = map_res(
+        preceded(
+            pair(char('#'), space0),
+            terminated(
+                // Comments may contain any UTF-8 text, validated below
+                take_till_line_ending,
+                char('\n'),
+            ),
+        ),
+        str::from_utf8,
+    )
+    .parse(input)?;
+
+    Ok((input, CommentEntry::Text(comment.to_owned())))
+}
+
+/// Parses blank lines or a comment, keeping the spaces after the `#` in its text, as GLib's `GKeyFile` does.
+///
+/// Such comments are written back as they were read with [FormatOptions::comments_without_space](crate::parser::FormatOptions::comments_without_space).
+pub fn parse_verbatim_comment_entry(input: &[u8]) -> IResult<&[u8], CommentEntry> {
+    alt((
+        parse_blank_comment_entry,
+        map(
+            map_res(
+                delimited(char('#'), take_till_line_ending, char('\n')),
+                str::from_utf8,
+            ),
+            |comment| CommentEntry::Text(comment.to_owned()),
+        ),
+    ))
+    .parse(input)
+}
+
+/// Parses the key of an entry, made of alphanumeric characters and dashes.
+pub fn parse_key(input: &[u8]) -> IResult<&[u8], &str> {
+    map_res(
+        take_while1(|c: u8| is_key_character(c.as_char())),
+        str::from_utf8,
+    )
+    .parse(input)
+}
+
+/// Returns whether the character is allowed in keys, see [parse_key].
+pub(crate) fn is_key_character(c: char) -> bool {
+    c.is_alphanumeric() || c == '-'
+}
+
+/// Parses a MIME type used as a key in `mimeapps.list` files, such as `image/svg+xml`.
+///
+/// Besides the characters of [parse_key], the `/`, `.`, `+` and `_` characters are accepted.
+pub fn parse_mime_type_key(input: &[u8]) -> IResult<&[u8], &str> {
+    map_res(
+        take_while1(|c: u8| {
+            let item = c.as_char();
+            is_key_character(item) || matches!(item, '/' | '.' | '+' | '_')
+        }),
+        str::from_utf8,
+    )
+    .parse(input)
+}
+
+/// Parses the groups and comments of a `mimeapps.list` file, whose keys are MIME types.
+///
+/// See [parse_top_level_entries] and [parse_mime_type_key].
+pub fn parse_mimeapps_entries(input: &[u8]) -> IResult<&[u8], Vec<TopLevelEntry>> {
+    parse_top_level_entries_with(input, parse_mime_type_key, parse_comment_entry)
+}
+
+/// A parser of keys, such as [parse_key] or [parse_mime_type_key].
+pub(crate) type KeyParser = fn(&[u8]) -> IResult<&[u8], &str>;
+
+/// A parser of comments, such as [parse_comment_entry] or [parse_verbatim_comment_entry].
+pub(crate) type CommentParser = fn(&[u8]) -> IResult<&[u8], CommentEntry>;
+
+/// Similar to [parse_top_level_entries], but with the given parsers of keys and comments.
+pub(crate) fn parse_top_level_entries_with(
+    input: &[u8],
+    key: KeyParser,
+    comment: CommentParser,
+) -> IResult<&[u8], Vec<TopLevelEntry>> {
+    many0(alt((
+        map(
+            pair(
+                parse_group_header,
+                many0(|input| parse_entry_with(input, key, comment)),
+            ),
+            |(header, content)| {
+                TopLevelEntry::Group(Group {
+                    header: header.to_owned(),
+                    content,
+                })
+            },
+        ),
+        map(comment, TopLevelEntry::Comment),
+    )))
+    .parse(input)
+}
+
+/// Similar to [parse_entry], but with the given parsers of keys and comments.
+pub(crate) fn parse_entry_with(
+    input: &[u8],
+    key: KeyParser,
+    comment: CommentParser,
+) -> IResult<&[u8], Entry> {
+    alt((
+        map(comment, Entry::Comment),
+        map(|input| parse_content_entry_with(input, key), Entry::Content),
+    ))
+    .parse(input)
+}
+
+/// Parses the values of an entry until the end of the line, which is consumed.
+///
+/// Values are not trimmed, see [parse_single_value].
+pub fn parse_value(input: &[u8]) -> IResult<&[u8], Vec<String>> {
+    map(
+        many_till(parse_single_value, alt((line_ending, eof))),
+        |r| r.0,
+    )
+    .parse(input)
+}
+
+/// Parses a single value and its terminating semicolon, if any.
+///
+/// Escape sequences are validated but kept as written, e.g. `\;` is returned as `\;`, except `\\` which is returned as `\`.
+/// See [unescape_value] to decode them.
+pub fn parse_single_value(input: &[u8]) -> IResult<&[u8], String> {
+    terminated(
+        map_res(
+            escaped_transform(
+                take_till_escape_or_separator,
+                '\\',
+                map(
+                    alt((
+                        value("\\n", char('n')),
+                        value("\\r", char('r')),
+                        value("\\s", char('s')),
+                        value("\\t", char('t')),
+                        value("\\", char('\\')),
+                        value("\\;", char(';')),
+                    )),
+                    |s| s.as_bytes(),
+                ),
+            ),
+            String::from_utf8,
+        ),
+        opt(char(';')),
+    )
+    .parse(input)
+}
+
+/// Decodes the escape sequences of a value, as kept by [parse_single_value].
+///
+/// `\s`, `\n`, `\t`, `\r` and `\\` are decoded as defined by the specification, and any other
+/// escaped character, such as a list separator, is decoded as itself.
+pub fn unescape_value(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => decoded.push(' '),
+            Some('n') => decoded.push('\n'),
+            Some('t') => decoded.push('\t'),
+            Some('r') => decoded.push('\r'),
+            Some(escaped) => decoded.push(escaped),
+            None => decoded.push(c),
+        }
+    }
+
+    decoded
+}
+
+/// Decodes the escaped backslashes of a value as written, keeping the other escape sequences as [parse_single_value] does.
+pub(crate) fn decode_backslashes(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        decoded.push(c);
+        if c == '\\' {
+            match chars.next() {
+                Some('\\') => {}
+                next => decoded.extend(next),
+            }
+        }
+    }
+
+    decoded
+}
+
+/// Parses the optional locale of an entry, such as `[en_US.UTF-8@new]`.
+///
+/// Fails with [nom::Err::Failure] if the brackets do not contain a valid locale, see [check_entry_locale] to know why.
+pub fn parse_entry_locale(input: &[u8]) -> IResult<&[u8], Option<Locale>> {
+    match split_entry_locale(input) {
+        Ok(Some((locale, rest))) => Ok((rest, Some(locale))),
+        Ok(None) => Ok((input, None)),
+        Err((_, position)) => Err(nom::Err::Failure(Error {
+            input: position,
+            code: ErrorKind::Verify,
+        })),
+    }
+}
+
+/// Similar to [parse_entry_locale], but returns why the locale is malformed.
+pub fn check_entry_locale(input: &[u8]) -> Result<Option<Locale>, LocaleError> {
+    split_entry_locale(input)
+        .map(|parsed| parsed.map(|(locale, _)| locale))
+        .map_err(|(error, _)| error)
+}
+
+/// A malformed locale, and where the error was found in the input.
+type LocatedLocaleError<'a> = (LocaleError, &'a [u8]);
+
+/// Splits the locale between brackets from the rest of the input, or returns the error and where it was found.
+fn split_entry_locale(input: &[u8]) -> Result<Option<(Locale, &[u8])>, LocatedLocaleError<'_>> {
+    let Some(inner) = input.strip_prefix(b"[") else {
+        return Ok(None);
+    };
+    let end = match memchr3(b'[', b']', b'\n', inner) {
+        Some(end) if inner[end] == b']' => end,
+        Some(end) if inner[end] == b'[' => return Err((LocaleError::NestedBracket, &inner[end..])),
+        _ => return Err((LocaleError::Unterminated, inner)),
+    };
+
+    let raw = &inner[..end];
+    let locale = match str::from_utf8(raw) {
+        Ok(raw) => parse_locale(raw),
+        Err(_) => Err(LocaleError::UnexpectedCharacter(
+            char::REPLACEMENT_CHARACTER,
+        )),
+    };
+    match locale {
+        Ok(locale) => Ok(Some((locale, &inner[end + 1..]))),
+        Err(error) => Err((error, raw)),
+    }
+}
+
+/// Parses a locale without brackets, such as `en_US.UTF-8@new`.
+pub(crate) fn parse_locale(raw: &str) -> Result<Locale, LocaleError> {
+    let mut rest = raw;
+    let lang = take_locale_component(&mut rest, |c| c.is_ascii_alphabetic());
+    match rest.chars().next() {
+        _ if !lang.is_empty() => {}
+        None => return Err(LocaleError::Empty),
+        Some('_' | '.' | '@') => return Err(LocaleError::MissingLanguage),
+        Some(c) => return Err(LocaleError::UnexpectedCharacter(c)),
+    }
+
+    let country = separated_locale_component(&mut rest, '_', |c| c.is_ascii_alphabetic())?;
+    let encoding =
+        separated_locale_component(&mut rest, '.', |c| c.is_ascii_alphanumeric() || c == '-')?;
+    let modifiers = separated_locale_component(&mut rest, '@', |c| c.is_ascii_alphabetic())?;
+    if let Some(c) = rest.chars().next() {
+        return Err(LocaleError::UnexpectedCharacter(c));
+    }
+
+    Ok(Locale {
+        lang: lang.to_owned(),
+        country,
+        encoding,
+        modifiers,
+    })
+}
+
+/// Takes the longest prefix of allowed characters.
+fn take_locale_component<'a>(rest: &mut &'a str, allowed: fn(char) -> bool) -> &'a str {
+    let end = rest.find(|c| !allowed(c)).unwrap_or(rest.len());
+    let (component, after) = rest.split_at(end);
+    *rest = after;
+    component
+}
+
+/// Takes the component following the separator, if the input starts with it.
+fn separated_locale_component(
+    rest: &mut &str,
+    separator: char,
+    allowed: fn(char) -> bool,
+) -> Result<Option<String>, LocaleError> {
+    let Some(mut after) = rest.strip_prefix(separator) else {
+        return Ok(None);
+    };
+    let component = take_locale_component(&mut after, allowed);
+    if component.is_empty() {
+        return Err(LocaleError::EmptyComponent(separator));
+    }
+    *rest = after;
+    Ok(Some(component.to_owned()))
+}
+
+/// Parses a key-values entry, such as `Name[fr]=Bonjour`.
+///
+/// The values are trimmed, and the values as written, with their escape sequences, are kept in [EntryFormat::raw_values].
+pub fn parse_content_entry(input: &[u8]) -> IResult<&[u8], ContentEntry> {
+    parse_content_entry_with(input, parse_key)
+}
+
+fn parse_content_entry_with(input: &[u8], parse_key: KeyParser) -> IResult<&[u8], ContentEntry> {
+    let (input, key) = parse_key(input)?;
+
+    let (input, locale) = parse_entry_locale.parse(input)?;
+
+    let (input, assignment) = recognize((space0, char('='), space0)).parse(input)?;
+    let (input, (written, values)) = consumed(parse_value).parse(input)?;
+    let trailing_separator = ends_with_separator(written.trim_ascii_end(), b";");
+    let written = written
+        .strip_suffix(b"\n")
+        .map_or(written, |line| line.strip_suffix(b"\r").unwrap_or(line));
+    let raw_values = split_values(&String::from_utf8_lossy(written), ';');
+    let assignment = (assignment != b"=").then(|| String::from_utf8_lossy(assignment).into_owned());
+    Ok((
+        input,
+        ContentEntry {
+            key: key.to_owned(),
+            values: values.iter().map(|v| v.trim().to_owned()).collect(),
+            locale,
+            format: EntryFormat {
+                raw_values: Some(raw_values),
+                values_trimmed: true,
+                assignment,
+                trailing_separator,
+                ..EntryFormat::default()
+            },
+        },
+    ))
+}
+
+// The following parsers are the hot paths of the parser, so they use memchr instead of checking every byte.
+
+fn split_at_position(input: &[u8], position: Option<usize>) -> (&[u8], &[u8]) {
+    let end = position.unwrap_or(input.len());
+    (&input[end..], &input[..end])
+}
+
+/// Recognizes the input until a bracket, which is the content of a group header or a locale.
+fn take_till_brackets(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    Ok(split_at_position(input, memchr2(b'[', b']', input)))
+}
+
+/// Recognizes the input until a line feed.
+fn take_till_line_ending(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    Ok(split_at_position(input, memchr(b'\n', input)))
+}
+
+/// Recognizes at least one byte until an escape character, a value separator or a line feed.
+fn take_till_escape_or_separator(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    match split_at_position(input, memchr3(b'\\', b';', b'\n', input)) {
+        (_, []) => Err(nom::Err::Error(Error::from_error_kind(
+            input,
+            ErrorKind::IsNot,
+        ))),
+        result => Ok(result),
+    }
+}
@@ -0,0 +1,153 @@
+use alloc::{borrow::ToOwned, string::String, vec, vec::Vec};
+use nom::{IResult, Parser};
+
+use crate::parser::models::{Entry, Group, TopLevelEntry};
+
+use super::raw::{CommentParser, parse_entry_with, parse_group_header, parse_key};
+
+/// Parses the input line by line, keeping the lines of a group that cannot be parsed as [Entry::Invalid].
+///
+/// Parsing stops at the first line before any group that cannot be parsed.
+pub(super) fn parse_recovering(
+    input: &[u8],
+    comment: CommentParser,
+) -> IResult<&[u8], Vec<TopLevelEntry>> {
+    let (input, content, _) = parse_recovering_with_positions(input, comment);
+    Ok((input, content))
+}
+
+/// Position of an invalid line in the input, and header of its group.
+pub(super) type InvalidLine = (usize, String);
+
+/// Similar to [parse_recovering], but also returns the position in the input of each invalid line,
+/// with the header of its group.
+pub(super) fn parse_recovering_with_positions(
+    mut input: &[u8],
+    comment: CommentParser,
+) -> (&[u8], Vec<TopLevelEntry>, Vec<InvalidLine>) {
+    let length = input.len();
+    let mut content: Vec<TopLevelEntry> = vec![];
+    let mut positions = vec![];
+
+    while !input.is_empty() {
+        if let Ok((rest, header)) = parse_group_header.parse(input) {
+            content.push(TopLevelEntry::Group(Group {
+                header: header.to_owned(),
+                content: vec![],
+            }));
+            input = rest;
+            continue;
+        }
+
+        if let Some(TopLevelEntry::Group(group)) = content.last_mut() {
+            match parse_entry_with(input, parse_key, comment) {
+                Ok((rest, entry)) if rest.len() < input.len() => {
+                    group.content.push(entry);
+                    input = rest;
+                }
+                _ => {
+                    positions.push((length - input.len(), group.header.clone()));
+                    let (line, rest) = split_line(input);
+                    group.content.push(Entry::Invalid(line.to_vec()));
+                    input = rest;
+                }
+            }
+        } else {
+            match comment(input) {
+                Ok((rest, comment)) => {
+                    content.push(TopLevelEntry::Comment(comment));
+                    input = rest;
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    (input, content, positions)
+}
+
+/// Splits the input after the first line ending, which is not part of the line.
+pub(super) fn split_line(input: &[u8]) -> (&[u8], &[u8]) {
+    match input.iter().position(|b| *b == b'\n') {
+        Some(end) => (&input[..end], &input[end + 1..]),
+        None => (input, &[]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::parser::{
+        ParserOptions,
+        models::{DesktopFile, EntrySet},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_recover_invalid_lines() {
+        let file = "# Leading comment
+[Desktop Entry]
+Name=App
+This line is invalid
+Name[fr_]=Appli
+Exec=app
+[Desktop Action new]
+=
+Exec=app --new";
+
+        let parsed = DesktopFile::parse_with_options(
+            file.as_bytes(),
+            &ParserOptions::new().recover_invalid_lines(),
+        )
+        .unwrap();
+        let group = parsed.get("Desktop Entry").unwrap();
+
+        assert_eq!(
+            group.content[1],
+            Entry::Invalid(b"This line is invalid".to_vec())
+        );
+        assert_eq!(
+            group.content[2],
+            Entry::Invalid(b"Name[fr_]=Appli".to_vec())
+        );
+        assert!(group.find("Exec").is_some());
+        assert!(
+            parsed
+                .get("Desktop Action new")
+                .unwrap()
+                .find("Exec")
+                .is_some()
+        );
+        assert_eq!(parsed.to_string(), file);
+
+        assert_eq!(
+            DesktopFile::try_from(file)
+                .unwrap()
+                .without_comments()
+                .len(),
+            1
+        );
+    }
+
+    // With the encoding feature, files which are not UTF-8 are transcoded before being parsed
+    #[cfg(not(feature = "encoding"))]
+    #[test]
+    fn test_invalid_bytes() {
+        let file = b"[Desktop Entry]\nName=App\n\xFF\xFE invalid\nExec=app";
+        let parsed =
+            DesktopFile::parse_with_options(file, &ParserOptions::new().recover_invalid_lines())
+                .unwrap();
+
+        assert_eq!(
+            parsed.get("Desktop Entry").unwrap().content[1],
+            Entry::Invalid(b"\xFF\xFE invalid".to_vec())
+        );
+        assert_eq!(parsed.to_bytes(), file);
+        assert_eq!(
+            parsed.to_string(),
+            "[Desktop Entry]\nName=App\n\u{FFFD}\u{FFFD} invalid\nExec=app"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use alloc::{string::String, vec, vec::Vec};
+use memchr::memchr_iter;
+use nom::error::Error;
+
+use crate::{
+    error::{LocaleError, ParseError, ParseErrorKind},
+    parser::models::{DesktopFile, FileFormat, TopLevelEntry},
+};
+
+use super::{
+    ParserOptions, normalize_line_endings,
+    raw::{
+        check_entry_locale, is_key_character, parse_entry_locale, parse_key,
+        parse_top_level_entries_with,
+    },
+    recovery::{parse_recovering_with_positions, split_line},
+    strip_byte_order_mark,
+};
+
+#[cfg(feature = "encoding")]
+use super::encoding::transcode_legacy;
+
+/// What the parser did with a line it could not parse.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum RecoveryAction {
+    /// The line was kept in its group as an [Entry::Invalid](crate::parser::models::Entry::Invalid).
+    KeptAsInvalid,
+    /// The line was dropped, as well as the rest of the input.
+    Ignored,
+}
+
+/// A line the parser could not parse.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Recovery {
+    /// The number of the line, starting at 1.
+    pub line: usize,
+    /// The header of the group of the line, if any.
+    pub group: Option<String>,
+    /// The content of the line.
+    pub content: String,
+    /// Why the line could not be parsed.
+    pub kind: ParseErrorKind,
+    /// What the parser did with the line.
+    pub action: RecoveryAction,
+}
+
+/// Report of the lines the parser could not parse.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct ParseReport {
+    /// The lines that could not be parsed, in order.
+    pub recoveries: Vec<Recovery>,
+}
+
+impl ParseReport {
+    /// Returns whether every line was parsed.
+    pub fn is_clean(&self) -> bool {
+        self.recoveries.is_empty()
+    }
+
+    /// Returns the recoveries with this action.
+    pub fn with_action(&self, action: RecoveryAction) -> Vec<&Recovery> {
+        self.recoveries
+            .iter()
+            .filter(|r| r.action == action)
+            .collect()
+    }
+}
+
+impl DesktopFile {
+    /// Similar to [Self::parse_with_options], but also reports the lines that could not be parsed.
+    ///
+    /// Without [ParserOptions::recover_invalid_lines], parsing stops at the first invalid line and the rest is ignored.
+    pub fn parse_with_report(
+        value: &[u8],
+        options: &ParserOptions,
+    ) -> Result<(Self, ParseReport), nom::Err<Error<Vec<u8>>>> {
+        let (value, byte_order_mark) = strip_byte_order_mark(value);
+        #[cfg(feature = "encoding")]
+        let transcoded = transcode_legacy(value);
+        #[cfg(feature = "encoding")]
+        let value = transcoded.as_ref();
+        let (normalized, line_ending) = normalize_line_endings(value);
+        let value = normalized.as_ref();
+
+        let (rest, content, positions) = if options.recover_invalid_lines {
+            parse_recovering_with_positions(value, options.comment_parser())
+        } else {
+            let (rest, content) =
+                parse_top_level_entries_with(value, parse_key, options.comment_parser())
+                    .map_err(|e| e.to_owned())?;
+            (rest, content, vec![])
+        };
+
+        // The positions are increasing, so the lines are counted from the previous one
+        let mut counted = (0, 1);
+        let mut line_of = |position: usize| {
+            counted.1 += memchr_iter(b'\n', &value[counted.0..position]).count();
+            counted.0 = position;
+            counted.1
+        };
+        let mut recoveries: Vec<Recovery> = positions
+            .into_iter()
+            .map(|(position, header)| {
+                let line = split_line(&value[position..]).0;
+                Recovery {
+                    line: line_of(position),
+                    group: Some(header),
+                    content: String::from_utf8_lossy(line).into_owned(),
+                    kind: classify_line(line, true),
+                    action: RecoveryAction::KeptAsInvalid,
+                }
+            })
+            .collect();
+
+        let last_header = content.iter().rev().find_map(|tle| match tle {
+            TopLevelEntry::Group(group) => Some(group.header.clone()),
+            TopLevelEntry::Comment(_) => None,
+        });
+        let mut position = value.len() - rest.len();
+        let mut ignored = rest;
+        while !ignored.is_empty() {
+            let (line, next) = split_line(ignored);
+            // Only the first ignored line caused the parsing to stop
+            let kind = if position == value.len() - rest.len() {
+                classify_line(line, last_header.is_some())
+            } else {
+                ParseErrorKind::Unexpected
+            };
+            recoveries.push(Recovery {
+                line: line_of(position),
+                group: last_header.clone(),
+                content: String::from_utf8_lossy(line).into_owned(),
+                kind,
+                action: RecoveryAction::Ignored,
+            });
+            position += ignored.len() - next.len();
+            ignored = next;
+        }
+
+        let mut file = Self {
+            content,
+            format: FileFormat {
+                byte_order_mark,
+                line_ending,
+            },
+        };
+        file.apply_options(options);
+        Ok((file, ParseReport { recoveries }))
+    }
+
+    /// Parses raw data, and fails on the first line that cannot be parsed.
+    pub fn parse_strict(value: &[u8]) -> Result<Self, ParseError> {
+        let (file, report) =
+            Self::parse_with_report(value, &ParserOptions::new().recover_invalid_lines())
+                .expect("Recovering parsing cannot fail");
+
+        match report.recoveries.first() {
+            Some(recovery) => Err(ParseError {
+                kind: recovery.kind,
+                line: recovery.line,
+            }),
+            None => Ok(file),
+        }
+    }
+}
+
+/// Finds why a line could not be parsed.
+fn classify_line(line: &[u8], in_group: bool) -> ParseErrorKind {
+    let Ok(text) = str::from_utf8(line) else {
+        return ParseErrorKind::InvalidUtf8;
+    };
+
+    if text.starts_with('[') {
+        return if text.contains(']') {
+            ParseErrorKind::InvalidGroupHeader
+        } else {
+            ParseErrorKind::UnterminatedGroupHeader
+        };
+    }
+    let Some((key, value)) = text.split_once('=') else {
+        return ParseErrorKind::MissingEquals;
+    };
+
+    let key = key.trim_end();
+    let (name, locale) = match key.find('[') {
+        Some(start) => key.split_at(start),
+        None => (key, ""),
+    };
+    if name.is_empty() || !name.chars().all(is_key_character) {
+        return ParseErrorKind::InvalidKeyCharacter;
+    }
+    if !locale.is_empty() {
+        match parse_entry_locale(locale.as_bytes()) {
+            Ok(([], Some(_))) => {}
+            Ok((rest, _)) => {
+                let c = str::from_utf8(rest).ok().and_then(|r| r.chars().next());
+                return ParseErrorKind::InvalidLocale(LocaleError::UnexpectedCharacter(
+                    c.unwrap_or(char::REPLACEMENT_CHARACTER),
+                ));
+            }
+            Err(_) => {
+                let error = check_entry_locale(locale.as_bytes())
+                    .expect_err("The locale could not be parsed");
+                return ParseErrorKind::InvalidLocale(error);
+            }
+        }
+    }
+
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && !matches!(chars.next(), Some('n' | 'r' | 's' | 't' | '\\' | ';')) {
+            return ParseErrorKind::InvalidEscape;
+        }
+    }
+
+    if in_group {
+        ParseErrorKind::Unexpected
+    } else {
+        ParseErrorKind::EntryOutsideGroup
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    const FILE: &str = "[Desktop Entry]
+Name=App
+Not an entry
+Exec=app
+[Desktop Action new
+Exec=app --new
+";
+
+    #[test]
+    fn test_report_invalid_lines() {
+        let (_, report) = DesktopFile::parse_with_report(
+            FILE.as_bytes(),
+            &ParserOptions::new().recover_invalid_lines(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.recoveries,
+            vec![
+                Recovery {
+                    line: 3,
+                    group: Some(String::from("Desktop Entry")),
+                    content: String::from("Not an entry"),
+                    kind: ParseErrorKind::MissingEquals,
+                    action: RecoveryAction::KeptAsInvalid,
+                },
+                Recovery {
+                    line: 5,
+                    group: Some(String::from("Desktop Entry")),
+                    content: String::from("[Desktop Action new"),
+                    kind: ParseErrorKind::UnterminatedGroupHeader,
+                    action: RecoveryAction::KeptAsInvalid,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let error = |input: &str| DesktopFile::parse_strict(input.as_bytes()).unwrap_err();
+
+        assert!(DesktopFile::parse_strict(b"[Desktop Entry]\nName=App").is_ok());
+        assert_eq!(
+            error("[Desktop Entry]\nHello World=Yay"),
+            ParseError {
+                kind: ParseErrorKind::InvalidKeyCharacter,
+                line: 2
+            }
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nName[fr_]=Appli").kind,
+            ParseErrorKind::InvalidLocale(LocaleError::EmptyComponent('_'))
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nName[]=Appli").kind,
+            ParseErrorKind::InvalidLocale(LocaleError::Empty)
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nName[fr[x]]=Appli").kind,
+            ParseErrorKind::InvalidLocale(LocaleError::NestedBracket)
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nName[fr]x=Appli").kind,
+            ParseErrorKind::InvalidLocale(LocaleError::UnexpectedCharacter('x'))
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nName=\\x").kind,
+            ParseErrorKind::InvalidEscape
+        );
+        assert_eq!(
+            error("Name=App\n[Desktop Entry]").kind,
+            ParseErrorKind::EntryOutsideGroup
+        );
+        assert_eq!(
+            error("[Desktop Entry]\nName=App\nExec").to_string(),
+            "line 3: missing `=` between the key and the value"
+        );
+    }
+
+    #[test]
+    fn test_report_ignored_lines() {
+        let (file, report) =
+            DesktopFile::parse_with_report(FILE.as_bytes(), &ParserOptions::default()).unwrap();
+
+        assert_eq!(file, DesktopFile::try_from(FILE).unwrap());
+        assert_eq!(report.with_action(RecoveryAction::Ignored).len(), 4);
+        assert_eq!(report.recoveries[0].line, 3);
+        assert!(
+            DesktopFile::parse_with_report(b"[Empty]\n", &ParserOptions::default())
+                .unwrap()
+                .1
+                .is_clean()
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use schemars::{Schema, json_schema};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    helpers::desktop_entry::{Key, ValueType},
+    parser::models::DesktopFile,
+};
+
+/// Keys of the `[Desktop Action <action>]` groups.
+const ACTION_KEYS: &[Key] = &[Key::Name, Key::Icon, Key::Exec];
+
+/// Returns the schema of a value of the type, as represented by [DesktopFile::to_json].
+fn value_schema(value_type: ValueType) -> Value {
+    match value_type {
+        ValueType::String | ValueType::LocaleString | ValueType::IconString => {
+            json!({"type": "string"})
+        }
+        ValueType::Boolean => json!({"anyOf": [{"type": "boolean"}, {"enum": ["true", "false"]}]}),
+        ValueType::Numeric => json!({"anyOf": [
+            {"type": "number"},
+            {"type": "string", "pattern": "^[+-]?([0-9]+\\.?[0-9]*|\\.[0-9]+)([eE][+-]?[0-9]+)?$"},
+        ]}),
+        // A single value without a trailing separator is represented as a string
+        ValueType::Strings | ValueType::LocaleStrings => json!({"anyOf": [
+            {"type": "array", "items": {"type": "string"}},
+            {"type": "string"},
+        ]}),
+    }
+}
+
+/// Returns the schema of a group of the keys, whose localized keys may be written with a locale
+/// and which may contain extension keys starting with `X-`.
+fn group_schema(keys: &[Key], required: &[Key]) -> Value {
+    let mut properties = Map::new();
+    let mut patterns = Map::new();
+    for key in keys {
+        let value_type = ValueType::of_key(key.as_str()).unwrap_or(key.value_type());
+        let value = value_schema(value_type);
+        if value_type.is_localized() {
+            patterns.insert(format!("^{key}\\[[^\\]]+\\]$"), value.clone());
+        }
+        properties.insert(key.to_string(), value);
+    }
+    patterns.insert(String::from("^X-"), json!({"$ref": "#/$defs/Value"}));
+    json!({
+        "type": "object",
+        "properties": properties,
+        "patternProperties": patterns,
+        "additionalProperties": false,
+        "required": required.iter().map(Key::as_str).collect::<Vec<_>>(),
+    })
+}
+
+impl DesktopFile {
+    /// Returns the JSON Schema of desktop entry files, as represented by [Self::to_json].
+    ///
+    /// The `[Desktop Entry]` group is required and typed by the standard [Key]s, which must be
+    /// of their [ValueType], along with the `[Desktop Action <action>]` groups. Other keys are
+    /// only allowed if they start with `X-`, and other groups may contain any key.
+    /// This allows tools which are not written in Rust to validate the generated entries against the same model.
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let schema = DesktopFile::json_schema();
+    /// assert_eq!(
+    ///     schema.pointer("/$defs/DesktopEntry/properties/Terminal/anyOf/0/type"),
+    ///     Some(&"boolean".into())
+    /// );
+    /// ```
+    pub fn json_schema() -> Schema {
+        json_schema!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Desktop Entry",
+            "description": "A desktop entry file, whose groups are objects of their keys",
+            "type": "object",
+            "properties": {"Desktop Entry": {"$ref": "#/$defs/DesktopEntry"}},
+            "patternProperties": {"^Desktop Action .+$": {"$ref": "#/$defs/DesktopAction"}},
+            "additionalProperties": {"$ref": "#/$defs/Group"},
+            "required": ["Desktop Entry"],
+            "$defs": {
+                "DesktopEntry": group_schema(Key::ALL, &[Key::Type, Key::Name]),
+                "DesktopAction": group_schema(ACTION_KEYS, &[Key::Name]),
+                "Group": {"type": "object", "additionalProperties": {"$ref": "#/$defs/Value"}},
+                "Value": {"anyOf": [
+                    {"type": ["string", "boolean", "number"]},
+                    {"type": "array", "items": {"type": "string"}},
+                ]},
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_schema() {
+        let schema = DesktopFile::json_schema();
+        let entry = schema.pointer("/$defs/DesktopEntry").unwrap();
+
+        assert_eq!(
+            entry["properties"].as_object().unwrap().len(),
+            Key::ALL.len()
+        );
+        assert_eq!(entry["properties"]["Exec"], json!({"type": "string"}));
+        assert_eq!(
+            entry["properties"]["Categories"]["anyOf"][0]["type"],
+            "array"
+        );
+        assert!(entry["patternProperties"]["^Keywords\\[[^\\]]+\\]$"].is_object());
+        assert!(entry["patternProperties"]["^Exec\\[[^\\]]+\\]$"].is_null());
+        assert_eq!(entry["required"], json!(["Type", "Name"]));
+
+        let action = schema.pointer("/$defs/DesktopAction/properties").unwrap();
+        assert_eq!(action.as_object().unwrap().len(), ACTION_KEYS.len());
+
+        let schema = serde_json::to_value(&schema).unwrap();
+        assert_eq!(schema["required"], json!(["Desktop Entry"]));
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+use crate::parser::models::Locale;
+
+/// Serializes the locale as written in files, such as `sr_RS@latin`.
+impl Serialize for Locale {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes a locale as written in files, failing if it is not valid.
+impl<'de> Deserialize<'de> for Locale {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let locale = String::deserialize(deserializer)?;
+        locale
+            .parse()
+            .map_err(|error| D::Error::custom(format!("invalid locale {locale:?}: {error}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::models::{ContentEntry, DesktopFile};
+
+    use super::*;
+
+    #[test]
+    fn test_serde() {
+        let file = DesktopFile::try_from(
+            "# Comment\n[Desktop Entry]\nName = App\nName[sr@latin]=Apl\nCategories=A;B;\n",
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&file).unwrap();
+        let deserialized: DesktopFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, file);
+        assert_eq!(deserialized.to_string(), file.to_string());
+
+        let entry: ContentEntry = "Name[sr_RS@latin]=Apl".parse().unwrap();
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["locale"], "sr_RS@latin");
+        assert!(serde_json::from_str::<Locale>("\"sr_\"").is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::parser::{
+    DuplicatePolicy,
+    models::{CommentEntry, DesktopFile, Entry, TopLevelEntry},
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Statistics of a [DesktopFile], see [DesktopFile::stats].
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct FileStats {
+    /// The number of groups.
+    pub groups: usize,
+    /// The number of key-value entries, duplicates included.
+    pub entries: usize,
+    /// The number of comment lines.
+    pub comments: usize,
+    /// The number of blank lines.
+    pub blank_lines: usize,
+    /// The number of lines which could not be parsed and were kept as is.
+    pub invalid_lines: usize,
+    /// The number of bytes taken by the comments, the blank lines and the invalid lines when the file is written.
+    pub overhead: usize,
+    /// The header of the group and the key, with its locale, of each entry which repeats a previous entry.
+    pub duplicate_keys: Vec<(String, String)>,
+    /// The number of bytes of the written file.
+    pub size: usize,
+}
+
+impl FileStats {
+    fn count_comment(&mut self, comment: &CommentEntry) {
+        match comment {
+            CommentEntry::Text(text) => {
+                self.comments += 1;
+                // The text is written after `# ` and followed by a line break
+                self.overhead += text.len() + 3;
+            }
+            CommentEntry::Blank(lines) => {
+                self.blank_lines += lines.matches('\n').count();
+                self.overhead += lines.len();
+            }
+        }
+    }
+}
+
+impl DesktopFile {
+    /// Returns statistics of the file, to audit large or generated files.
+    pub fn stats(&self) -> FileStats {
+        let mut stats = FileStats {
+            size: self.to_string().len(),
+            ..FileStats::default()
+        };
+        for top_level_entry in &self.content {
+            let group = match top_level_entry {
+                TopLevelEntry::Group(group) => group,
+                TopLevelEntry::Comment(comment) => {
+                    stats.count_comment(comment);
+                    continue;
+                }
+            };
+            stats.groups += 1;
+
+            let mut keys: Vec<(&str, _)> = vec![];
+            for entry in &group.content {
+                match entry {
+                    Entry::Content(content) => {
+                        stats.entries += 1;
+                        for comment in content.doc_comments() {
+                            stats.count_comment(&CommentEntry::Text(comment.clone()));
+                        }
+                        let key = (content.key.as_str(), &content.locale);
+                        if keys.contains(&key) {
+                            let written = match &content.locale {
+                                Some(locale) => format!("{}[{locale}]", content.key),
+                                None => content.key.clone(),
+                            };
+                            stats.duplicate_keys.push((group.header.clone(), written));
+                        } else {
+                            keys.push(key);
+                        }
+                    }
+                    Entry::Comment(comment) => stats.count_comment(comment),
+                    Entry::Invalid(line) => {
+                        stats.invalid_lines += 1;
+                        stats.overhead += line.len() + 1;
+                    }
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Shrinks the file to its meaningful content, as it is read by this crate.
+    ///
+    /// This is [DesktopFile::minified] with [DuplicatePolicy::FirstWins], applied in place:
+    /// comments, blank lines and invalid lines are removed, the entries which repeat a previous entry
+    /// of their group are removed as only the first one is found, and the entries are written without
+    /// the spaces which surrounded their `=` or their values.
+    pub fn compact(&mut self) {
+        self.minify(DuplicatePolicy::FirstWins);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ParserOptions;
+
+    use super::*;
+
+    #[test]
+    fn test_stats_and_compact() {
+        let input = b"# Generated
+
+[A]
+Key = value
+Key[fr]=valeur
+# Duplicate
+Key=other
+not an entry
+[B]
+Key=1";
+        let mut file =
+            DesktopFile::parse_with_options(input, &ParserOptions::new().recover_invalid_lines())
+                .unwrap();
+
+        let stats = file.stats();
+        assert_eq!(
+            stats,
+            FileStats {
+                groups: 2,
+                entries: 4,
+                comments: 2,
+                blank_lines: 1,
+                invalid_lines: 1,
+                overhead: 12 + 1 + 12 + 13,
+                duplicate_keys: vec![(String::from("A"), String::from("Key"))],
+                size: input.len(),
+            }
+        );
+
+        file.compact();
+        assert_eq!(
+            file.to_string(),
+            "[A]\nKey=value\nKey[fr]=valeur\n[B]\nKey=1"
+        );
+        let stats = file.stats();
+        assert_eq!(
+            (stats.overhead, stats.duplicate_keys.len(), stats.size),
+            (0, 0, 38)
+        );
+    }
+}
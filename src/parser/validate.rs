@@ -0,0 +1,510 @@
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+use core::fmt::Display;
+
+use crate::{
+    helpers::desktop_entry::{
+        Category, EntryType, Key, MimeType, ValueType,
+        exec::{decoded, tokenize},
+    },
+    parser::models::{ContentEntry, DesktopFile, EntrySet, Group, MAIN_GROUP},
+};
+
+/// The prefix of the headers of the groups of actions.
+const ACTION_PREFIX: &str = "Desktop Action ";
+
+/// The keys of the groups of actions.
+const ACTION_KEYS: &[Key] = &[Key::Name, Key::Icon, Key::Exec];
+
+/// Keys of the previous versions of the specification, which are now ignored.
+const DEPRECATED_KEYS: &[&str] = &[
+    "Encoding",
+    "MiniIcon",
+    "TerminalOptions",
+    "Protocols",
+    "Extensions",
+    "BinaryPattern",
+    "MapNotify",
+    "SwallowTitle",
+    "SwallowExec",
+    "SortOrder",
+    "FilePattern",
+];
+
+/// How serious a [Diagnostic] is.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The file does not follow the specification.
+    Error,
+    /// The file follows the specification, but not its recommendations.
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found by [DesktopFile::validate].
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The header of the group in which the problem was found.
+    pub group: String,
+    /// The key of the entry with the problem, with its locale if any, or `None` if the whole group has the problem.
+    pub key: Option<String>,
+    /// The description of the problem.
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    /// Writes the diagnostic like `desktop-file-validate`, such as `error: [Desktop Entry] Name[fr]: ...`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: [{}]", self.severity, self.group)?;
+        if let Some(key) = &self.key {
+            write!(f, " {key}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl DesktopFile {
+    /// Checks that the file follows the specification, and returns the problems found, in the order of the file.
+    ///
+    /// The file is valid if there is no diagnostic of [Severity::Error].
+    /// The `[Desktop Entry]` group is checked for the keys required and allowed by its type,
+    /// and with the groups of actions, for unknown keys not starting with `X-`, deprecated keys,
+    /// values which do not match the type of their key and invalid field codes in `Exec`.
+    /// With the `std` feature, the keys and their types are looked up in the
+    /// [KEY_REGISTRY](crate::helpers::desktop_entry::KEY_REGISTRY), and MIME types whose media type is not in the
+    /// [MIME_REGISTRY](crate::helpers::desktop_entry::MIME_REGISTRY) are reported as warnings.
+    /// Translated keys without a default value are reported in every group.
+    /// ```
+    /// use freedesktop_rs::parser::{Severity, models::DesktopFile};
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nType=Directory\nName=Menu\nComment[fr]=Jeux\n").unwrap();
+    /// let diagnostics = file.validate();
+    ///
+    /// assert_eq!(diagnostics[0].severity, Severity::Error);
+    /// assert_eq!(diagnostics[0].key.as_deref(), Some("Comment[fr]"));
+    /// ```
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for group in self.groups() {
+            if group.header == MAIN_GROUP {
+                check_entry_type(group, &mut diagnostics);
+                check_keys(group, None, &mut diagnostics);
+                check_categories(group, &mut diagnostics);
+                check_mime_types(group, &mut diagnostics);
+            } else if group.header.starts_with(ACTION_PREFIX) {
+                check_keys(group, Some(ACTION_KEYS), &mut diagnostics);
+            }
+            check_localized_defaults(group, &mut diagnostics);
+        }
+        diagnostics
+    }
+}
+
+/// Checks that the keys required by the type of the entry are present, and that the other keys are allowed by it.
+fn check_entry_type(group: &Group, diagnostics: &mut Vec<Diagnostic>) {
+    let error = |key: Option<&str>, message: String| Diagnostic {
+        severity: Severity::Error,
+        group: group.header.to_owned(),
+        key: key.map(ToOwned::to_owned),
+        message,
+    };
+    let entry_type = group
+        .find_preferred(Key::Type.as_str(), &[])
+        .and_then(|entry| entry.as_string().ok())
+        .map(|value| EntryType::from(&*value))
+        .unwrap_or(EntryType::Unknown(String::new()));
+
+    for key in entry_type.required_keys() {
+        if group.find_preferred(key.as_str(), &[]).is_none() {
+            diagnostics.push(error(
+                Some(key.as_str()),
+                String::from("the required key is missing"),
+            ));
+        }
+    }
+    let dbus_activatable = group
+        .find(Key::DBusActivatable.as_str())
+        .is_some_and(|entry| entry.as_bool().unwrap_or(false));
+    if entry_type.requires_exec(dbus_activatable) && group.find(Key::Exec.as_str()).is_none() {
+        diagnostics.push(error(
+            Some(Key::Exec.as_str()),
+            String::from("the key is required by applications which are not D-Bus activatable"),
+        ));
+    }
+
+    for entry in group.entries() {
+        let Ok(key) = entry.key.parse::<Key>() else {
+            continue;
+        };
+        if !entry_type.allows(key) {
+            diagnostics.push(error(
+                Some(&entry.key),
+                format!("the key is not allowed in entries of type {entry_type}"),
+            ));
+        }
+    }
+}
+
+/// Checks that the keys are either known, or extensions starting with `X-`, and that their values match their type.
+///
+/// The known keys are those typed by [ValueType::of_key], or only the allowed ones if any.
+fn check_keys(group: &Group, allowed: Option<&[Key]>, diagnostics: &mut Vec<Diagnostic>) {
+    for entry in group.entries() {
+        let mut report = |severity: Severity, message: String| {
+            diagnostics.push(Diagnostic {
+                severity,
+                group: group.header.to_owned(),
+                key: Some(key_name(entry)),
+                message,
+            })
+        };
+        if DEPRECATED_KEYS.contains(&entry.key.as_str()) {
+            report(Severity::Warning, String::from("the key is deprecated"));
+            continue;
+        }
+        let value_type = match allowed {
+            Some(allowed) if !allowed.iter().any(|key| key.as_str() == entry.key) => None,
+            _ => ValueType::of_key(&entry.key),
+        };
+        let Some(value_type) = value_type else {
+            if !entry.key.starts_with("X-") {
+                report(
+                    Severity::Error,
+                    String::from(
+                        "the key is not defined by the specification, and extensions must start with `X-`",
+                    ),
+                );
+            }
+            continue;
+        };
+
+        if value_type.is_list() {
+            continue;
+        }
+        let Ok(value) = entry.as_string() else {
+            report(
+                Severity::Error,
+                format!(
+                    "the key has a single value, but {} values",
+                    entry.values.len()
+                ),
+            );
+            continue;
+        };
+        match value_type {
+            ValueType::Boolean if entry.as_bool().is_err() => report(
+                Severity::Error,
+                format!("`{value}` is not a boolean, which is either `true` or `false`"),
+            ),
+            ValueType::String => {
+                if let Some(c) = value.chars().find(|c| !c.is_ascii()) {
+                    report(
+                        Severity::Error,
+                        format!("the value contains the non-ASCII character {c:?}"),
+                    );
+                }
+            }
+            _ => {}
+        }
+        if entry.key == Key::Exec.as_str()
+            && let Ok(exec) = decoded(entry)
+            && let Err(error) = tokenize(&exec)
+        {
+            report(Severity::Error, format!("{error}"));
+        }
+    }
+}
+
+/// Checks that the categories are registered or start with `X-`, and are used with the categories they are related to.
+fn check_categories(group: &Group, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(entry) = group.find_preferred(Key::Categories.as_str(), &[]) else {
+        return;
+    };
+    let mut report = |severity: Severity, message: String| {
+        diagnostics.push(Diagnostic {
+            severity,
+            group: group.header.to_owned(),
+            key: Some(key_name(entry)),
+            message,
+        })
+    };
+    let categories: Vec<Category> = entry
+        .values
+        .iter()
+        .map(|category| Category::from(category.as_str()))
+        .collect();
+    let only_show_in = group.find(Key::OnlyShowIn.as_str()).is_some();
+
+    for (i, category) in categories.iter().enumerate() {
+        if categories[..i].contains(category) {
+            report(
+                Severity::Warning,
+                format!("the category {category} is listed several times"),
+            );
+        } else if category.is_unknown() {
+            report(
+                Severity::Error,
+                format!(
+                    "the category {category} is not registered, and extensions must start with `X-`"
+                ),
+            );
+        } else if category.is_reserved() && !only_show_in {
+            report(
+                Severity::Error,
+                format!("the category {category} is reserved, and requires `OnlyShowIn`"),
+            );
+        } else {
+            let related = category.related();
+            if !related.is_empty() && !related.iter().any(|c| categories.contains(c)) {
+                let related: Vec<&str> = related.iter().map(Category::as_str).collect();
+                report(
+                    Severity::Warning,
+                    format!(
+                        "the category {category} should be used with one of the categories {}",
+                        related.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Checks the syntax of the MIME types.
+fn check_mime_types(group: &Group, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(entry) = group.find_preferred(Key::MimeType.as_str(), &[]) else {
+        return;
+    };
+    for mime_type in &entry.values {
+        let (severity, message) = match mime_type.parse::<MimeType>() {
+            Err(_) => (
+                Severity::Error,
+                format!("{mime_type} is not a valid MIME type, such as `image/png`"),
+            ),
+            #[cfg(feature = "std")]
+            Ok(parsed) if !parsed.is_registered() => (
+                Severity::Warning,
+                format!("the media type of {mime_type} is not registered"),
+            ),
+            Ok(_) => continue,
+        };
+        diagnostics.push(Diagnostic {
+            severity,
+            group: group.header.to_owned(),
+            key: Some(key_name(entry)),
+            message,
+        });
+    }
+}
+
+/// Returns the key of the entry, with its locale if any.
+fn key_name(entry: &ContentEntry) -> String {
+    match &entry.locale {
+        Some(locale) => format!("{}[{locale}]", entry.key),
+        None => entry.key.to_owned(),
+    }
+}
+
+/// Checks that every translated key has an unlocalized entry, as the specification requires.
+fn check_localized_defaults(group: &Group, diagnostics: &mut Vec<Diagnostic>) {
+    for entry in group.entries() {
+        if entry.locale.is_some()
+            && group
+                .localizations(&entry.key)
+                .all(|(locale, _)| locale.is_some())
+        {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                group: group.header.to_owned(),
+                key: Some(key_name(entry)),
+                message: format!("the translated key has no default value `{}=`", entry.key),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_entry_type() {
+        let diagnostics = |file: &str| -> Vec<String> {
+            DesktopFile::try_from(file)
+                .unwrap()
+                .validate()
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+
+        assert_eq!(
+            diagnostics("[Desktop Entry]\nExec=app\n"),
+            [
+                "error: [Desktop Entry] Type: the required key is missing",
+                "error: [Desktop Entry] Name: the required key is missing",
+            ]
+        );
+        assert_eq!(
+            diagnostics("[Desktop Entry]\nType=Application\nName=App\nURL=https://example.org\n"),
+            [
+                "error: [Desktop Entry] Exec: the key is required by applications which are not D-Bus activatable",
+                "error: [Desktop Entry] URL: the key is not allowed in entries of type Application",
+            ]
+        );
+        assert_eq!(
+            diagnostics("[Desktop Entry]\nType=Link\nName=Site\nTerminal=true\n"),
+            [
+                "error: [Desktop Entry] URL: the required key is missing",
+                "error: [Desktop Entry] Terminal: the key is not allowed in entries of type Link",
+            ]
+        );
+        assert!(
+            diagnostics("[Desktop Entry]\nType=Application\nName=App\nDBusActivatable=true\n")
+                .is_empty()
+        );
+        assert!(diagnostics("[Desktop Entry]\nType=Service\nName=App\nX-Key=1\n").is_empty());
+    }
+
+    #[test]
+    fn test_keys() {
+        let file = DesktopFile::try_from(
+            "[Desktop Entry]
+Type=Application
+Name=App
+Exec=sh -c 'app; app' %d
+Terminal=yes
+TryExec=appé
+Encoding=UTF-8
+Colour=red
+X-Colour=red
+Categories=Game;
+[Desktop Action new]
+Name=New
+Exec=app --new %F%U
+Terminal=true
+[X-Other]
+Colour=red
+",
+        )
+        .unwrap();
+        let diagnostics: Vec<String> = file.validate().iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            diagnostics,
+            [
+                "error: [Desktop Entry] Exec: deprecated field code %d",
+                "error: [Desktop Entry] Terminal: `yes` is not a boolean, which is either `true` or `false`",
+                "error: [Desktop Entry] TryExec: the value contains the non-ASCII character 'é'",
+                "warning: [Desktop Entry] Encoding: the key is deprecated",
+                "error: [Desktop Entry] Colour: the key is not defined by the specification, and extensions must start with `X-`",
+                "error: [Desktop Action new] Exec: the field code %F must be an argument of its own",
+                "error: [Desktop Action new] Terminal: the key is not defined by the specification, and extensions must start with `X-`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_categories() {
+        let diagnostics = |categories: &str| -> Vec<String> {
+            DesktopFile::try_from(
+                format!("[Desktop Entry]\nType=Application\nName=App\nExec=app\nCategories={categories}\n")
+                    .as_str(),
+            )
+            .unwrap()
+            .validate()
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+        };
+
+        assert!(diagnostics("AudioVideo;Audio;Player;X-Custom;").is_empty());
+        assert_eq!(
+            diagnostics("Game;Games;ArcadeGame;Game;TrayIcon;Video;"),
+            [
+                "the category Games is not registered, and extensions must start with `X-`",
+                "the category Game is listed several times",
+                "the category TrayIcon is reserved, and requires `OnlyShowIn`",
+                "the category Video should be used with one of the categories AudioVideo",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mime_types() {
+        let file = DesktopFile::try_from(
+            "[Desktop Entry]\nType=Application\nName=App\nExec=app %f\nMimeType=text/plain;image;text/*;\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            file.validate()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "error: [Desktop Entry] MimeType: image is not a valid MIME type, such as `image/png`"
+            ]
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_registries() {
+        use crate::helpers::desktop_entry::KEY_REGISTRY;
+
+        KEY_REGISTRY.register("X-Validate-Flag", ValueType::Boolean);
+        KEY_REGISTRY.register("ValidateRegistered", ValueType::String);
+        let file = DesktopFile::try_from(
+            "[Desktop Entry]\nType=Application\nName=App\nExec=app %f\nX-Validate-Flag=maybe\nValidateRegistered=yes\nMimeType=vendor/thing;\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            file.validate()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "error: [Desktop Entry] X-Validate-Flag: `maybe` is not a boolean, which is either `true` or `false`",
+                "warning: [Desktop Entry] MimeType: the media type of vendor/thing is not registered",
+            ]
+        );
+        KEY_REGISTRY.unregister("X-Validate-Flag");
+        KEY_REGISTRY.unregister("ValidateRegistered");
+    }
+
+    #[test]
+    fn test_localized_defaults() {
+        let file = DesktopFile::try_from(
+            "[Desktop Entry]\nType=Application\nExec=app\nName=App\nName[fr]=Appli\nComment[fr]=Texte\nComment[de]=Text\n[Desktop Action new]\nName[fr]=Nouveau\n",
+        )
+        .unwrap();
+        let diagnostics: Vec<String> = file.validate().iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            diagnostics,
+            [
+                "error: [Desktop Entry] Comment[fr]: the translated key has no default value `Comment=`",
+                "error: [Desktop Entry] Comment[de]: the translated key has no default value `Comment=`",
+                "error: [Desktop Action new] Name[fr]: the translated key has no default value `Name=`",
+            ]
+        );
+        assert!(
+            DesktopFile::try_from("[A]\nName[fr]=Appli\nName=App\n")
+                .unwrap()
+                .validate()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use alloc::{format, string::String};
+
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "std")]
+use crate::{error::Error, parser::models::DesktopFile};
+
+#[cfg(feature = "std")]
+use super::ParserOptions;
+
+/// Returns the SHA-256 hash of the data as lowercase hexadecimal, as printed by `sha256sum`.
+///
+/// This is the hash expected by [DesktopFile::open_verified], so that pinned files can be hashed with the same function.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl DesktopFile {
+    /// Reads and parses the file only if its SHA-256 hash is the expected one, see [sha256_hex].
+    ///
+    /// The expected hash is written in hexadecimal, in any case, as printed by `sha256sum`.
+    /// This lets managed desktops pin the entries shipped by a vendor:
+    /// a file which was modified since it was pinned fails with [Error::Tampered], without being parsed.
+    pub fn open_verified<P: AsRef<Path>>(path: P, expected_hash: &str) -> Result<Self, Error> {
+        Self::open_verified_with_options(path, expected_hash, &ParserOptions::default())
+    }
+
+    /// Similar to [Self::open_verified], but with the given options.
+    pub fn open_verified_with_options<P: AsRef<Path>>(
+        path: P,
+        expected_hash: &str,
+        options: &ParserOptions,
+    ) -> Result<Self, Error> {
+        let content = fs::read(path).map_err(Error::Io)?;
+        let actual = sha256_hex(&content);
+        let expected = expected_hash.trim().to_ascii_lowercase();
+        if actual != expected {
+            return Err(Error::Tampered { expected, actual });
+        }
+
+        Self::parse_with_options(&content, options).map_err(Error::Parsing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_open_verified() {
+        let content = "[Desktop Entry]\nType=Application\nName=Pinned\n";
+        let path = std::env::temp_dir().join(format!(
+            "freedesktop-rs-verified-{}.desktop",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        let hash = sha256_hex(content.as_bytes());
+
+        let parsed = DesktopFile::open_verified(&path, &hash.to_uppercase()).unwrap();
+        assert_eq!(parsed, DesktopFile::try_from(content).unwrap());
+
+        fs::write(&path, content.replace("Pinned", "Changed")).unwrap();
+        let error = DesktopFile::open_verified(&path, &hash).unwrap_err();
+        assert!(matches!(error, Error::Tampered { expected, .. } if expected == hash));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
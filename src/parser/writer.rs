@@ -0,0 +1,301 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::Path,
+};
+
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    error::Error,
+    helpers::permissions::replace_file,
+    parser::models::{DesktopFile, EndsWithLineBreak, Group, LineEnding},
+};
+
+use super::{BYTE_ORDER_MARK, detect_line_ending};
+
+impl DesktopFile {
+    /// Writes the file to the writer, as returned by [DesktopFile::to_bytes], without building the whole file first.
+    ///
+    /// The file is written one top-level entry at a time, so the writer should be buffered, such as a [std::io::BufWriter].
+    /// ```
+    /// use freedesktop_rs::parser::models::DesktopFile;
+    ///
+    /// let file = DesktopFile::try_from("[Desktop Entry]\nName=App\n").unwrap();
+    /// let mut written = vec![];
+    /// file.write_to(&mut written).unwrap();
+    ///
+    /// assert_eq!(written, b"[Desktop Entry]\nName=App");
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        self.write_entries(&mut writer).map_err(Error::Io)
+    }
+
+    fn write_entries<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.format.byte_order_mark {
+            writer.write_all("\u{feff}".as_bytes())?;
+        }
+        let mut peekable = self.content.iter().peekable();
+        while let Some(item) = peekable.next() {
+            writer.write_all(&item.to_bytes(self.format.line_ending))?;
+            if peekable.peek().is_some() && !item.ends_with_line_break() {
+                writer.write_all(self.format.line_ending.as_str().as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the file at the path, followed by its line ending, replacing the previous file atomically.
+    ///
+    /// The file is written to a temporary file in the same directory, synchronized to the disk and then renamed,
+    /// so that a crash never leaves a partially written file. The permissions of the previous file are kept,
+    /// and if the path is a symbolic link, the file it points to is replaced.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        replace_file(path.as_ref(), false, |writer| {
+            self.write_entries(writer)?;
+            writer.write_all(self.format.line_ending.as_str().as_bytes())
+        })
+    }
+
+    /// Replaces the group with the same header in the file at the path, without rewriting the rest of the file.
+    ///
+    /// The file is read again, and only the lines of the first group with this header, up to its last entry,
+    /// are replaced, so that the groups maintained by other programs and the comments heading them are kept byte for byte.
+    /// The group is added at the end of the file if it is missing, and the file is created if it does not exist.
+    /// The file is replaced atomically, as by [Self::save], and it is read again if another program modified it
+    /// in the meantime, so that its changes are not lost.
+    /// ```no_run
+    /// use freedesktop_rs::parser::models::{DesktopFile, Group};
+    ///
+    /// let mut defaults = Group::new("Default Applications");
+    /// defaults.set_list("text/plain", ["editor.desktop"]);
+    /// DesktopFile::save_group("/home/user/.config/mimeapps.list", &defaults).unwrap();
+    /// ```
+    pub fn save_group<P: AsRef<Path>>(path: P, group: &Group) -> Result<(), Error> {
+        let path = path.as_ref();
+        for _ in 0..SAVE_ATTEMPTS {
+            if try_save_group(path, group)? {
+                return Ok(());
+            }
+        }
+        Err(Error::Io(io::Error::other(format!(
+            "{} kept being modified by another program",
+            path.display()
+        ))))
+    }
+
+    /// Similar to [Self::write_to], but with an asynchronous writer.
+    ///
+    /// The file is written one group at a time, so that only the current group is buffered.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[cfg(feature = "tokio")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, mut writer: W) -> Result<(), Error> {
+        if self.format.byte_order_mark {
+            writer
+                .write_all("\u{feff}".as_bytes())
+                .await
+                .map_err(Error::Io)?;
+        }
+        let mut peekable = self.content.iter().peekable();
+        while let Some(item) = peekable.next() {
+            let mut bytes = item.to_bytes(self.format.line_ending);
+            if peekable.peek().is_some() && !item.ends_with_line_break() {
+                bytes.extend_from_slice(self.format.line_ending.as_str().as_bytes());
+            }
+            writer.write_all(&bytes).await.map_err(Error::Io)?;
+        }
+        writer.flush().await.map_err(Error::Io)
+    }
+}
+
+/// How many times [DesktopFile::save_group] reads the file again when it is modified by another program.
+const SAVE_ATTEMPTS: usize = 3;
+
+/// Replaces the group in the file, unless it was modified since it was read, in which case `false` is returned.
+fn try_save_group(path: &Path, group: &Group) -> Result<bool, Error> {
+    let modified = || fs::metadata(path).and_then(|m| m.modified()).ok();
+    let read_modified = modified();
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => vec![],
+        Err(error) => return Err(Error::Io(error)),
+    };
+    let line_ending = detect_line_ending(&content);
+
+    let mut written = group.to_string();
+    if !written.ends_with('\n') {
+        written.push('\n');
+    }
+    let (start, end) = match group_range(&content, &group.header) {
+        Some(range) => range,
+        None => {
+            if !content.is_empty() && !content.ends_with(b"\n") {
+                written.insert(0, '\n');
+            }
+            (content.len(), content.len())
+        }
+    };
+    if line_ending == LineEnding::CrLf {
+        written = written.replace('\n', "\r\n");
+    }
+
+    let mut conflict = false;
+    let saved = replace_file(path, false, |writer| {
+        // Checked again right before the file is replaced
+        if modified() != read_modified {
+            conflict = true;
+            return Err(io::Error::other("the file was modified"));
+        }
+        writer.write_all(&content[..start])?;
+        writer.write_all(written.as_bytes())?;
+        writer.write_all(&content[end..])
+    });
+    match saved {
+        Err(_) if conflict => Ok(false),
+        saved => saved.map(|_| true),
+    }
+}
+
+/// Returns the byte range of the first group with the header in the content, from its header to its last entry.
+///
+/// The blank lines and comments after the last entry are left out of the range, as they may head the next group.
+fn group_range(content: &[u8], header: &str) -> Option<(usize, usize)> {
+    let mut start = None;
+    let mut end = 0;
+    let mut position = 0;
+    for line in content.split_inclusive(|b| *b == b'\n') {
+        // The byte order mark stays before the group
+        let without_mark = line.strip_prefix(BYTE_ORDER_MARK).unwrap_or(line);
+        let mark = line.len() - without_mark.len();
+        let text = without_mark.strip_suffix(b"\n").unwrap_or(without_mark);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        if text.first() == Some(&b'[') {
+            if start.is_some() {
+                return start.map(|start| (start, end));
+            }
+            let is_header = text
+                .strip_prefix(b"[")
+                .and_then(|text| text.strip_suffix(b"]"))
+                .is_some_and(|name| name == header.as_bytes());
+            if is_header {
+                start = Some(position + mark);
+            }
+        }
+        position += line.len();
+        let blank = text.iter().all(u8::is_ascii_whitespace);
+        if !blank && text.trim_ascii_start().first() != Some(&b'#') {
+            end = position;
+        }
+    }
+    start.map(|start| (start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::models::EntrySet;
+
+    const FILE: &str = "\u{feff}# Outside comment
+[Desktop Entry]
+Type=Application
+
+[Desktop Action new]
+Name=New window
+Exec=app --new
+";
+
+    #[test]
+    fn test_write_to() {
+        let file = DesktopFile::try_from(FILE).unwrap();
+        let mut written = vec![];
+        file.write_to(&mut written).unwrap();
+
+        assert_eq!(String::from_utf8(written).unwrap(), file.to_string());
+    }
+
+    #[test]
+    fn test_save() {
+        let root = std::env::temp_dir().join(format!("freedesktop-rs-save-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("app.desktop");
+        let mut file = DesktopFile::try_from(FILE).unwrap();
+
+        file.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{file}\n"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        file.get_mut("Desktop Entry").unwrap().set("Name", "App");
+        file.save(&path).unwrap();
+        assert_eq!(
+            DesktopFile::try_from(fs::read_to_string(&path).unwrap().as_str()).unwrap(),
+            file
+        );
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+                0o640
+            );
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_save_group() {
+        let root =
+            std::env::temp_dir().join(format!("freedesktop-rs-save-group-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("mimeapps.list");
+        fs::write(
+            &path,
+            "\u{feff}[Default Applications]\ntext/plain=old.desktop\n# Heading the next group\n\n[Added Associations]\n#  kept   as is\ntext/plain = a.desktop;b.desktop;\n",
+        )
+        .unwrap();
+
+        let mut defaults = Group::new("Default Applications");
+        defaults.set_list("text/plain", ["editor.desktop"]);
+        DesktopFile::save_group(&path, &defaults).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}[Default Applications]\ntext/plain=editor.desktop;\n# Heading the next group\n\n[Added Associations]\n#  kept   as is\ntext/plain = a.desktop;b.desktop;\n"
+        );
+
+        let mut removed = Group::new("Removed Associations");
+        removed.set_list("text/plain", ["b.desktop"]);
+        DesktopFile::save_group(&path, &removed).unwrap();
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .ends_with("b.desktop;\n[Removed Associations]\ntext/plain=b.desktop;\n")
+        );
+
+        let created = root.join("created.list");
+        DesktopFile::save_group(&created, &removed).unwrap();
+        assert_eq!(
+            fs::read_to_string(&created).unwrap(),
+            "[Removed Associations]\ntext/plain=b.desktop;\n"
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_write_to_async() {
+        let file = DesktopFile::try_from(FILE).unwrap();
+        let mut written = vec![];
+        file.write_to_async(&mut written).await.unwrap();
+
+        assert_eq!(String::from_utf8(written).unwrap(), file.to_string());
+    }
+}
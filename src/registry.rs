@@ -0,0 +1,95 @@
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A process-global registry of values identified by name.
+///
+/// The registry is initialized with its defaults on first access, and can be extended or overridden at runtime
+/// (e.g. to load a newer list from a data file). It can be shared between threads.
+pub struct Registry<V> {
+    entries: OnceLock<RwLock<HashMap<String, V>>>,
+    defaults: fn() -> Vec<(String, V)>,
+}
+
+impl<V: Clone> Registry<V> {
+    /// Creates a registry which will be initialized with the result of `defaults`.
+    pub const fn new(defaults: fn() -> Vec<(String, V)>) -> Self {
+        Self {
+            entries: OnceLock::new(),
+            defaults,
+        }
+    }
+
+    fn lock(&self) -> &RwLock<HashMap<String, V>> {
+        self.entries
+            .get_or_init(|| RwLock::new((self.defaults)().into_iter().collect()))
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, V>> {
+        self.lock().read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, V>> {
+        self.lock().write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the value registered with this name, or `None` if there is none.
+    pub fn get(&self, name: &str) -> Option<V> {
+        self.read().get(name).cloned()
+    }
+
+    /// Returns whether a value is registered with this name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.read().contains_key(name)
+    }
+
+    /// Returns the registered names, in no particular order.
+    pub fn names(&self) -> Vec<String> {
+        self.read().keys().cloned().collect()
+    }
+
+    /// Registers a value, and returns the value it overrides, if any.
+    pub fn register(&self, name: &str, value: V) -> Option<V> {
+        self.write().insert(name.to_owned(), value)
+    }
+
+    /// Removes the value registered with this name, and returns it.
+    pub fn unregister(&self, name: &str) -> Option<V> {
+        self.write().remove(name)
+    }
+
+    /// Replaces all the registered values.
+    pub fn replace_all<I: IntoIterator<Item = (String, V)>>(&self, entries: I) {
+        *self.write() = entries.into_iter().collect();
+    }
+
+    /// Restores the default values.
+    pub fn reset(&self) {
+        self.replace_all((self.defaults)());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static REGISTRY: Registry<u8> = Registry::new(|| vec![(String::from("default"), 1)]);
+
+    #[test]
+    fn test_registry_overrides() {
+        assert_eq!(REGISTRY.get("default"), Some(1));
+
+        assert_eq!(REGISTRY.register("default", 2), Some(1));
+        assert_eq!(REGISTRY.register("custom", 3), None);
+        assert_eq!(REGISTRY.get("default"), Some(2));
+        assert!(REGISTRY.contains("custom"));
+
+        REGISTRY.replace_all([(String::from("other"), 4)]);
+        assert_eq!(REGISTRY.names(), vec![String::from("other")]);
+
+        REGISTRY.reset();
+        assert_eq!(REGISTRY.get("default"), Some(1));
+        assert!(!REGISTRY.contains("custom"));
+    }
+}
@@ -0,0 +1,711 @@
+use serde::{
+    Serialize, Serializer,
+    ser::{self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple},
+};
+
+use crate::{
+    error::SerdeError,
+    parser::{
+        FormatOptions,
+        models::{ContentEntry, DesktopFile, Entry, FileFormat, Group, MAIN_GROUP, TopLevelEntry},
+    },
+};
+
+impl ser::Error for SerdeError {
+    fn custom<T: std::fmt::Display>(message: T) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
+fn unsupported(what: &str) -> SerdeError {
+    SerdeError {
+        message: format!("{what} cannot be written in a key-file"),
+    }
+}
+
+/// Rejects the scalars which cannot be written at this level of the file.
+macro_rules! unsupported_scalars {
+    ($what:literal) => {
+        unsupported_methods! {$what;
+            serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32),
+            serialize_i64(i64), serialize_u8(u8), serialize_u16(u16), serialize_u32(u32),
+            serialize_u64(u64), serialize_f32(f32), serialize_f64(f64), serialize_bytes(&[u8]),
+            serialize_unit_struct(&'static str),
+        }
+
+        fn serialize_unit(self) -> Result<Self::Ok, SerdeError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            self,
+            _name: &'static str,
+            _index: u32,
+            _variant: &'static str,
+            _value: &T,
+        ) -> Result<Self::Ok, SerdeError> {
+            Err(unsupported($what))
+        }
+    };
+}
+
+/// Rejects the methods taking a single value.
+macro_rules! unsupported_methods {
+    ($what:literal; $($method:ident($type:ty),)*) => {
+        $(
+            fn $method(self, _v: $type) -> Result<Self::Ok, SerdeError> {
+                Err(unsupported($what))
+            }
+        )*
+    };
+}
+
+/// Rejects the sequences.
+macro_rules! unsupported_lists {
+    ($what:literal) => {
+        fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+            Err(unsupported($what))
+        }
+    };
+}
+
+/// Rejects the tuple structs and the variants with data.
+macro_rules! unsupported_compounds {
+    ($what:literal) => {
+        fn serialize_tuple_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleStruct, SerdeError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _name: &'static str,
+            _index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _name: &'static str,
+            _index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStructVariant, SerdeError> {
+            Err(unsupported($what))
+        }
+    };
+}
+
+/// Rejects the structs and the maps.
+macro_rules! unsupported_maps {
+    ($what:literal) => {
+        fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStruct, SerdeError> {
+            Err(unsupported($what))
+        }
+    };
+}
+
+/// Serializes a user type as a key-file, whose fields are the groups of the file, with a final line break.
+///
+/// This is the inverse of [crate::from_str]: a group is serialized from a struct or a map whose fields are the keys of the group,
+/// such as `Name` or `Name[fr]` for a localized key. Values are escaped, sequences are written as lists followed by a separator,
+/// and the keys and groups whose value is `None` are skipped. The `[Desktop Entry]` group is written first, as required by the specification.
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct File {
+///     #[serde(rename = "Desktop Entry")]
+///     entry: Entry,
+/// }
+///
+/// #[derive(Serialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Entry {
+///     name: String,
+///     comment: Option<String>,
+///     terminal: bool,
+///     categories: Vec<String>,
+/// }
+///
+/// let file = File {
+///     entry: Entry {
+///         name: String::from("App"),
+///         comment: None,
+///         terminal: false,
+///         categories: vec![String::from("Utility")],
+///     },
+/// };
+///
+/// assert_eq!(
+///     freedesktop_rs::to_string(&file).unwrap(),
+///     "[Desktop Entry]\nName=App\nTerminal=false\nCategories=Utility;\n"
+/// );
+/// ```
+///
+/// Fails if the type is not made of groups of keys, or if a key is not valid.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    Ok(format!("{}\n", to_file(value)?))
+}
+
+/// Serializes a user type as a parsed key-file, see [to_string].
+pub fn to_file<T: Serialize + ?Sized>(value: &T) -> Result<DesktopFile, SerdeError> {
+    let mut groups = value.serialize(FileSerializer)?;
+    if let Some(position) = groups.iter().position(|g| g.header == MAIN_GROUP) {
+        let main_group = groups.remove(position);
+        groups.insert(0, main_group);
+    }
+
+    let mut file = DesktopFile {
+        content: groups.into_iter().map(TopLevelEntry::Group).collect(),
+        format: FileFormat::default(),
+    };
+    // Separates the groups with a blank line
+    file.normalize(&FormatOptions::new().unsorted_keys());
+    Ok(file)
+}
+
+/// Serializes the names of the groups and the keys, which must be strings.
+struct NameSerializer;
+
+impl Serializer for NameSerializer {
+    type Ok = String;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<String, SerdeError>;
+    type SerializeTuple = Impossible<String, SerdeError>;
+    type SerializeTupleStruct = Impossible<String, SerdeError>;
+    type SerializeTupleVariant = Impossible<String, SerdeError>;
+    type SerializeMap = Impossible<String, SerdeError>;
+    type SerializeStruct = Impossible<String, SerdeError>;
+    type SerializeStructVariant = Impossible<String, SerdeError>;
+
+    fn serialize_str(self, v: &str) -> Result<String, SerdeError> {
+        Ok(v.to_owned())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, SerdeError> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, SerdeError> {
+        value.serialize(self)
+    }
+
+    unsupported_scalars! {"A name which is not a string"}
+    unsupported_lists! {"A name which is not a string"}
+    unsupported_compounds! {"A name which is not a string"}
+    unsupported_maps! {"A name which is not a string"}
+
+    fn serialize_none(self) -> Result<String, SerdeError> {
+        Err(unsupported("A name which is not a string"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, SerdeError> {
+        Err(unsupported("A name which is not a string"))
+    }
+}
+
+/// Serializes the groups of the file from a struct or a map.
+struct FileSerializer;
+
+/// Collects the groups of the file.
+struct FileFields {
+    groups: Vec<Group>,
+    header: Option<String>,
+}
+
+impl FileFields {
+    fn add<T: Serialize + ?Sized>(&mut self, header: String, value: &T) -> Result<(), SerdeError> {
+        if let Some(content) = value.serialize(GroupSerializer)? {
+            self.groups.push(Group { header, content });
+        }
+        Ok(())
+    }
+}
+
+impl SerializeStruct for FileFields {
+    type Ok = Vec<Group>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.add(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Vec<Group>, SerdeError> {
+        Ok(self.groups)
+    }
+}
+
+impl SerializeMap for FileFields {
+    type Ok = Vec<Group>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.header = Some(key.serialize(NameSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let header = self.header.take().expect("The key is serialized first");
+        self.add(header, value)
+    }
+
+    fn end(self) -> Result<Vec<Group>, SerdeError> {
+        Ok(self.groups)
+    }
+}
+
+impl Serializer for FileSerializer {
+    type Ok = Vec<Group>;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<Vec<Group>, SerdeError>;
+    type SerializeTuple = Impossible<Vec<Group>, SerdeError>;
+    type SerializeTupleStruct = Impossible<Vec<Group>, SerdeError>;
+    type SerializeTupleVariant = Impossible<Vec<Group>, SerdeError>;
+    type SerializeMap = FileFields;
+    type SerializeStruct = FileFields;
+    type SerializeStructVariant = Impossible<Vec<Group>, SerdeError>;
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<FileFields, SerdeError> {
+        Ok(FileFields {
+            groups: vec![],
+            header: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<FileFields, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Vec<Group>, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<Vec<Group>, SerdeError> {
+        Err(unsupported("A file which is not a struct or a map"))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Vec<Group>, SerdeError> {
+        Err(unsupported("A file which is not a struct or a map"))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Vec<Group>, SerdeError> {
+        Err(unsupported("A file which is not a struct or a map"))
+    }
+
+    fn serialize_none(self) -> Result<Vec<Group>, SerdeError> {
+        Err(unsupported("A file which is not a struct or a map"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<Group>, SerdeError> {
+        value.serialize(self)
+    }
+
+    unsupported_scalars! {"A file which is not a struct or a map"}
+    unsupported_lists! {"A file which is not a struct or a map"}
+    unsupported_compounds! {"A file which is not a struct or a map"}
+}
+
+/// Serializes the entries of a group from a struct or a map, or `None` to skip the group.
+struct GroupSerializer;
+
+/// Collects the entries of a group.
+struct GroupFields {
+    content: Vec<Entry>,
+    key: Option<String>,
+}
+
+impl GroupFields {
+    fn add<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), SerdeError> {
+        let entry = match value.serialize(ValueSerializer)? {
+            Some(Value::Single(value)) => ContentEntry::new(key, value)?,
+            Some(Value::List(values)) => ContentEntry::new_list(key, values)?,
+            None => return Ok(()),
+        };
+        self.content.push(Entry::Content(entry));
+        Ok(())
+    }
+}
+
+impl SerializeStruct for GroupFields {
+    type Ok = Option<Vec<Entry>>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.add(key, value)
+    }
+
+    fn end(self) -> Result<Option<Vec<Entry>>, SerdeError> {
+        Ok(Some(self.content))
+    }
+}
+
+impl SerializeMap for GroupFields {
+    type Ok = Option<Vec<Entry>>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(NameSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self.key.take().expect("The key is serialized first");
+        self.add(&key, value)
+    }
+
+    fn end(self) -> Result<Option<Vec<Entry>>, SerdeError> {
+        Ok(Some(self.content))
+    }
+}
+
+impl Serializer for GroupSerializer {
+    type Ok = Option<Vec<Entry>>;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<Option<Vec<Entry>>, SerdeError>;
+    type SerializeTuple = Impossible<Option<Vec<Entry>>, SerdeError>;
+    type SerializeTupleStruct = Impossible<Option<Vec<Entry>>, SerdeError>;
+    type SerializeTupleVariant = Impossible<Option<Vec<Entry>>, SerdeError>;
+    type SerializeMap = GroupFields;
+    type SerializeStruct = GroupFields;
+    type SerializeStructVariant = Impossible<Option<Vec<Entry>>, SerdeError>;
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<GroupFields, SerdeError> {
+        Ok(GroupFields {
+            content: vec![],
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<GroupFields, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, SerdeError> {
+        Err(unsupported("A group which is not a struct or a map"))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, SerdeError> {
+        Err(unsupported("A group which is not a struct or a map"))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, SerdeError> {
+        Err(unsupported("A group which is not a struct or a map"))
+    }
+
+    unsupported_scalars! {"A group which is not a struct or a map"}
+    unsupported_lists! {"A group which is not a struct or a map"}
+    unsupported_compounds! {"A group which is not a struct or a map"}
+}
+
+/// The values of an entry, before they are escaped.
+enum Value {
+    Single(String),
+    List(Vec<String>),
+}
+
+/// Serializes the values of an entry, or `None` to skip the entry.
+struct ValueSerializer;
+
+/// Collects the values of a list.
+struct ListValues(Vec<String>);
+
+impl ListValues {
+    fn add<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        match value.serialize(ValueSerializer)? {
+            Some(Value::Single(value)) => self.0.push(value),
+            Some(Value::List(_)) => return Err(unsupported("A list of lists")),
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl SerializeSeq for ListValues {
+    type Ok = Option<Value>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.add(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, SerdeError> {
+        Ok(Some(Value::List(self.0)))
+    }
+}
+
+impl SerializeTuple for ListValues {
+    type Ok = Option<Value>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.add(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, SerdeError> {
+        Ok(Some(Value::List(self.0)))
+    }
+}
+
+macro_rules! serialize_displayed {
+    ($($method:ident($type:ty);)*) => {
+        $(
+            fn $method(self, v: $type) -> Result<Self::Ok, SerdeError> {
+                Ok(Some(Value::Single(v.to_string())))
+            }
+        )*
+    };
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Option<Value>;
+    type Error = SerdeError;
+    type SerializeSeq = ListValues;
+    type SerializeTuple = ListValues;
+    type SerializeTupleStruct = Impossible<Option<Value>, SerdeError>;
+    type SerializeTupleVariant = Impossible<Option<Value>, SerdeError>;
+    type SerializeMap = Impossible<Option<Value>, SerdeError>;
+    type SerializeStruct = Impossible<Option<Value>, SerdeError>;
+    type SerializeStructVariant = Impossible<Option<Value>, SerdeError>;
+
+    serialize_displayed! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, SerdeError> {
+        Err(unsupported("A value of bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    /// Serializes the name of the variant, such as `Application` for `Type`.
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, SerdeError> {
+        Ok(Some(Value::Single(variant.to_owned())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        Err(unsupported("A value of an enum variant with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ListValues, SerdeError> {
+        Ok(ListValues(Vec::with_capacity(len.unwrap_or_default())))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListValues, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    unsupported_compounds! {"A value which is not a scalar or a list"}
+    unsupported_maps! {"A value which is not a scalar or a list"}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum Type {
+        Application,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Entry {
+        #[serde(rename = "Type")]
+        kind: Type,
+        name: String,
+        #[serde(rename = "Name[fr]")]
+        name_fr: Option<String>,
+        comment: Option<String>,
+        #[serde(rename = "X-Priority")]
+        priority: u8,
+        keywords: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct File {
+        #[serde(rename = "Desktop Action new")]
+        action: Option<BTreeMap<String, String>>,
+        #[serde(rename = "Desktop Entry")]
+        entry: Entry,
+    }
+
+    #[test]
+    fn test_to_string() {
+        let file = File {
+            action: Some(BTreeMap::from([(
+                String::from("Exec"),
+                String::from("app --new"),
+            )])),
+            entry: Entry {
+                kind: Type::Application,
+                name: String::from(" App\n"),
+                name_fr: Some(String::from("Appli")),
+                comment: None,
+                priority: 2,
+                keywords: vec![String::from("One"), String::from("Two;Three")],
+            },
+        };
+
+        let written = to_string(&file).unwrap();
+        assert_eq!(
+            written,
+            "[Desktop Entry]
+Type=Application
+Name=\\sApp\\n
+Name[fr]=Appli
+X-Priority=2
+Keywords=One;Two\\;Three;
+
+[Desktop Action new]
+Exec=app --new
+"
+        );
+        assert_eq!(crate::from_str::<File>(&written).unwrap(), file);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |result: Result<String, SerdeError>| result.unwrap_err().message;
+
+        assert_eq!(
+            error(to_string(&BTreeMap::from([("Group", "value")]))),
+            "A group which is not a struct or a map cannot be written in a key-file"
+        );
+        let group = |key: &str| BTreeMap::from([("Group", BTreeMap::from([(key.to_owned(), 1)]))]);
+        assert!(to_string(&group("Key[fr]")).is_ok());
+        assert_eq!(
+            error(to_string(&group("Key=1"))),
+            "Key=1 is not a valid key: unexpected character '='"
+        );
+        assert_eq!(
+            error(to_string(&BTreeMap::from([(
+                "Group",
+                BTreeMap::from([("Key", vec![vec![1]])])
+            )]))),
+            "A list of lists cannot be written in a key-file"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::Error,
+    parser::{
+        Severity,
+        models::{DesktopFile, TopLevelEntry},
+    },
+};
+
+/// A kind of files found in a corpus.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CorpusFileKind {
+    /// A `.desktop` or `.directory` file.
+    DesktopEntry,
+    /// A `.trashinfo` file.
+    TrashInfo,
+}
+
+impl CorpusFileKind {
+    /// Returns the kind of the file from its extension, if it is part of corpora.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "desktop" | "directory" => Some(Self::DesktopEntry),
+            "trashinfo" => Some(Self::TrashInfo),
+            _ => None,
+        }
+    }
+}
+
+/// The stage at which a file of a corpus failed.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CorpusStage {
+    /// The file could not be read, or is not valid UTF-8.
+    Read,
+    /// The file could not be parsed.
+    Parse,
+    /// The file was not written back as it was read.
+    RoundTrip,
+    /// The file does not follow its specification.
+    Validate,
+}
+
+impl Display for CorpusStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stage = match self {
+            Self::Read => "read",
+            Self::Parse => "parse",
+            Self::RoundTrip => "round-trip",
+            Self::Validate => "validate",
+        };
+        write!(f, "{stage}")
+    }
+}
+
+/// A file of a corpus which failed a stage.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct CorpusFailure {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The failed stage. The following stages were not run.
+    pub stage: CorpusStage,
+    /// What failed, to be included in bug reports.
+    pub message: String,
+}
+
+/// Statistics of a corpus, see [run_corpus].
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct CorpusReport {
+    /// The number of files of the corpus.
+    pub files: usize,
+    /// The number of files which could be parsed.
+    pub parsed: usize,
+    /// The number of parsed files which were written back as they were read.
+    pub round_tripped: usize,
+    /// The number of round-tripped files which follow their specification.
+    pub valid: usize,
+    /// The files which failed a stage.
+    pub failures: Vec<CorpusFailure>,
+}
+
+impl CorpusReport {
+    /// Returns whether every file passed every stage.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn check_file(&mut self, path: &Path, kind: CorpusFileKind) {
+        self.files += 1;
+        let mut fail = |stage, message: String| {
+            self.failures.push(CorpusFailure {
+                path: path.to_owned(),
+                stage,
+                message,
+            })
+        };
+
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(error) => return fail(CorpusStage::Read, error.to_string()),
+        };
+        let file = match DesktopFile::try_from(content.as_slice()) {
+            Ok(file) => file,
+            Err(error) => return fail(CorpusStage::Parse, error.to_string()),
+        };
+        self.parsed += 1;
+
+        let written = file.to_string();
+        let read = String::from_utf8_lossy(&content);
+        if written.trim_end() != read.trim_end() {
+            let line = written
+                .lines()
+                .zip(read.lines())
+                .position(|(written, read)| written != read)
+                .unwrap_or(written.lines().count().min(read.lines().count()));
+            return self.failures.push(CorpusFailure {
+                path: path.to_owned(),
+                stage: CorpusStage::RoundTrip,
+                message: format!("line {} is written differently", line + 1),
+            });
+        }
+        self.round_tripped += 1;
+
+        let validated = match kind {
+            CorpusFileKind::DesktopEntry => validate_desktop_entry(&file),
+            CorpusFileKind::TrashInfo => validate_trash_info(file),
+        };
+        match validated {
+            Ok(()) => self.valid += 1,
+            Err(error) => self.failures.push(CorpusFailure {
+                path: path.to_owned(),
+                stage: CorpusStage::Validate,
+                message: format!("{error:?}"),
+            }),
+        }
+    }
+}
+
+impl Display for CorpusReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} files: {} parsed, {} round-tripped, {} valid",
+            self.files, self.parsed, self.round_tripped, self.valid
+        )?;
+        for failure in &self.failures {
+            writeln!(
+                f,
+                "{}: {} failed: {}",
+                failure.path.display(),
+                failure.stage,
+                failure.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses, writes back and validates every desktop entry and trash info file of the directory and its subdirectories.
+///
+/// Distributions can run it against all the files of their packages, and report the failures:
+/// ```no_run
+/// let report = freedesktop_rs::testing::run_corpus("/usr/share/applications").unwrap();
+/// print!("{report}");
+/// ```
+pub fn run_corpus<P: AsRef<Path>>(dir: P) -> Result<CorpusReport, Error> {
+    let mut report = CorpusReport::default();
+    let mut dirs = vec![dir.as_ref().to_owned()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .map_err(Error::Io)?
+            .map(|entry| entry.and_then(|e| Ok((e.path(), e.file_type()?))))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::Io)?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (path, file_type) in entries {
+            // The symbolic links to directories are not followed, as they may loop
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if let Some(kind) = CorpusFileKind::from_path(&path) {
+                report.check_file(&path, kind);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Checks that the `[Desktop Entry]` group is the first one, and that the file has no error
+/// reported by [DesktopFile::validate].
+fn validate_desktop_entry(file: &DesktopFile) -> Result<(), Error> {
+    let first_group = file.content.iter().find_map(|entry| match entry {
+        TopLevelEntry::Group(group) => Some(group),
+        TopLevelEntry::Comment(_) => None,
+    });
+    if first_group.is_none_or(|group| group.header != "Desktop Entry") {
+        return Err(Error::InvalidValue(String::from(
+            "[Desktop Entry] is not the first group",
+        )));
+    }
+    match file
+        .validate()
+        .into_iter()
+        .find(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        Some(diagnostic) => Err(Error::InvalidValue(diagnostic.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the file is a trash info file, with a path and a deletion date.
+#[cfg(feature = "trash")]
+fn validate_trash_info(file: DesktopFile) -> Result<(), Error> {
+    crate::helpers::TrashFile::try_from(file).map(|_| ())
+}
+
+/// Checks that the file is a trash info file, with a path and a deletion date.
+#[cfg(not(feature = "trash"))]
+fn validate_trash_info(file: DesktopFile) -> Result<(), Error> {
+    use crate::parser::models::EntrySet;
+
+    let group = file.get("Trash Info")?;
+    group.get("Path")?;
+    group.get("DeletionDate")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_run_corpus() {
+        let dir = env::temp_dir().join(format!("freedesktop-rs-corpus-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let files = [
+            (
+                "valid.desktop",
+                "[Desktop Entry]\nType=Application\nName=App\nExec=app\n",
+            ),
+            (
+                "invalid.desktop",
+                "[Desktop Entry]\nType=Application\nName=App\nExec=app\nTerminal=yes\n",
+            ),
+            ("outside.desktop", "Name=App\n"),
+            (
+                "sub/file.trashinfo",
+                "[Trash Info]\nPath=/tmp/a\nDeletionDate=2024-01-01T10:00:00\n",
+            ),
+            ("ignored.txt", "Not a desktop file"),
+        ];
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+
+        let report = run_corpus(&dir).unwrap();
+
+        assert_eq!(
+            (
+                report.files,
+                report.parsed,
+                report.round_tripped,
+                report.valid
+            ),
+            (4, 4, 3, 2)
+        );
+        let stages: Vec<_> = report
+            .failures
+            .iter()
+            .map(|f| (f.path.file_name().unwrap().to_str().unwrap(), f.stage))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                ("invalid.desktop", CorpusStage::Validate),
+                ("outside.desktop", CorpusStage::RoundTrip)
+            ]
+        );
+        assert!(
+            report
+                .to_string()
+                .starts_with("4 files: 4 parsed, 3 round-tripped, 2 valid\n")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}